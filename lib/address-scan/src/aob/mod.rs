@@ -0,0 +1,230 @@
+//! Array-of-bytes scanning.
+//!
+//! The [`AOBS`] patterns are searched in the code sections of a game
+//! executable. The same scan generates the compiled-in base address tables
+//! and finds the addresses at runtime when the game version isn't in them.
+
+mod scans;
+
+use std::collections::BTreeMap;
+
+pub use scans::AOBS;
+
+use crate::pe::{PeFile, PeHeaders};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AobKind {
+    /// The address is where the pattern matches.
+    Direct,
+    /// The pattern matches an instruction with a 32-bit displacement at
+    /// `offset_read`, relative to the end of the instruction at `offset_end`.
+    IndirectTwice { offset_read: usize, offset_end: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aob {
+    pub name: &'static str,
+    /// Alternative patterns, in order of preference. Bytes are hex pairs and
+    /// `??` is a wildcard.
+    pub patterns: &'static [&'static str],
+    pub kind: AobKind,
+}
+
+pub const fn aob_direct(name: &'static str, patterns: &'static [&'static str]) -> Aob {
+    Aob { name, patterns, kind: AobKind::Direct }
+}
+
+pub const fn aob_indirect_twice(
+    name: &'static str,
+    patterns: &'static [&'static str],
+    offset_read: usize,
+    offset_end: usize,
+) -> Aob {
+    Aob { name, patterns, kind: AobKind::IndirectTwice { offset_read, offset_end } }
+}
+
+/// Parses a pattern like `48 8B 0D ?? ?? ?? ??`.
+pub fn parse_pattern(pattern: &str) -> Result<Vec<Option<u8>>, String> {
+    pattern
+        .split_whitespace()
+        .map(|byte| match byte {
+            "??" => Ok(None),
+            byte => u8::from_str_radix(byte, 16)
+                .map(Some)
+                .map_err(|_| format!("Invalid byte {byte:?} in pattern {pattern:?}")),
+        })
+        .collect()
+}
+
+/// Outcome of a scan. Addresses are relative to the module base.
+#[derive(Debug, Clone, Default)]
+pub struct ScanResult {
+    pub found: BTreeMap<&'static str, usize>,
+    pub missing: Vec<&'static str>,
+}
+
+/// Scans the code sections of a PE image. `sections` holds the contents of
+/// each section to scan along with its RVA.
+pub fn scan(pe: &PeHeaders, sections: &[(u32, &[u8])], aobs: &[Aob]) -> ScanResult {
+    struct Candidate {
+        aob: usize,
+        pattern: usize,
+        bytes: Vec<Option<u8>>,
+        anchor: usize,
+    }
+
+    // Index the patterns by their first non-wildcard byte, so that each
+    // position in the image is only checked against the patterns that can
+    // possibly match there.
+    let mut candidates = Vec::new();
+    let mut by_anchor: Vec<Vec<usize>> = vec![Vec::new(); 256];
+    for (aob_idx, aob) in aobs.iter().enumerate() {
+        for (pattern_idx, pattern) in aob.patterns.iter().enumerate() {
+            let Ok(bytes) = parse_pattern(pattern) else {
+                continue;
+            };
+            let Some((anchor, Some(first))) = bytes.iter().enumerate().find(|(_, b)| b.is_some())
+            else {
+                continue;
+            };
+            by_anchor[*first as usize].push(candidates.len());
+            candidates.push(Candidate { aob: aob_idx, pattern: pattern_idx, bytes, anchor });
+        }
+    }
+
+    let mut matches: Vec<Option<(u32, usize)>> = vec![None; candidates.len()];
+
+    for &(rva, data) in sections {
+        for (pos, byte) in data.iter().enumerate() {
+            for &idx in &by_anchor[*byte as usize] {
+                let candidate = &candidates[idx];
+                if matches[idx].is_some() || pos < candidate.anchor {
+                    continue;
+                }
+
+                let start = pos - candidate.anchor;
+                let Some(window) = data.get(start..start + candidate.bytes.len()) else {
+                    continue;
+                };
+
+                if window.iter().zip(&candidate.bytes).all(|(b, p)| p.is_none_or(|p| p == *b)) {
+                    matches[idx] = Some((rva, start));
+                }
+            }
+        }
+    }
+
+    let mut result = ScanResult::default();
+
+    for (aob_idx, aob) in aobs.iter().enumerate() {
+        let address = candidates
+            .iter()
+            .zip(&matches)
+            .filter(|(c, _)| c.aob == aob_idx)
+            .filter_map(|(c, m)| m.map(|m| (c.pattern, m)))
+            .min_by_key(|(pattern, _)| *pattern)
+            .and_then(|(_, (rva, start))| {
+                let data = sections.iter().find(|(r, _)| *r == rva).map(|(_, data)| *data)?;
+                resolve(aob.kind, pe, rva as usize, data, start)
+            });
+
+        match address {
+            Some(address) => {
+                result.found.insert(aob.name, address);
+            },
+            None => result.missing.push(aob.name),
+        }
+    }
+
+    result
+}
+
+/// Computes the address for a match at `start` in a section at `rva`.
+/// Indirect addresses must land inside the image.
+pub fn resolve(
+    kind: AobKind,
+    pe: &PeHeaders,
+    rva: usize,
+    data: &[u8],
+    start: usize,
+) -> Option<usize> {
+    match kind {
+        AobKind::Direct => Some(rva + start),
+        AobKind::IndirectTwice { offset_read, offset_end } => {
+            let displacement = data.get(start + offset_read..start + offset_read + 4)?;
+            let displacement = i32::from_le_bytes(displacement.try_into().ok()?);
+            let address = (rva + start + offset_end).checked_add_signed(displacement as isize)?;
+            pe.contains_rva(address).then_some(address)
+        },
+    }
+}
+
+/// Scans a game executable read from disk for the [`AOBS`] patterns.
+pub fn scan_file(file: &PeFile) -> ScanResult {
+    let sections = file
+        .headers
+        .sections
+        .iter()
+        .filter(|s| s.is_code())
+        .filter_map(|s| Some((s.virtual_address, file.section_data(s)?)))
+        .collect::<Vec<_>>();
+
+    scan(&file.headers, &sections, AOBS)
+}
+
+/// The name in [`AOBS`] equal to `name`.
+pub fn aob_name(name: &str) -> Option<&'static str> {
+    AOBS.iter().map(|aob| aob.name).find(|n| *n == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pe::fixtures::{synthetic_image, CODE, DATA};
+
+    const TEST_AOBS: &[Aob] = &[
+        aob_indirect_twice("Singleton", &["48 8B 0D ?? ?? ?? ?? 48 85 C9"], 3, 7),
+        aob_direct("Function", &["AA BB ?? DD", "?? 11 22 33"]),
+        aob_indirect_twice("Call", &["E8 ?? ?? ?? ?? 84 C0"], 1, 5),
+        aob_direct("Missing", &["DE AD BE EF"]),
+    ];
+
+    fn code() -> Vec<u8> {
+        let mut code = vec![0xccu8; 0x100];
+        // mov rcx, [rip + 0x1000 - 0x17]; test rcx, rcx
+        code[0x10..0x1a].copy_from_slice(&[0x48, 0x8b, 0x0d, 0xe9, 0x0f, 0, 0, 0x48, 0x85, 0xc9]);
+        // Both alternatives match, the first one is preferred.
+        code[0x20..0x24].copy_from_slice(&[0x00, 0x11, 0x22, 0x33]);
+        code[0x40..0x44].copy_from_slice(&[0xaa, 0xbb, 0x00, 0xdd]);
+        // call to somewhere outside of the image
+        code[0x50..0x57].copy_from_slice(&[0xe8, 0, 0, 0, 0x10, 0x84, 0xc0]);
+        code
+    }
+
+    #[test]
+    fn test_all_patterns_parse() {
+        for aob in AOBS {
+            for pattern in aob.patterns {
+                assert!(parse_pattern(pattern).is_ok(), "{}", aob.name);
+            }
+        }
+        assert_eq!(parse_pattern("4c ?? 8B").unwrap(), vec![Some(0x4c), None, Some(0x8b)]);
+        assert!(parse_pattern("4G").is_err());
+    }
+
+    #[test]
+    fn test_scan_prefers_first_pattern_and_checks_bounds() {
+        let code = code();
+        let image = synthetic_image(&[
+            (".text", 0x1000, CODE, &code),
+            (".data", 0x2000, DATA, &[0; 0x100]),
+        ]);
+        let pe = PeHeaders::parse(&image).unwrap();
+
+        let result = scan(&pe, &[(0x1000, &code)], TEST_AOBS);
+
+        assert_eq!(result.found.get("Singleton"), Some(&0x2000));
+        assert_eq!(result.found.get("Function"), Some(&0x1040));
+        assert_eq!(result.missing, vec!["Call", "Missing"]);
+    }
+}
@@ -0,0 +1,9 @@
+//! Finding the game's base addresses in its executable.
+//!
+//! This crate has no generated code and doesn't depend on `libeldenring`, so
+//! the codegen task can use it to build the tables that `libeldenring` is
+//! compiled with, and the runtime resolves addresses with the same code.
+
+pub mod aob;
+pub mod pack;
+pub mod pe;
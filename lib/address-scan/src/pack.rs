@@ -0,0 +1,247 @@
+//! Base address packs: the base addresses for one or more game builds, in a
+//! JSON file that can be updated without releasing a new binary.
+//!
+//! Each entry is keyed by the game version and, optionally, by the SHA-256
+//! of the game executable. Addresses are named like the [`AOBS`] patterns and
+//! written as hex strings:
+//!
+//! ```json
+//! {
+//!   "format": 1,
+//!   "entries": [
+//!     {
+//!       "game_version": "2.6.0",
+//!       "exe_sha256": "9f2c...",
+//!       "addresses": { "ChrDbgFlags": "0x3c4a8e8", "GameDataMan": "0x3d5df38" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! For a known version, the pack only needs the addresses that differ from
+//! the compiled-in table. For any other version it must contain all of them.
+//!
+//! Entries can also override the version-specific offsets by name, in an
+//! optional `"offsets"` object of the same shape as `"addresses"`. Offset
+//! names belong to the runtime, so only their values are checked here.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::aob;
+
+/// Name of the pack file looked up next to the tool's DLL.
+pub const ADDRESS_PACK_FILE_NAME: &str = "er_address_pack.json";
+
+/// Current version of the pack format.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressPack {
+    pub format: u32,
+    pub entries: Vec<AddressPackEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressPackEntry {
+    /// `major.minor.patch`, as in the executable's file version.
+    pub game_version: String,
+    /// Lowercase hex digest. If absent, the entry applies to any executable
+    /// with the given version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe_sha256: Option<String>,
+    pub addresses: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub offsets: BTreeMap<String, String>,
+}
+
+impl AddressPack {
+    pub fn new(entries: Vec<AddressPackEntry>) -> Self {
+        AddressPack { format: FORMAT_VERSION, entries }
+    }
+
+    /// Parses and validates a pack.
+    pub fn parse(json: &str) -> Result<AddressPack, String> {
+        let pack: AddressPack =
+            serde_json::from_str(json).map_err(|e| format!("Invalid address pack: {e}"))?;
+
+        if pack.format != FORMAT_VERSION {
+            return Err(format!("Unsupported address pack format {}", pack.format));
+        }
+
+        for entry in &pack.entries {
+            entry.version()?;
+            entry.addresses()?;
+            entry.offsets()?;
+        }
+
+        Ok(pack)
+    }
+
+    pub fn load(path: &Path) -> Result<AddressPack, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+        AddressPack::parse(&json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Address packs are always serializable")
+    }
+
+    /// The entry for the given executable. `exe_sha256` is only called if an
+    /// entry for the version is keyed by hash.
+    pub fn find(
+        &self,
+        version: (u32, u32, u32),
+        exe_sha256: impl FnOnce() -> Option<String>,
+    ) -> Option<&AddressPackEntry> {
+        let mut exe_sha256 = Some(exe_sha256);
+        let mut digest = None;
+
+        self.entries.iter().filter(|entry| entry.version() == Ok(version)).find(
+            |entry| match &entry.exe_sha256 {
+                None => true,
+                Some(expected) => {
+                    if let Some(f) = exe_sha256.take() {
+                        digest = f();
+                    }
+                    digest.as_deref().is_some_and(|d| d.eq_ignore_ascii_case(expected))
+                },
+            },
+        )
+    }
+}
+
+impl AddressPackEntry {
+    pub fn new(
+        (major, minor, patch): (u32, u32, u32),
+        exe_sha256: Option<String>,
+        addresses: &BTreeMap<&'static str, usize>,
+    ) -> Self {
+        AddressPackEntry {
+            game_version: format!("{major}.{minor}.{patch}"),
+            exe_sha256,
+            addresses: addresses
+                .iter()
+                .map(|(name, addr)| (name.to_string(), format!("{addr:#x}")))
+                .collect(),
+            offsets: BTreeMap::new(),
+        }
+    }
+
+    pub fn version(&self) -> Result<(u32, u32, u32), String> {
+        let parts = self
+            .game_version
+            .split('.')
+            .map(str::parse::<u32>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid game version {}: {e}", self.game_version))?;
+
+        match parts[..] {
+            [major, minor, patch] => Ok((major, minor, patch)),
+            _ => Err(format!("Invalid game version {}", self.game_version)),
+        }
+    }
+
+    pub fn addresses(&self) -> Result<BTreeMap<&'static str, usize>, String> {
+        self.addresses
+            .iter()
+            .map(|(name, addr)| {
+                let name =
+                    aob::aob_name(name).ok_or_else(|| format!("Unknown base address {name}"))?;
+                let addr = usize::from_str_radix(addr.trim_start_matches("0x"), 16)
+                    .map_err(|e| format!("Invalid address {addr} for {name}: {e}"))?;
+                Ok((name, addr))
+            })
+            .collect()
+    }
+
+    pub fn offsets(&self) -> Result<BTreeMap<&str, usize>, String> {
+        self.offsets
+            .iter()
+            .map(|(name, offset)| {
+                let offset = usize::from_str_radix(offset.trim_start_matches("0x"), 16)
+                    .map_err(|e| format!("Invalid offset {offset} for {name}: {e}"))?;
+                Ok((name.as_str(), offset))
+            })
+            .collect()
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Lowercase hex SHA-256 of the file at `path`.
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn pack() -> AddressPack {
+        let mut with_offsets =
+            AddressPackEntry::new((2, 6, 0), None, &BTreeMap::from([("ChrDbgFlags", 0xabc)]));
+        with_offsets.offsets.insert("player_ins".to_string(), "0x1f000".to_string());
+
+        AddressPack::new(vec![
+            with_offsets,
+            AddressPackEntry::new(
+                (9, 0, 0),
+                Some(sha256_hex(b"new exe")),
+                &BTreeMap::from([("GameDataMan", 0x1234)]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let pack = AddressPack::parse(&pack().to_json()).unwrap();
+        assert_eq!(pack.entries.len(), 2);
+        assert_eq!(pack.entries[0].addresses(), Ok(BTreeMap::from([("ChrDbgFlags", 0xabc)])));
+        assert_eq!(pack.entries[0].offsets(), Ok(BTreeMap::from([("player_ins", 0x1f000)])));
+        assert!(pack.entries[1].offsets.is_empty());
+
+        assert!(AddressPack::parse(r#"{ "format": 2, "entries": [] }"#).is_err());
+        assert!(AddressPack::parse(
+            r#"{ "format": 1, "entries": [
+                { "game_version": "2.6.0", "addresses": { "NotAnAddress": "0x10" } }
+            ] }"#
+        )
+        .is_err());
+        assert!(AddressPack::parse(
+            r#"{ "format": 1, "entries": [
+                { "game_version": "2.6.0", "addresses": {}, "offsets": { "player_ins": "zz" } }
+            ] }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_find_only_hashes_when_needed() {
+        let pack = pack();
+        let hashed = Cell::new(false);
+
+        let entry = pack.find((2, 6, 0), || {
+            hashed.set(true);
+            None
+        });
+        assert!(entry.is_some());
+        assert!(!hashed.get());
+
+        assert!(pack.find((9, 0, 0), || Some(sha256_hex(b"other exe"))).is_none());
+        assert!(pack.find((9, 0, 0), || Some(sha256_hex(b"new exe"))).is_some());
+        assert!(pack.find((9, 0, 1), || None).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+//! Minimal reader for PE images: headers, section table and version
+//! resource.
+//!
+//! Works on plain bytes, so the same code handles the running game's module
+//! and executables read from disk, on any OS.
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+const RT_VERSION: u32 = 16;
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xfeef04bd;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub pointer_to_raw_data: u32,
+    pub size_of_raw_data: u32,
+    pub characteristics: u32,
+}
+
+impl Section {
+    pub fn is_code(&self) -> bool {
+        self.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0
+    }
+
+    pub fn contains_rva(&self, rva: usize) -> bool {
+        let start = self.virtual_address as usize;
+        let size = self.virtual_size.max(self.size_of_raw_data) as usize;
+        (start..start + size).contains(&rva)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PeHeaders {
+    pub image_base: u64,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    /// RVA and size of the resource directory, if any.
+    pub resource_directory: Option<(u32, u32)>,
+    pub sections: Vec<Section>,
+}
+
+impl PeHeaders {
+    /// Parses the DOS, COFF and optional headers and the section table at
+    /// the start of `bytes`.
+    pub fn parse(bytes: &[u8]) -> Result<PeHeaders, String> {
+        if bytes.get(0..2) != Some(b"MZ") {
+            return Err("Not a PE image: missing MZ signature".to_string());
+        }
+
+        let nt = u32_at(bytes, 0x3c)? as usize;
+        if bytes.get(nt..nt + 4) != Some(b"PE\0\0") {
+            return Err("Not a PE image: missing PE signature".to_string());
+        }
+
+        let coff = nt + 4;
+        let section_count = u16_at(bytes, coff + 2)? as usize;
+        let optional_header_size = u16_at(bytes, coff + 16)? as usize;
+
+        let optional = coff + 20;
+        let (image_base, data_directories) = match u16_at(bytes, optional)? {
+            0x20b => (u64_at(bytes, optional + 24)?, optional + 108),
+            0x10b => (u32_at(bytes, optional + 28)? as u64, optional + 92),
+            magic => return Err(format!("Unknown optional header magic {magic:#x}")),
+        };
+        let size_of_image = u32_at(bytes, optional + 56)?;
+        let size_of_headers = u32_at(bytes, optional + 60)?;
+
+        let data_directory_count = u32_at(bytes, data_directories)? as usize;
+        let resource_directory = if data_directory_count > IMAGE_DIRECTORY_ENTRY_RESOURCE {
+            let entry = data_directories + 4 + IMAGE_DIRECTORY_ENTRY_RESOURCE * 8;
+            let (rva, size) = (u32_at(bytes, entry)?, u32_at(bytes, entry + 4)?);
+            (rva != 0 && size != 0).then_some((rva, size))
+        } else {
+            None
+        };
+
+        let section_table = optional + optional_header_size;
+        let sections = (0..section_count)
+            .map(|i| {
+                let offset = section_table + i * 40;
+                let name = bytes.get(offset..offset + 8).ok_or("Truncated section table")?;
+                let name = name.iter().take_while(|&&c| c != 0).map(|&c| c as char).collect();
+
+                Ok(Section {
+                    name,
+                    virtual_size: u32_at(bytes, offset + 8)?,
+                    virtual_address: u32_at(bytes, offset + 12)?,
+                    size_of_raw_data: u32_at(bytes, offset + 16)?,
+                    pointer_to_raw_data: u32_at(bytes, offset + 20)?,
+                    characteristics: u32_at(bytes, offset + 36)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(PeHeaders { image_base, size_of_image, size_of_headers, resource_directory, sections })
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Whether `rva` points inside one of the image's sections.
+    pub fn contains_rva(&self, rva: usize) -> bool {
+        self.sections.iter().any(|s| s.contains_rva(rva))
+    }
+
+    /// Converts an RVA into an offset in the file on disk.
+    pub fn rva_to_file_offset(&self, rva: u32) -> Option<usize> {
+        if rva < self.size_of_headers {
+            return Some(rva as usize);
+        }
+
+        self.sections.iter().find_map(|s| {
+            let offset = rva.checked_sub(s.virtual_address)?;
+            (offset < s.size_of_raw_data).then(|| (s.pointer_to_raw_data + offset) as usize)
+        })
+    }
+}
+
+/// A PE file as stored on disk.
+#[derive(Debug)]
+pub struct PeFile<'a> {
+    bytes: &'a [u8],
+    pub headers: PeHeaders,
+}
+
+impl<'a> PeFile<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<PeFile<'a>, String> {
+        Ok(PeFile { bytes, headers: PeHeaders::parse(bytes)? })
+    }
+
+    /// The raw contents of `section` in the file.
+    pub fn section_data(&self, section: &Section) -> Option<&'a [u8]> {
+        let start = section.pointer_to_raw_data as usize;
+        let len = match section.virtual_size {
+            0 => section.size_of_raw_data,
+            size => size.min(section.size_of_raw_data),
+        };
+        self.bytes.get(start..start + len as usize)
+    }
+
+    /// The `(major, minor, patch)` file version from the version resource.
+    pub fn file_version(&self) -> Result<(u32, u32, u32), String> {
+        let (rva, size) =
+            self.headers.resource_directory.ok_or_else(|| "No resources".to_string())?;
+        let offset = self
+            .headers
+            .rva_to_file_offset(rva)
+            .ok_or_else(|| format!("Resource directory at {rva:#x} is not in the file"))?;
+        let resources = self
+            .bytes
+            .get(offset..offset + size as usize)
+            .ok_or_else(|| "Truncated resource directory".to_string())?;
+
+        version_from_resources(resources, rva)
+    }
+}
+
+/// Finds the `VS_FIXEDFILEINFO` of the first `RT_VERSION` resource.
+/// `resources` holds the resource directory, which starts at RVA `rva`.
+pub fn version_from_resources(resources: &[u8], rva: u32) -> Result<(u32, u32, u32), String> {
+    // Returns the target of the first entry in a directory, or of the entry
+    // with the given id.
+    let entry = |directory: usize, id: Option<u32>| -> Result<u32, String> {
+        let named = u16_at(resources, directory + 12)? as usize;
+        let ids = u16_at(resources, directory + 14)? as usize;
+        (0..named + ids)
+            .map(|i| directory + 16 + i * 8)
+            .find_map(|entry| {
+                let name = u32_at(resources, entry).ok()?;
+                if id.is_none_or(|id| name == id) {
+                    u32_at(resources, entry + 4).ok()
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| "No version resource".to_string())
+    };
+    let subdirectory = |target: u32| -> Result<usize, String> {
+        match target & 0x8000_0000 {
+            0 => Err("Malformed resource directory".to_string()),
+            _ => Ok((target & 0x7fff_ffff) as usize),
+        }
+    };
+
+    let names = subdirectory(entry(0, Some(RT_VERSION))?)?;
+    let languages = subdirectory(entry(names, None)?)?;
+    let data_entry = entry(languages, None)? as usize;
+
+    let data_rva = u32_at(resources, data_entry)?;
+    let data_size = u32_at(resources, data_entry + 4)? as usize;
+    let data_offset = data_rva
+        .checked_sub(rva)
+        .ok_or_else(|| "Version resource outside of the resource directory".to_string())?
+        as usize;
+    let data = resources
+        .get(data_offset..data_offset + data_size)
+        .ok_or_else(|| "Truncated version resource".to_string())?;
+
+    let fixed_file_info = (0..data.len().saturating_sub(16))
+        .step_by(4)
+        .find(|&offset| u32_at(data, offset).ok() == Some(VS_FIXEDFILEINFO_SIGNATURE))
+        .ok_or_else(|| "No VS_FIXEDFILEINFO in version resource".to_string())?;
+
+    let version_ms = u32_at(data, fixed_file_info + 8)?;
+    let version_ls = u32_at(data, fixed_file_info + 12)?;
+
+    Ok((version_ms >> 16, version_ms & 0xffff, version_ls >> 16))
+}
+
+fn le_bytes<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], String> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("Truncated PE image at {offset:#x}"))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    le_bytes(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    le_bytes(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    le_bytes(bytes, offset).map(u64::from_le_bytes)
+}
+
+/// Synthetic PE images, for tests here and in the crates using this one.
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures {
+    use super::*;
+
+    pub const CODE: u32 = 0x6000_0020;
+    pub const DATA: u32 = 0xc000_0040;
+
+    /// Builds a PE32+ image whose file layout matches its memory layout, so
+    /// it can be used both as a file and as a loaded module. Each section is
+    /// `(name, rva, characteristics, contents)`; RVAs must be ascending and
+    /// aligned to 0x1000.
+    pub fn synthetic_image(sections: &[(&str, u32, u32, &[u8])]) -> Vec<u8> {
+        let size_of_image = sections
+            .iter()
+            .map(|(_, rva, _, data)| (*rva as usize + data.len() + 0xfff) & !0xfff)
+            .max()
+            .unwrap_or(0x1000);
+        let mut image = vec![0u8; size_of_image];
+
+        let put = |image: &mut Vec<u8>, offset: usize, bytes: &[u8]| {
+            image[offset..offset + bytes.len()].copy_from_slice(bytes)
+        };
+
+        put(&mut image, 0, b"MZ");
+        put(&mut image, 0x3c, &0x80u32.to_le_bytes());
+        put(&mut image, 0x80, b"PE\0\0");
+        put(&mut image, 0x84, &0x8664u16.to_le_bytes());
+        put(&mut image, 0x86, &(sections.len() as u16).to_le_bytes());
+        put(&mut image, 0x94, &0xf0u16.to_le_bytes());
+
+        let optional = 0x98;
+        put(&mut image, optional, &0x20bu16.to_le_bytes());
+        put(&mut image, optional + 24, &0x1_4000_0000u64.to_le_bytes());
+        put(&mut image, optional + 32, &0x1000u32.to_le_bytes());
+        put(&mut image, optional + 36, &0x1000u32.to_le_bytes());
+        put(&mut image, optional + 56, &(size_of_image as u32).to_le_bytes());
+        put(&mut image, optional + 60, &0x1000u32.to_le_bytes());
+        put(&mut image, optional + 108, &16u32.to_le_bytes());
+        if let Some((_, rva, _, data)) = sections.iter().find(|(name, ..)| *name == ".rsrc") {
+            put(&mut image, optional + 112 + 16, &rva.to_le_bytes());
+            put(&mut image, optional + 112 + 20, &(data.len() as u32).to_le_bytes());
+        }
+
+        for (i, (name, rva, characteristics, data)) in sections.iter().enumerate() {
+            let header = optional + 0xf0 + i * 40;
+            let size = data.len() as u32;
+            put(&mut image, header, name.as_bytes());
+            put(&mut image, header + 8, &size.to_le_bytes());
+            put(&mut image, header + 12, &rva.to_le_bytes());
+            put(&mut image, header + 16, &size.to_le_bytes());
+            put(&mut image, header + 20, &rva.to_le_bytes());
+            put(&mut image, header + 36, &characteristics.to_le_bytes());
+            put(&mut image, *rva as usize, data);
+        }
+
+        image
+    }
+
+    /// Builds a resource directory, to be placed at `rva`, holding a single
+    /// version resource.
+    pub fn version_resources(rva: u32, (major, minor, patch): (u32, u32, u32)) -> Vec<u8> {
+        let mut rsrc = vec![0u8; 0x58];
+        let mut directory = |offset: usize, id: u32, target: u32| {
+            rsrc[offset + 14..offset + 16].copy_from_slice(&1u16.to_le_bytes());
+            rsrc[offset + 16..offset + 20].copy_from_slice(&id.to_le_bytes());
+            rsrc[offset + 20..offset + 24].copy_from_slice(&target.to_le_bytes());
+        };
+        directory(0, RT_VERSION, 0x8000_0018);
+        directory(0x18, 1, 0x8000_0030);
+        directory(0x30, 0x409, 0x48);
+
+        let mut version_info = vec![0u8; 40];
+        for (i, c) in "VS_VERSION_INFO".encode_utf16().enumerate() {
+            version_info[6 + i * 2..8 + i * 2].copy_from_slice(&c.to_le_bytes());
+        }
+        for value in [VS_FIXEDFILEINFO_SIGNATURE, 0x1_0000, major << 16 | minor, patch << 16] {
+            version_info.extend(value.to_le_bytes());
+        }
+        version_info.resize(40 + 52, 0);
+        let len = version_info.len() as u16;
+        version_info[0..2].copy_from_slice(&len.to_le_bytes());
+        version_info[2..4].copy_from_slice(&52u16.to_le_bytes());
+
+        rsrc[0x48..0x4c].copy_from_slice(&(rva + 0x58).to_le_bytes());
+        rsrc[0x4c..0x50].copy_from_slice(&(version_info.len() as u32).to_le_bytes());
+        rsrc.extend(version_info);
+        rsrc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn test_parse_sections() {
+        let image = synthetic_image(&[
+            (".text", 0x1000, CODE, &[0xcc; 0x10]),
+            (".data", 0x2000, DATA, &[0; 0x1800]),
+        ]);
+        let pe = PeHeaders::parse(&image).unwrap();
+
+        assert_eq!(pe.image_base, 0x1_4000_0000);
+        assert_eq!(pe.size_of_image, 0x4000);
+        assert_eq!(pe.sections.len(), 2);
+        assert!(pe.section(".text").unwrap().is_code());
+        assert!(!pe.section(".data").unwrap().is_code());
+        assert!(pe.contains_rva(0x37ff));
+        assert!(!pe.contains_rva(0x3800));
+        assert!(PeHeaders::parse(&image[..0x100]).is_err());
+    }
+
+    #[test]
+    fn test_file_version() {
+        let image = synthetic_image(&[
+            (".text", 0x1000, CODE, &[0xcc; 0x10]),
+            (".rsrc", 0x2000, DATA, &version_resources(0x2000, (2, 6, 0))),
+        ]);
+
+        let file = PeFile::parse(&image).unwrap();
+        assert_eq!(file.file_version(), Ok((2, 6, 0)));
+
+        let image = synthetic_image(&[(".text", 0x1000, CODE, &[0xcc; 0x10])]);
+        assert!(PeFile::parse(&image).unwrap().file_version().is_err());
+    }
+}
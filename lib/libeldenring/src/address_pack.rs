@@ -0,0 +1,189 @@
+//! Base address packs: the base addresses for one or more game builds, in a
+//! JSON file that can be updated without releasing a new binary.
+//!
+//! The file format is defined in `address-scan`, so that the codegen task
+//! can write packs. This module checks the offset names and resolves entries
+//! against the compiled-in tables.
+//!
+//! Each entry is keyed by the game version and, optionally, by the SHA-256
+//! of the game executable. Addresses are named like the [`AOBS`] patterns and
+//! written as hex strings:
+//!
+//! ```json
+//! {
+//!   "format": 1,
+//!   "entries": [
+//!     {
+//!       "game_version": "2.6.0",
+//!       "exe_sha256": "9f2c...",
+//!       "addresses": { "ChrDbgFlags": "0x3c4a8e8", "GameDataMan": "0x3d5df38" }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! For a known version, the pack only needs the addresses that differ from
+//! the compiled-in table. For any other version it must contain all of them.
+//!
+//! Entries can also override the version-specific [`Offsets`] by name, in an
+//! optional `"offsets"` object of the same shape as `"addresses"`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub use address_scan::pack::*;
+
+use crate::aob::{self, AOBS};
+use crate::codegen::base_addresses::BaseAddresses;
+use crate::offsets::{self, Offsets};
+use crate::version::Version;
+
+/// Base addresses and offsets for an executable, after applying a pack entry.
+#[derive(Debug)]
+pub struct ResolvedEntry {
+    pub addresses: BTreeMap<&'static str, usize>,
+    pub offsets: Offsets,
+}
+
+/// Parses and validates a pack, offset names included.
+pub fn parse(json: &str) -> Result<AddressPack, String> {
+    let pack = AddressPack::parse(json)?;
+
+    for entry in &pack.entries {
+        entry_offsets(entry)?;
+    }
+
+    Ok(pack)
+}
+
+pub fn load(path: &Path) -> Result<AddressPack, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+    parse(&json)
+}
+
+/// The offset overrides of `entry`, keyed by their name in [`Offsets`].
+pub fn entry_offsets(entry: &AddressPackEntry) -> Result<BTreeMap<&'static str, usize>, String> {
+    entry
+        .offsets()?
+        .into_iter()
+        .map(|(name, offset)| {
+            let name =
+                offsets::offset_name(name).ok_or_else(|| format!("Unknown offset {name}"))?;
+            Ok((name, offset))
+        })
+        .collect()
+}
+
+/// The complete set of base addresses and offsets for the given executable,
+/// or `None` if no entry matches. Addresses missing from the entry are taken
+/// from the compiled-in table, if the version is known.
+pub fn resolve(
+    pack: &AddressPack,
+    version: (u32, u32, u32),
+    exe_sha256: impl FnOnce() -> Option<String>,
+) -> Option<Result<ResolvedEntry, String>> {
+    let entry = pack.find(version, exe_sha256)?;
+
+    let mut addresses = match Version::try_from(version) {
+        Ok(version) => aob::base_addresses_to_map(&BaseAddresses::from(version)),
+        Err(()) => BTreeMap::new(),
+    };
+
+    Some(entry.addresses().and_then(|overrides| {
+        addresses.extend(overrides);
+        let missing = AOBS
+            .iter()
+            .filter(|aob| !addresses.contains_key(aob.name))
+            .map(|aob| aob.name)
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            return Err(format!("Address pack is missing {}", missing.join(", ")));
+        }
+
+        let offsets = Offsets::resolve(version, &entry_offsets(entry)?)?;
+        Ok(ResolvedEntry { addresses, offsets })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn pack() -> AddressPack {
+        let mut full = aob::base_addresses_to_map(&BaseAddresses::from(Version::V2_06_0));
+        full.insert("GameDataMan", 0x1234);
+
+        let mut with_offsets =
+            AddressPackEntry::new((2, 6, 0), None, &BTreeMap::from([("ChrDbgFlags", 0xabc)]));
+        with_offsets.offsets.insert("player_ins".to_string(), "0x1f000".to_string());
+
+        AddressPack::new(vec![
+            with_offsets,
+            AddressPackEntry::new((9, 0, 0), Some(sha256_hex(b"new exe")), &full),
+            AddressPackEntry::new((9, 0, 1), None, &BTreeMap::from([("ChrDbgFlags", 0xabc)])),
+        ])
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let pack = parse(&pack().to_json()).unwrap();
+        assert_eq!(pack.entries.len(), 3);
+        assert_eq!(pack.entries[0].addresses(), Ok(BTreeMap::from([("ChrDbgFlags", 0xabc)])));
+
+        assert!(parse(r#"{ "format": 2, "entries": [] }"#).is_err());
+        assert_eq!(entry_offsets(&pack.entries[0]), Ok(BTreeMap::from([("player_ins", 0x1f000)])));
+        assert!(pack.entries[1].offsets.is_empty());
+
+        assert!(parse(
+            r#"{ "format": 1, "entries": [
+                { "game_version": "2.6.0", "addresses": { "NotAnAddress": "0x10" } }
+            ] }"#
+        )
+        .is_err());
+        assert!(parse(
+            r#"{ "format": 1, "entries": [
+                { "game_version": "2.6.0", "addresses": {}, "offsets": { "nope": "0x10" } }
+            ] }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_resolve_overrides_builtin_table() {
+        let pack = pack();
+        let hashed = Cell::new(false);
+
+        let resolved = resolve(&pack, (2, 6, 0), || {
+            hashed.set(true);
+            None
+        })
+        .unwrap()
+        .unwrap();
+        let base_addresses = aob::base_addresses_from_map(&resolved.addresses);
+        let builtin = BaseAddresses::from(Version::V2_06_0);
+
+        assert!(!hashed.get());
+        assert_eq!(base_addresses.chr_dbg_flags, 0xabc);
+        assert_eq!(base_addresses.game_data_man, builtin.game_data_man);
+        assert_eq!(resolved.offsets.player_ins, 0x1f000);
+        assert_eq!(resolved.offsets.map_id, 0x6d0);
+    }
+
+    #[test]
+    fn test_resolve_unknown_version() {
+        let pack = pack();
+
+        assert!(resolve(&pack, (9, 0, 0), || Some(sha256_hex(b"other exe"))).is_none());
+        let resolved = resolve(&pack, (9, 0, 0), || Some(sha256_hex(b"new exe"))).unwrap().unwrap();
+        assert_eq!(resolved.addresses["GameDataMan"], 0x1234);
+        assert_eq!(resolved.offsets.torrent_enemy_ins, 0x1cc90);
+
+        // Incomplete entries for unknown versions are rejected.
+        assert!(resolve(&pack, (9, 0, 1), || None).unwrap().is_err());
+        assert!(resolve(&pack, (9, 0, 2), || None).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Runtime array-of-bytes scanning.
+//!
+//! When the game version isn't in the compiled-in tables, the [`AOBS`]
+//! patterns are searched in the code sections of the game module to build a
+//! [`BaseAddresses`] on the fly. Addresses obtained this way haven't been
+//! checked against a known build, so they are reported as unverified.
+//!
+//! The patterns and the scanner live in `address-scan`, which the codegen
+//! task uses to build those same tables.
+
+use std::collections::BTreeMap;
+
+pub use address_scan::aob::*;
+
+use crate::codegen::base_addresses::BaseAddresses;
+use crate::memory::MemorySource;
+use crate::pe::PeHeaders;
+
+/// Size of the chunks in which sections are read from a memory source.
+/// Unreadable chunks are left zeroed rather than failing the whole scan.
+const READ_CHUNK_SIZE: usize = 0x10000;
+
+macro_rules! base_address_names {
+    ($(($field:ident, $name:expr),)*) => {
+        /// Builds a [`BaseAddresses`] from addresses keyed by their name in
+        /// [`AOBS`]. Missing addresses are zero.
+        pub fn base_addresses_from_map(map: &BTreeMap<&'static str, usize>) -> BaseAddresses {
+            BaseAddresses { $($field: map.get($name).copied().unwrap_or(0),)* }
+        }
+
+        /// Names of the [`BaseAddresses`] fields.
+        pub const BASE_ADDRESS_FIELDS: &[&str] = &[$(stringify!($field),)*];
+
+        /// The address in the [`BaseAddresses`] field named `field`.
+        pub fn base_address(base_addresses: &BaseAddresses, field: &str) -> Option<usize> {
+            match field {
+                $(stringify!($field) => Some(base_addresses.$field),)*
+                _ => None,
+            }
+        }
+
+        /// The addresses in `base_addresses`, keyed by their name in [`AOBS`].
+        pub fn base_addresses_to_map(
+            base_addresses: &BaseAddresses,
+        ) -> BTreeMap<&'static str, usize> {
+            BTreeMap::from([$(($name, base_addresses.$field),)*])
+        }
+    };
+}
+
+base_address_names!(
+    (bullet_man, "BulletMan"),
+    (chr_dbg_flags, "ChrDbgFlags"),
+    (csfd4_virtual_memory_flag, "CSFD4VirtualMemoryFlag"),
+    (cs_flipper, "CSFlipper"),
+    (cs_lua_event_manager, "CSLuaEventManager"),
+    (cs_menu_man, "CSMenuMan"),
+    (cs_menu_man_imp, "CSMenuManImp"),
+    (cs_net_man, "CSNetMan"),
+    (cs_regulation_manager, "CSRegulationManager"),
+    (cs_session_manager, "CSSessionManager"),
+    (damage_ctrl, "DamageCtrl"),
+    (field_area, "FieldArea"),
+    (game_data_man, "GameDataMan"),
+    (game_man, "GameMan"),
+    (global_pos, "GlobalPos"),
+    (group_mask, "GroupMask"),
+    (hit_ins, "HitIns"),
+    (hit_ins_hitbox_offset, "HitInsHitboxOffset"),
+    (map_item_man, "MapItemMan"),
+    (menu_man_ins, "MenuManIns"),
+    (msg_repository, "MsgRepository"),
+    (solo_param_repository, "SoloParamRepository"),
+    (world_chr_man, "WorldChrMan"),
+    (world_chr_man_dbg, "WorldChrManDbg"),
+    (world_chr_man_imp, "WorldChrManImp"),
+    (func_item_spawn, "FuncItemSpawn"),
+    (func_item_inject, "FuncItemInject"),
+    (func_remove_intro_screens, "FuncRemoveIntroScreens"),
+    (func_dbg_action_force, "FuncDbgActionForce"),
+    (lua_warp, "LuaWarp"),
+    (current_target, "CurrentTarget"),
+    (func_check_graces, "FuncCheckGraces"),
+    (base_fps, "BaseFPS"),
+    (base_anim, "BaseAnim"),
+    (dbg_event_man_off, "DbgEventManOff"),
+    (event_patch1, "EventPatch1"),
+    (event_patch2, "EventPatch2"),
+    (font_patch, "FontPatch"),
+);
+
+/// Scans the game module mapped in `source` for the [`AOBS`] patterns.
+pub fn scan_module(source: &dyn MemorySource) -> Result<ScanResult, String> {
+    let base = source.module_base().ok_or_else(|| "Couldn't find the game module".to_string())?;
+
+    let mut headers = vec![0u8; 0x1000];
+    source.read(base, &mut headers).ok_or_else(|| "Couldn't read the PE headers".to_string())?;
+    let pe = PeHeaders::parse(&headers)?;
+
+    let sections = pe
+        .sections
+        .iter()
+        .filter(|s| s.is_code())
+        .map(|s| {
+            let mut data = vec![0u8; s.virtual_size as usize];
+            let start = base + s.virtual_address as usize;
+            for (i, chunk) in data.chunks_mut(READ_CHUNK_SIZE).enumerate() {
+                if source.read(start + i * READ_CHUNK_SIZE, chunk).is_none() {
+                    chunk.fill(0);
+                }
+            }
+            (s.virtual_address, data)
+        })
+        .collect::<Vec<_>>();
+
+    let sections = sections.iter().map(|(rva, data)| (*rva, data.as_slice())).collect::<Vec<_>>();
+    Ok(scan(&pe, &sections, AOBS))
+}
+
+#[cfg(test)]
+mod tests {
+    use address_scan::pe::fixtures::{synthetic_image, CODE, DATA};
+
+    use super::*;
+    use crate::memory::FakeMemory;
+
+    #[test]
+    fn test_scan_module() {
+        let mut code = vec![0xccu8; 0x100];
+        // GameDataMan: mov rax, [rip + 0x2080 - 0x1017]
+        code[0x10..0x22].copy_from_slice(&[
+            0x48, 0x8b, 0x05, 0x69, 0x10, 0, 0, 0x48, 0x85, 0xc0, 0x74, 0x05, 0x48, 0x8b, 0x40,
+            0x58, 0xc3, 0xc3,
+        ]);
+        let image = synthetic_image(&[
+            (".text", 0x1000, CODE, &code),
+            (".data", 0x2000, DATA, &[0; 0x100]),
+        ]);
+
+        let mem = FakeMemory::with_module_base(0x1_4000_0000);
+        mem.map(0x1_4000_0000, image);
+
+        let result = scan_module(&mem).unwrap();
+        assert_eq!(result.found.len(), 1);
+        assert_eq!(result.missing.len(), AOBS.len() - 1);
+
+        let base_addresses = base_addresses_from_map(&result.found);
+        assert_eq!(base_addresses.game_data_man, 0x2080);
+        assert_eq!(base_addresses.chr_dbg_flags, 0);
+    }
+}
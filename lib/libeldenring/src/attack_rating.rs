@@ -0,0 +1,589 @@
+//! Attack rating of weapons, computed from the params the way the game does.
+//!
+//! A weapon's rating for each damage type is its base damage at the upgrade
+//! level, plus a bonus from each attribute it scales with. The bonus follows
+//! the weapon's `CalcCorrectGraph` curve for that damage type. Attributes
+//! below the weapon's requirements replace the bonus with a 40% penalty.
+//!
+//! Rows are looked up by ID, so this works the same with live params and
+//! with params read from `regulation.bin`.
+
+use crate::params::{
+    AttackElementCorrectParam, CalcCorrectGraph, EquipParamWeapon, Params, ReinforceParamWeapon,
+    SpEffect,
+};
+use crate::pointers::CharacterStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Fire,
+    Lightning,
+    Holy,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 5] = [
+        DamageType::Physical,
+        DamageType::Magic,
+        DamageType::Fire,
+        DamageType::Lightning,
+        DamageType::Holy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Physical => "Physical",
+            DamageType::Magic => "Magic",
+            DamageType::Fire => "Fire",
+            DamageType::Lightning => "Lightning",
+            DamageType::Holy => "Holy",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Intelligence,
+    Faith,
+    Arcane,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 5] = [
+        Attribute::Strength,
+        Attribute::Dexterity,
+        Attribute::Intelligence,
+        Attribute::Faith,
+        Attribute::Arcane,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Attribute::Strength => "Str",
+            Attribute::Dexterity => "Dex",
+            Attribute::Intelligence => "Int",
+            Attribute::Faith => "Fai",
+            Attribute::Arcane => "Arc",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusEffect {
+    Poison,
+    Bleed,
+    Frost,
+    Sleep,
+    Madness,
+}
+
+impl StatusEffect {
+    pub const ALL: [StatusEffect; 5] = [
+        StatusEffect::Poison,
+        StatusEffect::Bleed,
+        StatusEffect::Frost,
+        StatusEffect::Sleep,
+        StatusEffect::Madness,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusEffect::Poison => "Poison",
+            StatusEffect::Bleed => "Bleed",
+            StatusEffect::Frost => "Frost",
+            StatusEffect::Sleep => "Sleep",
+            StatusEffect::Madness => "Madness",
+        }
+    }
+}
+
+/// Affinity names, by affinity number.
+pub const AFFINITIES: [&str; 13] = [
+    "Standard",
+    "Heavy",
+    "Keen",
+    "Quality",
+    "Fire",
+    "Flame Art",
+    "Lightning",
+    "Sacred",
+    "Magic",
+    "Cold",
+    "Poison",
+    "Blood",
+    "Occult",
+];
+
+/// Attribute values, in [`Attribute::ALL`] order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Attributes(pub [i32; 5]);
+
+impl Attributes {
+    pub fn get(&self, attribute: Attribute) -> i32 {
+        self.0[attribute as usize]
+    }
+}
+
+impl From<&CharacterStats> for Attributes {
+    fn from(stats: &CharacterStats) -> Self {
+        Attributes([stats.strength, stats.dexterity, stats.intelligence, stats.faith, stats.arcane])
+    }
+}
+
+/// A weapon as the game identifies it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Weapon {
+    /// ID of the standard, unupgraded weapon, e.g. `1000000`.
+    pub id: u32,
+    pub affinity: u32,
+    pub level: u32,
+    pub two_handed: bool,
+}
+
+impl Weapon {
+    /// Splits an equipped weapon's param ID. Empty slots are negative.
+    pub fn from_equip_id(id: i32) -> Option<Weapon> {
+        let id = u32::try_from(id).ok()?;
+        Some(Weapon {
+            id: id - id % 10000,
+            affinity: id / 100 % 100,
+            level: id % 100,
+            two_handed: false,
+        })
+    }
+
+    /// ID of the weapon's `EquipParamWeapon` row.
+    pub fn param_id(&self) -> u64 {
+        self.id as u64 + self.affinity as u64 * 100
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttackRating {
+    /// Base damage, in [`DamageType::ALL`] order.
+    pub base: [f32; 5],
+    /// Damage from attribute scaling, in [`DamageType::ALL`] order. Negative
+    /// when requirements aren't met.
+    pub scaling: [f32; 5],
+    /// Scaling grade, in [`Attribute::ALL`] order.
+    pub grades: [Option<char>; 5],
+    /// Whether each requirement is met, in [`Attribute::ALL`] order.
+    pub requirements_met: [bool; 5],
+    /// Status buildup, in [`StatusEffect::ALL`] order.
+    pub status: [f32; 5],
+}
+
+impl AttackRating {
+    pub fn damage(&self, damage_type: DamageType) -> f32 {
+        self.base[damage_type as usize] + self.scaling[damage_type as usize]
+    }
+
+    pub fn total(&self) -> f32 {
+        DamageType::ALL.iter().map(|&t| self.damage(t)).sum()
+    }
+}
+
+/// Computes the attack rating of a weapon with the given attributes.
+pub fn calculate(
+    params: &Params,
+    weapon: &Weapon,
+    attributes: &Attributes,
+) -> Result<AttackRating, String> {
+    let row = params
+        .row::<EquipParamWeapon>(weapon.param_id())
+        .ok_or_else(|| format!("No weapon {} with affinity {}", weapon.id, weapon.affinity))?;
+    let reinforce_id = row.reinforce_type_id as i64 + weapon.level as i64;
+    let reinforce = u64::try_from(reinforce_id)
+        .ok()
+        .and_then(|id| params.row::<ReinforceParamWeapon>(id))
+        .ok_or_else(|| format!("No upgrade level {} for weapon {}", weapon.level, weapon.id))?;
+    let element_correct = u64::try_from(row.attack_element_correct_id)
+        .ok()
+        .and_then(|id| params.row::<AttackElementCorrectParam>(id))
+        .ok_or_else(|| format!("No attack element correct {}", row.attack_element_correct_id))?;
+    let graph = |id: u8| {
+        params.row::<CalcCorrectGraph>(id as u64).ok_or_else(|| format!("No correct graph {id}"))
+    };
+
+    // Two-handing counts as 50% more strength, except for paired weapons.
+    let mut attributes = *attributes;
+    if weapon.two_handed && !row.is_dual_blade() {
+        attributes.0[0] = (attributes.0[0] * 3 / 2).min(148);
+    }
+
+    let correct = [
+        (row.correct_strength, reinforce.correct_strength_rate, row.proper_strength),
+        (row.correct_agility, reinforce.correct_agility_rate, row.proper_agility),
+        (row.correct_magic, reinforce.correct_magic_rate, row.proper_magic),
+        (row.correct_faith, reinforce.correct_faith_rate, row.proper_faith),
+        (row.correct_luck, reinforce.correct_luck_rate, row.proper_luck),
+    ];
+
+    let mut rating = AttackRating::default();
+
+    for (i, &(correct, rate, proper)) in correct.iter().enumerate() {
+        rating.grades[i] = grade(correct * rate);
+        rating.requirements_met[i] = attributes.0[i] >= proper as i32;
+    }
+
+    let damage = [
+        (row.attack_base_physics, reinforce.physics_atk_rate, row.correct_type_physics),
+        (row.attack_base_magic, reinforce.magic_atk_rate, row.correct_type_magic),
+        (row.attack_base_fire, reinforce.fire_atk_rate, row.correct_type_fire),
+        (row.attack_base_thunder, reinforce.thunder_atk_rate, row.correct_type_thunder),
+        (row.attack_base_dark, reinforce.dark_atk_rate, row.correct_type_dark),
+    ];
+
+    for (t, &(base, rate, graph_id)) in damage.iter().enumerate() {
+        let base = base as f32 * rate;
+        rating.base[t] = base;
+        if base == 0. {
+            continue;
+        }
+
+        let attrs = (0..5).filter(|&a| element_corrects(element_correct, t, a)).collect::<Vec<_>>();
+        if attrs.iter().any(|&a| !rating.requirements_met[a]) {
+            rating.scaling[t] = base * -0.4;
+            continue;
+        }
+
+        let graph = graph(graph_id)?;
+        for a in attrs {
+            let (correct, rate, _) = correct[a];
+            let (overwrite, influence) = element_correct_rates(element_correct, t, a);
+            let correct = if overwrite >= 0 { overwrite as f32 } else { correct };
+            let scaling = correct * rate * influence as f32 / 100.;
+            rating.scaling[t] +=
+                base * scaling / 100. * correct_graph(graph, attributes.0[a] as f32) / 100.;
+        }
+    }
+
+    // Status buildup comes from the weapon's special effects. All of it but
+    // frost scales with arcane.
+    let (luck_correct, luck_rate, _) = correct[Attribute::Arcane as usize];
+    let sp_effects = [
+        (row.sp_effect_behavior_id0, reinforce.sp_effect_id1),
+        (row.sp_effect_behavior_id1, reinforce.sp_effect_id2),
+        (row.sp_effect_behavior_id2, reinforce.sp_effect_id3),
+    ];
+    for (id, offset) in sp_effects {
+        let Some(sp_effect) = u64::try_from(id)
+            .ok()
+            .filter(|&id| id > 0)
+            .and_then(|id| params.row::<SpEffect>(id + offset as u64))
+        else {
+            continue;
+        };
+
+        let buildup = [
+            (sp_effect.poizon_attack_power, Some(row.correct_type_poison)),
+            (sp_effect.blood_attack_power, Some(row.correct_type_blood)),
+            (sp_effect.freeze_attack_power, None),
+            (sp_effect.sleep_attack_power, Some(row.correct_type_sleep)),
+            (sp_effect.madness_attack_power, Some(row.correct_type_madness)),
+        ];
+        for (s, (power, graph_id)) in buildup.into_iter().enumerate() {
+            let mut power = power as f32;
+            if let Some(graph_id) = graph_id.filter(|_| luck_correct > 0. && power > 0.) {
+                if rating.requirements_met[Attribute::Arcane as usize] {
+                    let arcane = attributes.get(Attribute::Arcane) as f32;
+                    let graph = correct_graph(graph(graph_id)?, arcane);
+                    power *= 1. + luck_correct * luck_rate / 100. * graph / 100.;
+                }
+            }
+            rating.status[s] += power;
+        }
+    }
+
+    Ok(rating)
+}
+
+/// Whether a damage type scales with an attribute. The flags are bits
+/// `damage_type * 5 + attribute` of the row's first four bytes; the last one
+/// isn't in the generated struct, so they are read directly.
+fn element_corrects(row: &AttackElementCorrectParam, damage_type: usize, attribute: usize) -> bool {
+    let flags = unsafe { (row as *const AttackElementCorrectParam as *const [u8; 4]).read() };
+    u32::from_le_bytes(flags) & (1 << (damage_type * 5 + attribute)) != 0
+}
+
+/// Overwritten scaling (negative if not overwritten) and influence, in
+/// percent, of an attribute on a damage type.
+fn element_correct_rates(
+    row: &AttackElementCorrectParam,
+    damage_type: usize,
+    attribute: usize,
+) -> (i16, i16) {
+    let overwrite = [
+        row.overwrite_strength_correct_rate_by_physics,
+        row.overwrite_dexterity_correct_rate_by_physics,
+        row.overwrite_magic_correct_rate_by_physics,
+        row.overwrite_faith_correct_rate_by_physics,
+        row.overwrite_luck_correct_rate_by_physics,
+        row.overwrite_strength_correct_rate_by_magic,
+        row.overwrite_dexterity_correct_rate_by_magic,
+        row.overwrite_magic_correct_rate_by_magic,
+        row.overwrite_faith_correct_rate_by_magic,
+        row.overwrite_luck_correct_rate_by_magic,
+        row.overwrite_strength_correct_rate_by_fire,
+        row.overwrite_dexterity_correct_rate_by_fire,
+        row.overwrite_magic_correct_rate_by_fire,
+        row.overwrite_faith_correct_rate_by_fire,
+        row.overwrite_luck_correct_rate_by_fire,
+        row.overwrite_strength_correct_rate_by_thunder,
+        row.overwrite_dexterity_correct_rate_by_thunder,
+        row.overwrite_magic_correct_rate_by_thunder,
+        row.overwrite_faith_correct_rate_by_thunder,
+        row.overwrite_luck_correct_rate_by_thunder,
+        row.overwrite_strength_correct_rate_by_dark,
+        row.overwrite_dexterity_correct_rate_by_dark,
+        row.overwrite_magic_correct_rate_by_dark,
+        row.overwrite_faith_correct_rate_by_dark,
+        row.overwrite_luck_correct_rate_by_dark,
+    ];
+    let influence = [
+        row.influence_strength_correct_rate_by_physics,
+        row.influence_dexterity_correct_rate_by_physics,
+        row.influence_magic_correct_rate_by_physics,
+        row.influence_faith_correct_rate_by_physics,
+        row.influence_luck_correct_rate_by_physics,
+        row.influence_strength_correct_rate_by_magic,
+        row.influence_dexterity_correct_rate_by_magic,
+        row.influence_magic_correct_rate_by_magic,
+        row.influence_faith_correct_rate_by_magic,
+        row.influence_luck_correct_rate_by_magic,
+        row.influence_strength_correct_rate_by_fire,
+        row.influence_dexterity_correct_rate_by_fire,
+        row.influence_magic_correct_rate_by_fire,
+        row.influence_faith_correct_rate_by_fire,
+        row.influence_luck_correct_rate_by_fire,
+        row.influence_strength_correct_rate_by_thunder,
+        row.influence_dexterity_correct_rate_by_thunder,
+        row.influence_magic_correct_rate_by_thunder,
+        row.influence_faith_correct_rate_by_thunder,
+        row.influence_luck_correct_rate_by_thunder,
+        row.influence_strength_correct_rate_by_dark,
+        row.influence_dexterity_correct_rate_by_dark,
+        row.influence_magic_correct_rate_by_dark,
+        row.influence_faith_correct_rate_by_dark,
+        row.influence_luck_correct_rate_by_dark,
+    ];
+    let i = damage_type * 5 + attribute;
+    (overwrite[i], influence[i])
+}
+
+/// Evaluates a scaling curve at an attribute value, in percent of the
+/// scaling. Each stage interpolates between its bounds, bent by its
+/// exponent: positive exponents curve up, negative ones curve down.
+pub fn correct_graph(graph: &CalcCorrectGraph, value: f32) -> f32 {
+    let stages = [
+        graph.stage_max_val0,
+        graph.stage_max_val1,
+        graph.stage_max_val2,
+        graph.stage_max_val3,
+        graph.stage_max_val4,
+    ];
+    let grow = [
+        graph.stage_max_grow_val0,
+        graph.stage_max_grow_val1,
+        graph.stage_max_grow_val2,
+        graph.stage_max_grow_val3,
+        graph.stage_max_grow_val4,
+    ];
+    let exponents = [
+        graph.adj_pt_max_grow_val0,
+        graph.adj_pt_max_grow_val1,
+        graph.adj_pt_max_grow_val2,
+        graph.adj_pt_max_grow_val3,
+        graph.adj_pt_max_grow_val4,
+    ];
+
+    if value <= stages[0] {
+        return grow[0];
+    }
+
+    for i in 0..4 {
+        if value > stages[i + 1] || stages[i + 1] <= stages[i] {
+            continue;
+        }
+
+        let ratio = (value - stages[i]) / (stages[i + 1] - stages[i]);
+        let e = exponents[i];
+        let ratio = if e > 0. {
+            ratio.powf(e)
+        } else if e < 0. {
+            1. - (1. - ratio).powf(-e)
+        } else {
+            ratio
+        };
+        return grow[i] + (grow[i + 1] - grow[i]) * ratio;
+    }
+
+    grow[4]
+}
+
+/// Letter grade of a scaling value, as shown in the game's menus.
+pub fn grade(scaling: f32) -> Option<char> {
+    match scaling {
+        s if s >= 175. => Some('S'),
+        s if s >= 140. => Some('A'),
+        s if s >= 90. => Some('B'),
+        s if s >= 60. => Some('C'),
+        s if s >= 25. => Some('D'),
+        s if s > 0. => Some('E'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::params::tests::param_file;
+    use crate::regulation::BinderFile;
+
+    fn bytes<T>(row: &T) -> Vec<u8> {
+        unsafe { std::slice::from_raw_parts(row as *const T as *const u8, mem::size_of::<T>()) }
+            .to_vec()
+    }
+
+    fn graph() -> CalcCorrectGraph {
+        let mut graph: CalcCorrectGraph = unsafe { mem::zeroed() };
+        graph.stage_max_val0 = 1.;
+        graph.stage_max_val1 = 18.;
+        graph.stage_max_val2 = 60.;
+        graph.stage_max_val3 = 80.;
+        graph.stage_max_val4 = 150.;
+        graph.stage_max_grow_val1 = 25.;
+        graph.stage_max_grow_val2 = 75.;
+        graph.stage_max_grow_val3 = 90.;
+        graph.stage_max_grow_val4 = 110.;
+        graph.adj_pt_max_grow_val0 = 1.2;
+        graph.adj_pt_max_grow_val1 = -1.2;
+        graph.adj_pt_max_grow_val2 = 1.;
+        graph.adj_pt_max_grow_val3 = 1.;
+        graph
+    }
+
+    /// A weapon with 100 physical damage at +0, 125 at +5, scaling D with
+    /// strength and 50 bleed.
+    fn params() -> Params {
+        let mut weapon: EquipParamWeapon = unsafe { mem::zeroed() };
+        weapon.attack_base_physics = 100;
+        weapon.correct_strength = 50.;
+        weapon.proper_strength = 10;
+        weapon.reinforce_type_id = 0;
+        weapon.attack_element_correct_id = 10000;
+        weapon.sp_effect_behavior_id0 = 6000;
+        weapon.sp_effect_behavior_id1 = -1;
+        weapon.sp_effect_behavior_id2 = -1;
+
+        let mut reinforce: ReinforceParamWeapon = unsafe { mem::zeroed() };
+        reinforce.physics_atk_rate = 1.;
+        reinforce.correct_strength_rate = 1.;
+        let mut reinforce5: ReinforceParamWeapon = unsafe { mem::zeroed() };
+        reinforce5.physics_atk_rate = 1.25;
+        reinforce5.correct_strength_rate = 1.1;
+        reinforce5.sp_effect_id1 = 5;
+
+        // Physical damage scales with strength, no scaling is overwritten and
+        // every influence is 100%.
+        let mut element_correct = bytes(&unsafe { mem::zeroed::<AttackElementCorrectParam>() });
+        element_correct[0] = 1;
+        for (i, b) in element_correct[4..104].chunks_mut(2).enumerate() {
+            b.copy_from_slice(&if i < 25 { -1i16 } else { 100 }.to_le_bytes());
+        }
+
+        let mut bleed: SpEffect = unsafe { mem::zeroed() };
+        bleed.blood_attack_power = 50;
+        let mut bleed5: SpEffect = unsafe { mem::zeroed() };
+        bleed5.blood_attack_power = 60;
+
+        let file =
+            |name: &str, data: Vec<u8>| BinderFile { id: 0, name: format!("{name}.param"), data };
+        Params::from_param_files(&[
+            file("EquipParamWeapon", param_file(&[(1000000, &bytes(&weapon))])),
+            file(
+                "ReinforceParamWeapon",
+                param_file(&[(0, &bytes(&reinforce)), (5, &bytes(&reinforce5))]),
+            ),
+            file("CalcCorrectGraph", param_file(&[(0, &bytes(&graph()))])),
+            file("AttackElementCorrectParam", param_file(&[(10000, &element_correct)])),
+            file("SpEffect", param_file(&[(6000, &bytes(&bleed)), (6005, &bytes(&bleed5))])),
+        ])
+        .unwrap()
+    }
+
+    fn attributes(strength: i32) -> Attributes {
+        Attributes([strength, 10, 10, 10, 10])
+    }
+
+    #[test]
+    fn test_correct_graph() {
+        let graph = graph();
+        assert_eq!(correct_graph(&graph, 0.), 0.);
+        assert_eq!(correct_graph(&graph, 1.), 0.);
+        assert_eq!(correct_graph(&graph, 18.), 25.);
+        assert_eq!(correct_graph(&graph, 60.), 75.);
+        assert_eq!(correct_graph(&graph, 70.), 82.5);
+        assert_eq!(correct_graph(&graph, 150.), 110.);
+        assert_eq!(correct_graph(&graph, 200.), 110.);
+        // Negative exponents make the curve grow faster early on.
+        assert!(correct_graph(&graph, 39.) > 50.);
+        assert!(correct_graph(&graph, 9.5) < 12.5);
+    }
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade(0.), None);
+        assert_eq!(grade(10.), Some('E'));
+        assert_eq!(grade(55.), Some('D'));
+        assert_eq!(grade(60.), Some('C'));
+        assert_eq!(grade(175.), Some('S'));
+    }
+
+    #[test]
+    fn test_equip_id() {
+        assert_eq!(Weapon::from_equip_id(-1), None);
+        let weapon = Weapon::from_equip_id(1001105).unwrap();
+        assert_eq!((weapon.id, weapon.affinity, weapon.level), (1000000, 11, 5));
+        assert_eq!(weapon.param_id(), 1001100);
+    }
+
+    #[test]
+    fn test_calculate() {
+        let params = params();
+        let weapon = Weapon { id: 1000000, ..Default::default() };
+
+        let rating = calculate(&params, &weapon, &attributes(60)).unwrap();
+        assert_eq!(rating.base, [100., 0., 0., 0., 0.]);
+        assert_eq!(rating.scaling, [37.5, 0., 0., 0., 0.]);
+        assert_eq!(rating.damage(DamageType::Physical), 137.5);
+        assert_eq!(rating.grades, [Some('D'), None, None, None, None]);
+        assert_eq!(rating.status, [0., 50., 0., 0., 0.]);
+
+        let upgraded = Weapon { level: 5, ..weapon };
+        let rating = calculate(&params, &upgraded, &attributes(60)).unwrap();
+        assert_eq!(rating.base[0], 125.);
+        assert_eq!(rating.scaling[0], 125. * 0.55 * 0.75);
+        assert_eq!(rating.status[StatusEffect::Bleed as usize], 60.);
+
+        // Unmet requirements are a 40% penalty.
+        let rating = calculate(&params, &weapon, &attributes(5)).unwrap();
+        assert!(!rating.requirements_met[0]);
+        assert_eq!(rating.total(), 60.);
+
+        let two_handed = Weapon { two_handed: true, ..weapon };
+        let rating = calculate(&params, &two_handed, &attributes(40)).unwrap();
+        assert_eq!(rating.total(), 137.5);
+
+        let missing = Weapon { affinity: 1, ..weapon };
+        assert!(calculate(&params, &missing, &attributes(60)).is_err());
+        let missing = Weapon { level: 3, ..weapon };
+        assert!(calculate(&params, &missing, &attributes(60)).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! Reads the game state from outside of the game process.
+//!
+//! Attaches to Elden Ring running under Proton through `/proc/<pid>/mem`.
+//! Usage:
+//!
+//! ```text
+//! inspect [--pid <pid>] [--version <major>.<minor>.<patch>] [--scan] [--params]
+//! inspect --regulation <path>
+//! ```
+//!
+//! The game version is read from the running executable unless `--version`
+//! is given. `--scan` looks up the base addresses by scanning the game module
+//! instead of using the tables for the version. `--regulation` lists the
+//! params in a `regulation.bin`, or in the one in a game folder, without
+//! attaching to the game.
+
+#[cfg(target_os = "linux")]
+fn main() {
+    if let Err(e) = inspect::run() {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("The inspector is only supported on Linux");
+    std::process::exit(1);
+}
+
+#[cfg(target_os = "linux")]
+mod inspect {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use libeldenring::aob::{self, AOBS};
+    use libeldenring::memory::{self, ProcessMemory, GAME_EXECUTABLE};
+    use libeldenring::prelude::*;
+    use libeldenring::{pe, version};
+
+    pub(super) fn run() -> Result<(), String> {
+        let mut pid = None;
+        let mut version = None;
+        let mut show_params = false;
+        let mut scan = false;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--pid" => {
+                    let value = args.next().ok_or("Missing value for --pid")?;
+                    pid = Some(value.parse::<u32>().map_err(|e| format!("Invalid pid: {e}"))?);
+                },
+                "--version" => {
+                    let value = args.next().ok_or("Missing value for --version")?;
+                    version = Some(parse_version(&value)?);
+                },
+                "--regulation" => {
+                    let value = args.next().ok_or("Missing value for --regulation")?;
+                    return print_params(&Params::load_regulation(Path::new(&value))?);
+                },
+                "--params" => show_params = true,
+                "--scan" => scan = true,
+                arg => return Err(format!("Unknown argument {arg}")),
+            }
+        }
+
+        let pid = match pid {
+            Some(pid) => pid,
+            None => ProcessMemory::find_pid(GAME_EXECUTABLE)
+                .ok_or_else(|| format!("Could not find process {GAME_EXECUTABLE}"))?,
+        };
+
+        let source = ProcessMemory::attach(pid)?;
+        let module_base = source
+            .module_base()
+            .ok_or_else(|| format!("{GAME_EXECUTABLE} is not mapped in process {pid}"))?;
+
+        let version = match version {
+            Some(version) => Some(version),
+            None => match pe::module_version(&source) {
+                Ok(exe_version) => Version::try_from(exe_version).ok().or_else(|| {
+                    let (major, minor, patch) = exe_version;
+                    println!("Unknown game version {major}.{minor}.{patch}, try --scan");
+                    None
+                }),
+                Err(e) => {
+                    println!("Couldn't read the game version: {e}");
+                    None
+                },
+            },
+        };
+        if let Some(version) = version {
+            version::set_version(version).ok();
+        }
+
+        if scan {
+            let result = aob::scan_module(&source)?;
+            println!("Scan found     {} of {} addresses", result.found.len(), AOBS.len());
+            if !result.missing.is_empty() {
+                println!("Not found      {}", result.missing.join(", "));
+            }
+            version::set_scanned_addresses(result).ok();
+        }
+
+        memory::set_default_source(Arc::new(source));
+
+        let (major, minor, patch) = version::get_version().into();
+        println!("Process        {pid}");
+        println!("Module base    {module_base:#x}");
+        println!("Game version   {major}.{minor}.{patch}");
+        if version::is_unverified() {
+            println!("Addresses      unverified");
+        }
+
+        let pointers = Pointers::new();
+
+        if let Some(igt) = pointers.igt.read() {
+            let secs = igt / 1000;
+            println!("IGT            {:02}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60);
+        }
+        if let Some([x, y, z, angle, _]) = pointers.global_position.read() {
+            println!("Position       {x:.2} {y:.2} {z:.2} ({angle:.2})");
+        }
+        if let Some(map_id) = pointers.global_position.read_map_id() {
+            println!("Map id         {map_id:08x}");
+        }
+        if let Some(runes) = pointers.runes.read() {
+            println!("Runes          {runes}");
+        }
+        if let Some(points) = pointers.character_points.read() {
+            println!(
+                "HP/FP/SP       {}/{} {}/{} {}/{}",
+                points.hp,
+                points.max_hp,
+                points.fp,
+                points.max_fp,
+                points.stamina,
+                points.max_stamina
+            );
+        }
+        if let Some(stats) = pointers.character_stats.read() {
+            println!("{stats:#?}");
+        }
+
+        if show_params {
+            println!("Reading params...");
+            print_params(&PARAMS.read())?;
+        }
+
+        Ok(())
+    }
+
+    fn print_params(params: &Params) -> Result<(), String> {
+        for name in params.keys() {
+            let rows = unsafe { params.iter_param_ids(name) }.map(|ids| ids.count());
+            println!("{name:40} {:>6} rows", rows.unwrap_or_default());
+        }
+
+        Ok(())
+    }
+
+    fn parse_version(s: &str) -> Result<Version, String> {
+        let parts = s
+            .split('.')
+            .map(|p| p.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid version {s}: {e}"))?;
+
+        match parts[..] {
+            [major, minor, patch] => Version::try_from((major, minor, patch))
+                .map_err(|_| format!("Unsupported version {s}")),
+            _ => Err(format!("Invalid version {s}")),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Hits on a target, derived from its HP, poise and status buildup.
+//!
+//! The values are sampled every frame, and any frame where HP or poise went
+//! down, or some buildup went up, counts as a hit. Regeneration and buildup
+//! decay only move values the other way, so they are never mistaken for
+//! hits. Buildup that procs within a frame resets instead of growing and
+//! isn't counted.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+pub const STATUS_NAMES: [&str; 7] =
+    ["Poison", "Rot", "Bleed", "Blight", "Frost", "Sleep", "Madness"];
+
+/// The target's state in one frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    /// Seconds since an arbitrary start.
+    pub time: f64,
+    /// Address of the target's instance.
+    pub entity: u64,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub poise: f32,
+    /// Status buildup, in [`STATUS_NAMES`] order.
+    pub buildup: [u32; 7],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hit {
+    pub time: f64,
+    pub damage: u32,
+    /// HP left after the hit.
+    pub hp: u32,
+    pub poise_damage: f32,
+    /// Buildup added, in [`STATUS_NAMES`] order.
+    pub buildup: [u32; 7],
+    /// Seconds since the previous hit, if any.
+    pub since_last_hit: Option<f64>,
+}
+
+/// Turns consecutive samples into hits.
+#[derive(Debug, Default)]
+pub struct HitDetector {
+    last: Option<Sample>,
+    last_hit_time: Option<f64>,
+}
+
+impl HitDetector {
+    /// Compares a sample with the previous one. A different entity means a
+    /// different target, which starts over, and so does a different max HP
+    /// since HP can't be compared across it.
+    pub fn sample(&mut self, sample: Sample) -> Option<Hit> {
+        let last = self
+            .last
+            .replace(sample)
+            .filter(|last| last.entity == sample.entity && last.max_hp == sample.max_hp);
+        let Some(last) = last else {
+            self.last_hit_time = None;
+            return None;
+        };
+
+        let damage = last.hp.saturating_sub(sample.hp);
+        let poise_damage = (last.poise - sample.poise).max(0.);
+        let mut buildup = [0; 7];
+        for (added, (last, current)) in
+            buildup.iter_mut().zip(last.buildup.iter().zip(sample.buildup))
+        {
+            *added = current.saturating_sub(*last);
+        }
+
+        if damage == 0 && poise_damage == 0. && buildup.iter().all(|&b| b == 0) {
+            return None;
+        }
+
+        let since_last_hit = self.last_hit_time.map(|t| sample.time - t);
+        self.last_hit_time = Some(sample.time);
+        Some(Hit {
+            time: sample.time,
+            damage,
+            hp: sample.hp,
+            poise_damage,
+            buildup,
+            since_last_hit,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = HitDetector::default();
+    }
+}
+
+/// The most recent hits.
+#[derive(Debug)]
+pub struct CombatLog {
+    detector: HitDetector,
+    hits: VecDeque<Hit>,
+    capacity: usize,
+}
+
+impl CombatLog {
+    pub fn new(capacity: usize) -> Self {
+        CombatLog { detector: HitDetector::default(), hits: VecDeque::new(), capacity }
+    }
+
+    /// Records the hit in the sample, if any, dropping the oldest one when
+    /// full.
+    pub fn sample(&mut self, sample: Sample) -> Option<&Hit> {
+        let hit = self.detector.sample(sample)?;
+        if self.hits.len() == self.capacity {
+            self.hits.pop_front();
+        }
+        self.hits.push_back(hit);
+        self.hits.back()
+    }
+
+    /// Forgets the previous sample, e.g. when the target is lost.
+    pub fn lose_target(&mut self) {
+        self.detector.reset();
+    }
+
+    pub fn clear(&mut self) {
+        self.detector.reset();
+        self.hits.clear();
+    }
+
+    /// Hits from oldest to newest.
+    pub fn hits(&self) -> impl DoubleEndedIterator<Item = &Hit> + ExactSizeIterator {
+        self.hits.iter()
+    }
+
+    pub fn to_csv(&self) -> Result<String, String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let csv_error = |e: csv::Error| format!("Couldn't write CSV: {e}");
+
+        writer
+            .write_record(
+                ["Time", "Damage", "HP", "Poise damage"]
+                    .into_iter()
+                    .chain(STATUS_NAMES)
+                    .chain(["Since last hit"]),
+            )
+            .map_err(csv_error)?;
+
+        for hit in &self.hits {
+            writer
+                .write_record(
+                    [
+                        format!("{:.3}", hit.time),
+                        hit.damage.to_string(),
+                        hit.hp.to_string(),
+                        hit.poise_damage.to_string(),
+                    ]
+                    .into_iter()
+                    .chain(hit.buildup.iter().map(u32::to_string))
+                    .chain([hit.since_last_hit.map(|t| format!("{t:.3}")).unwrap_or_default()]),
+                )
+                .map_err(csv_error)?;
+        }
+
+        let bytes = writer.into_inner().map_err(|e| format!("Couldn't write CSV: {e}"))?;
+        String::from_utf8(bytes).map_err(|e| format!("Couldn't write CSV: {e}"))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.hits).expect("Hits are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: f64, hp: u32, poise: f32, bleed: u32) -> Sample {
+        Sample { time, entity: 0x1000, hp, max_hp: 1000, poise, buildup: [0, 0, bleed, 0, 0, 0, 0] }
+    }
+
+    #[test]
+    fn test_hit_detector() {
+        let mut detector = HitDetector::default();
+        assert_eq!(detector.sample(sample(0., 1000, 50., 0)), None);
+        assert_eq!(detector.sample(sample(0.1, 1000, 50., 0)), None);
+
+        let hit = detector.sample(sample(0.2, 900, 30., 20)).unwrap();
+        assert_eq!((hit.damage, hit.hp, hit.poise_damage), (100, 900, 20.));
+        assert_eq!(hit.buildup[2], 20);
+        assert_eq!(hit.since_last_hit, None);
+
+        // Regeneration and decay aren't hits.
+        assert_eq!(detector.sample(sample(0.3, 910, 40., 15)), None);
+
+        // Buildup alone is.
+        let hit = detector.sample(sample(0.7, 910, 40., 35)).unwrap();
+        assert_eq!((hit.damage, hit.poise_damage, hit.buildup[2]), (0, 0., 20));
+        assert!((hit.since_last_hit.unwrap() - 0.5).abs() < 1e-9);
+
+        // A new target starts over.
+        let other = Sample { max_hp: 2000, ..sample(1., 500, 10., 0) };
+        assert_eq!(detector.sample(other), None);
+        let hit = detector.sample(Sample { max_hp: 2000, ..sample(1.1, 400, 10., 0) }).unwrap();
+        assert_eq!((hit.damage, hit.since_last_hit), (100, None));
+
+        // So does another entity with the same max HP and less HP left.
+        let other = Sample { entity: 0x2000, max_hp: 2000, ..sample(1.2, 300, 10., 0) };
+        assert_eq!(detector.sample(other), None);
+        let hit = detector.sample(Sample { time: 1.3, hp: 250, ..other }).unwrap();
+        assert_eq!((hit.damage, hit.since_last_hit), (50, None));
+    }
+
+    #[test]
+    fn test_combat_log() {
+        let mut log = CombatLog::new(2);
+        log.sample(sample(0., 1000, 50., 0));
+        for (i, hp) in [900, 800, 700].into_iter().enumerate() {
+            assert!(log.sample(sample(i as f64 + 1., hp, 50., 0)).is_some());
+        }
+        assert_eq!(log.hits().map(|h| h.hp).collect::<Vec<_>>(), [800, 700]);
+
+        let csv = log.to_csv().unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "Time,Damage,HP,Poise damage,Poison,Rot,Bleed,Blight,Frost,Sleep,Madness,Since \
+                 last hit"
+            )
+        );
+        assert_eq!(lines.next(), Some("2.000,100,800,0,0,0,0,0,0,0,0,1.000"));
+        assert_eq!(lines.count(), 1);
+
+        let json: serde_json::Value = serde_json::from_str(&log.to_json()).unwrap();
+        assert_eq!(json[1]["damage"], 100);
+        assert_eq!(json[1]["hp"], 700);
+
+        // Losing the target keeps the hits but not the last sample.
+        log.lose_target();
+        assert!(log.sample(sample(5., 100, 50., 0)).is_none());
+        assert_eq!(log.hits().len(), 2);
+
+        log.clear();
+        assert_eq!(log.hits().len(), 0);
+    }
+}
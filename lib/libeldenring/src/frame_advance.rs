@@ -0,0 +1,155 @@
+//! Pausing the game and stepping it one or more frames at a time, by
+//! setting the global game speed.
+//!
+//! Every method returns the speed to write, if it changed.
+//! [`FrameAdvance::tick`] has to be called once per rendered frame, before
+//! handling input, so that a step of `n` frames lets the game run for exactly
+//! `n` frames.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Running,
+    Paused,
+    /// Frames left to run before pausing again.
+    Stepping(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct FrameAdvance {
+    state: State,
+    /// The speed the game ran at before it was paused.
+    speed: f32,
+}
+
+impl Default for FrameAdvance {
+    fn default() -> Self {
+        FrameAdvance { state: State::Running, speed: 1. }
+    }
+}
+
+impl FrameAdvance {
+    /// Whether the game is paused or stepping.
+    pub fn is_paused(&self) -> bool {
+        self.state != State::Running
+    }
+
+    /// Whether the game is paused and not stepping, i.e. whether the next
+    /// frame leaves the game as it is.
+    pub fn is_frozen(&self) -> bool {
+        self.state == State::Paused
+    }
+
+    /// Frames left in the current step.
+    pub fn steps_left(&self) -> u32 {
+        match self.state {
+            State::Stepping(frames) => frames,
+            _ => 0,
+        }
+    }
+
+    /// Pauses the game, remembering the speed it ran at.
+    pub fn pause(&mut self, current_speed: f32) -> Option<f32> {
+        if self.is_paused() {
+            return None;
+        }
+        self.state = State::Paused;
+        self.speed = if current_speed > 0. { current_speed } else { 1. };
+        Some(0.)
+    }
+
+    pub fn resume(&mut self) -> Option<f32> {
+        let was_frozen = self.is_frozen();
+        if !self.is_paused() {
+            return None;
+        }
+        self.state = State::Running;
+        // Stepping already runs at the previous speed.
+        was_frozen.then_some(self.speed)
+    }
+
+    pub fn toggle(&mut self, current_speed: f32) -> Option<f32> {
+        if self.is_paused() {
+            self.resume()
+        } else {
+            self.pause(current_speed)
+        }
+    }
+
+    /// Lets a paused game run for `frames` more frames. Does nothing while
+    /// the game isn't paused.
+    pub fn step(&mut self, frames: u32) -> Option<f32> {
+        match self.state {
+            State::Running => None,
+            _ if frames == 0 => None,
+            State::Paused => {
+                self.state = State::Stepping(frames);
+                Some(self.speed)
+            },
+            State::Stepping(left) => {
+                self.state = State::Stepping(left.saturating_add(frames));
+                None
+            },
+        }
+    }
+
+    /// Counts a rendered frame, pausing again at the end of a step.
+    pub fn tick(&mut self) -> Option<f32> {
+        match self.state {
+            State::Stepping(1) => {
+                self.state = State::Paused;
+                Some(0.)
+            },
+            State::Stepping(left) => {
+                self.state = State::Stepping(left - 1);
+                None
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_and_resume() {
+        let mut fa = FrameAdvance::default();
+        assert_eq!(fa.tick(), None);
+        assert_eq!(fa.step(1), None);
+        assert_eq!(fa.resume(), None);
+
+        assert_eq!(fa.toggle(2.), Some(0.));
+        assert!(fa.is_frozen());
+        assert_eq!(fa.pause(0.), None);
+        assert_eq!(fa.toggle(0.), Some(2.));
+        assert!(!fa.is_paused());
+
+        // Pausing an already stopped game resumes at normal speed.
+        assert_eq!(fa.pause(0.), Some(0.));
+        assert_eq!(fa.resume(), Some(1.));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut fa = FrameAdvance::default();
+        fa.pause(1.);
+        assert_eq!(fa.step(0), None);
+
+        // Runs for exactly the stepped frames.
+        assert_eq!(fa.step(3), Some(1.));
+        let speeds = (0..4).map(|_| fa.tick()).collect::<Vec<_>>();
+        assert_eq!(speeds, [None, None, Some(0.), None]);
+        assert!(fa.is_frozen());
+
+        // Steps requested while stepping add up.
+        fa.step(1);
+        assert_eq!(fa.step(2), None);
+        assert_eq!(fa.steps_left(), 3);
+        assert!(fa.is_paused() && !fa.is_frozen());
+
+        // Resuming while stepping keeps the current speed.
+        assert_eq!(fa.resume(), None);
+        assert_eq!(fa.tick(), None);
+    }
+}
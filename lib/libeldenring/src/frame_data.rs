@@ -0,0 +1,195 @@
+//! Animation frame data, recorded from the animation ID and time of a
+//! character sampled every frame.
+//!
+//! A new record starts whenever the animation ID changes, or the same
+//! animation restarts (its time goes back), so chained rolls or attacks are
+//! told apart.
+
+use serde::{Deserialize, Serialize};
+
+/// One animation, from when it started to when the next one did.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnimationRecord {
+    pub animation: u32,
+    /// Frame of the recording the animation started in.
+    pub start_frame: u64,
+    /// Seconds from the start of the recording.
+    pub start_time: f64,
+    /// Frames the animation played for.
+    pub frames: u64,
+    /// Seconds the animation played for.
+    pub duration: f64,
+    /// Full length of the animation, in seconds.
+    pub length: f32,
+}
+
+/// The animation state of a character in one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationSample {
+    pub animation: u32,
+    pub time: f32,
+    pub length: f32,
+}
+
+/// Animation records of one character.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    pub name: String,
+    pub records: Vec<AnimationRecord>,
+    /// The animation still playing. Its frames and duration are updated
+    /// with every sample.
+    pub current: Option<AnimationRecord>,
+    #[serde(skip)]
+    last_time: f32,
+}
+
+impl Track {
+    pub fn new(name: &str) -> Self {
+        Track { name: name.to_string(), ..Default::default() }
+    }
+
+    /// Records a frame. `None` means the character isn't available, which
+    /// ends the current animation.
+    pub fn sample(&mut self, frame: u64, time: f64, sample: Option<AnimationSample>) {
+        let restarted = match (&self.current, sample) {
+            (Some(current), Some(sample)) => {
+                current.animation != sample.animation || sample.time < self.last_time
+            },
+            (None, None) => false,
+            _ => true,
+        };
+
+        if restarted {
+            if let Some(mut current) = self.current.take() {
+                current.frames = frame - current.start_frame;
+                current.duration = time - current.start_time;
+                self.records.push(current);
+            }
+            self.current = sample.map(|sample| AnimationRecord {
+                animation: sample.animation,
+                start_frame: frame,
+                start_time: time,
+                frames: 0,
+                duration: 0.,
+                length: sample.length,
+            });
+        } else if let Some(current) = &mut self.current {
+            current.frames = frame - current.start_frame;
+            current.duration = time - current.start_time;
+        }
+
+        self.last_time = sample.map_or(0., |sample| sample.time);
+    }
+
+    /// Finished records followed by the current one.
+    pub fn all_records(&self) -> impl DoubleEndedIterator<Item = &AnimationRecord> {
+        self.records.iter().chain(&self.current)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Track::new(&self.name);
+    }
+}
+
+/// Tracks recorded together, with a shared frame counter.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub tracks: Vec<Track>,
+    pub frames: u64,
+    pub duration: f64,
+}
+
+impl Session {
+    pub fn new(names: &[&str]) -> Self {
+        Session {
+            tracks: names.iter().map(|name| Track::new(name)).collect(),
+            frames: 0,
+            duration: 0.,
+        }
+    }
+
+    /// Records a frame with one sample per track, `time` seconds after the
+    /// start of the recording.
+    pub fn sample(&mut self, time: f64, samples: &[Option<AnimationSample>]) {
+        for (track, &sample) in self.tracks.iter_mut().zip(samples) {
+            track.sample(self.frames, time, sample);
+        }
+        self.frames += 1;
+        self.duration = time;
+    }
+
+    pub fn clear(&mut self) {
+        self.tracks.iter_mut().for_each(Track::clear);
+        self.frames = 0;
+        self.duration = 0.;
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Sessions are always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Session, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid frame data session: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anim(animation: u32, time: f32) -> Option<AnimationSample> {
+        Some(AnimationSample { animation, time, length: 1. })
+    }
+
+    fn summary(track: &Track) -> Vec<(u32, u64, u64)> {
+        track.all_records().map(|r| (r.animation, r.start_frame, r.frames)).collect()
+    }
+
+    #[test]
+    fn test_track() {
+        let mut track = Track::new("Player");
+        let samples = [
+            anim(0, 0.),
+            anim(0, 0.1),
+            anim(27000, 0.),
+            anim(27000, 0.1),
+            anim(27000, 0.2),
+            // Rolling again restarts the same animation.
+            anim(27000, 0.),
+            anim(27000, 0.1),
+            None,
+            anim(0, 0.),
+        ];
+        for (frame, sample) in samples.into_iter().enumerate() {
+            track.sample(frame as u64, frame as f64 / 60., sample);
+        }
+
+        assert_eq!(summary(&track), [(0, 0, 2), (27000, 2, 3), (27000, 5, 2), (0, 8, 0)]);
+        assert!((track.records[1].duration - 3. / 60.).abs() < 1e-9);
+        assert_eq!(track.current.as_ref().unwrap().start_frame, 8);
+
+        track.clear();
+        assert_eq!(track.name, "Player");
+        assert_eq!(summary(&track), []);
+    }
+
+    #[test]
+    fn test_session() {
+        let mut session = Session::new(&["Player", "Target"]);
+        session.sample(0., &[anim(1, 0.), None]);
+        session.sample(0.5, &[anim(1, 0.5), anim(2, 0.)]);
+        session.sample(1., &[anim(3, 0.), anim(2, 0.5)]);
+
+        assert_eq!(session.frames, 3);
+        assert_eq!(summary(&session.tracks[0]), [(1, 0, 2), (3, 2, 0)]);
+        assert_eq!(summary(&session.tracks[1]), [(2, 1, 1)]);
+
+        let loaded = Session::from_json(&session.to_json()).unwrap();
+        assert_eq!(loaded.tracks[0].records, session.tracks[0].records);
+        assert_eq!(loaded.tracks[1].current, session.tracks[1].current);
+        assert!(Session::from_json("{}").is_err());
+
+        session.clear();
+        assert_eq!((session.frames, session.tracks.len()), (0, 2));
+    }
+}
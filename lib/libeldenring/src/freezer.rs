@@ -0,0 +1,222 @@
+//! Keeps memory values locked by re-writing them at a fixed rate.
+//!
+//! Targets are added to the global [`FREEZER`] and written on a background
+//! thread started by [`start`]. The first write to wherever a chain leads goes
+//! through [`PointerChain::write`](crate::memedit::PointerChain::write), so
+//! frozen locations are recorded in the [`JOURNAL`](crate::journal::JOURNAL)
+//! like any other write; the writes on every tick after that skip it.
+//! Anything that brings the game back to its vanilla state
+//! must [`Freezer::clear`] the freezer first, or the values will be written
+//! again on the next tick.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::memedit::PointerChain;
+
+/// How often frozen values are re-written.
+pub const FREEZE_INTERVAL: Duration = Duration::from_millis(16);
+
+pub static FREEZER: Lazy<Freezer> = Lazy::new(Freezer::default);
+
+/// Identifies a frozen value, for unfreezing it later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FreezeId(u64);
+
+trait FreezeTarget: Send + Sync {
+    fn write(&self) -> Option<()>;
+}
+
+/// A frozen location, and the address it was last journaled at.
+struct Location<T> {
+    chain: PointerChain<T>,
+    journaled: AtomicUsize,
+}
+
+impl<T> Location<T> {
+    fn new(chain: PointerChain<T>) -> Self {
+        Location { chain, journaled: AtomicUsize::new(0) }
+    }
+
+    /// Journals the location only when the chain leads somewhere new, e.g.
+    /// after a loading screen.
+    fn write(&self, value: T) -> Option<()> {
+        let addr = self.chain.eval()? as usize;
+        if self.journaled.swap(addr, Ordering::Relaxed) == addr {
+            self.chain.write_untracked(value)
+        } else {
+            self.chain.write(value)
+        }
+    }
+}
+
+/// Keeps writing a fixed value.
+struct FixedValue<T> {
+    location: Location<T>,
+    value: T,
+}
+
+impl<T: Copy + Send + Sync> FreezeTarget for FixedValue<T> {
+    fn write(&self) -> Option<()> {
+        self.location.write(self.value)
+    }
+}
+
+/// Keeps copying the value from another location, e.g. HP from max HP.
+struct CopyFrom<T> {
+    location: Location<T>,
+    source: PointerChain<T>,
+}
+
+impl<T: Copy + Send + Sync> FreezeTarget for CopyFrom<T> {
+    fn write(&self) -> Option<()> {
+        self.location.write(self.source.read()?)
+    }
+}
+
+struct Frozen {
+    id: FreezeId,
+    label: String,
+    target: Box<dyn FreezeTarget>,
+}
+
+#[derive(Default)]
+pub struct Freezer {
+    frozen: Mutex<Vec<Frozen>>,
+    next_id: AtomicU64,
+}
+
+impl Freezer {
+    /// Locks the value pointed to by `chain` to `value`.
+    pub fn freeze<T: Copy + Send + Sync + 'static>(
+        &self,
+        label: &str,
+        chain: PointerChain<T>,
+        value: T,
+    ) -> FreezeId {
+        self.insert(label, Box::new(FixedValue { location: Location::new(chain), value }))
+    }
+
+    /// Locks the value pointed to by `chain` to whatever `source` holds.
+    pub fn freeze_to<T: Copy + Send + Sync + 'static>(
+        &self,
+        label: &str,
+        chain: PointerChain<T>,
+        source: PointerChain<T>,
+    ) -> FreezeId {
+        self.insert(label, Box::new(CopyFrom { location: Location::new(chain), source }))
+    }
+
+    fn insert(&self, label: &str, target: Box<dyn FreezeTarget>) -> FreezeId {
+        let id = FreezeId(self.next_id.fetch_add(1, Ordering::SeqCst));
+        target.write();
+        self.frozen.lock().push(Frozen { id, label: label.to_string(), target });
+        id
+    }
+
+    /// Stops re-writing a value. Returns `false` if it wasn't frozen, e.g.
+    /// because the freezer was cleared in the meantime.
+    pub fn unfreeze(&self, id: FreezeId) -> bool {
+        let mut frozen = self.frozen.lock();
+        let len = frozen.len();
+        frozen.retain(|f| f.id != id);
+        frozen.len() != len
+    }
+
+    pub fn is_frozen(&self, id: FreezeId) -> bool {
+        self.frozen.lock().iter().any(|f| f.id == id)
+    }
+
+    /// Labels of the frozen values, in the order they were frozen.
+    pub fn labels(&self) -> Vec<String> {
+        self.frozen.lock().iter().map(|f| f.label.clone()).collect()
+    }
+
+    /// Unfreezes everything. Returns the labels of the values that were
+    /// frozen.
+    pub fn clear(&self) -> Vec<String> {
+        self.frozen.lock().drain(..).map(|f| f.label).collect()
+    }
+
+    /// Re-writes every frozen value once. Values that can't be written, e.g.
+    /// during loading screens, are skipped until the next tick.
+    pub fn tick(&self) {
+        for f in self.frozen.lock().iter() {
+            f.target.write();
+        }
+    }
+}
+
+/// Starts ticking the global [`FREEZER`] every [`FREEZE_INTERVAL`] on a
+/// background thread. Only the first call has an effect.
+pub fn start() {
+    static STARTED: Once = Once::new();
+
+    STARTED.call_once(|| {
+        thread::spawn(|| loop {
+            FREEZER.tick();
+            thread::sleep(FREEZE_INTERVAL);
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::memory::{FakeMemory, MemorySource};
+
+    fn fixture() -> (Arc<FakeMemory>, Arc<dyn MemorySource>) {
+        let mem = Arc::new(FakeMemory::new());
+        mem.map(0x1000, [0u8; 8]);
+        let source: Arc<dyn MemorySource> = mem.clone();
+        (mem, source)
+    }
+
+    #[test]
+    fn test_freeze_rewrites_value() {
+        let (mem, source) = fixture();
+        let runes = PointerChain::<u32>::with_source(Arc::clone(&source), &[0x1000]);
+
+        let freezer = Freezer::default();
+        let id = freezer.freeze("Runes", runes.clone(), 1000);
+        assert_eq!(runes.read(), Some(1000));
+
+        mem.write(0x1000, &5u32.to_le_bytes()).unwrap();
+        freezer.tick();
+        assert_eq!(runes.read(), Some(1000));
+
+        assert!(freezer.unfreeze(id));
+        assert!(!freezer.unfreeze(id));
+        mem.write(0x1000, &5u32.to_le_bytes()).unwrap();
+        freezer.tick();
+        assert_eq!(runes.read(), Some(5));
+    }
+
+    #[test]
+    fn test_freeze_to_source() {
+        let (mem, source) = fixture();
+        let hp = PointerChain::<i32>::with_source(Arc::clone(&source), &[0x1000]);
+        let max_hp = PointerChain::<i32>::with_source(Arc::clone(&source), &[0x1004]);
+        mem.write(0x1004, &1200i32.to_le_bytes()).unwrap();
+
+        let freezer = Freezer::default();
+        let id = freezer.freeze_to("HP", hp.clone(), max_hp);
+        assert_eq!(hp.read(), Some(1200));
+
+        mem.write(0x1004, &1500i32.to_le_bytes()).unwrap();
+        freezer.tick();
+        assert_eq!(hp.read(), Some(1500));
+        assert!(freezer.is_frozen(id));
+
+        assert_eq!(freezer.clear(), vec!["HP".to_string()]);
+        assert!(!freezer.is_frozen(id));
+        assert!(freezer.labels().is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! Input history, in the style of fighting game training modes: a new entry
+//! whenever the held inputs change, with the frame it started on and how
+//! many frames it was held for.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// XInput button bits and their names.
+pub const BUTTONS: [(u16, &str); 14] = [
+    (0x1000, "A"),
+    (0x2000, "B"),
+    (0x4000, "X"),
+    (0x8000, "Y"),
+    (0x0100, "LB"),
+    (0x0200, "RB"),
+    (0x0040, "L3"),
+    (0x0080, "R3"),
+    (0x0001, "Up"),
+    (0x0002, "Down"),
+    (0x0004, "Left"),
+    (0x0008, "Right"),
+    (0x0010, "Start"),
+    (0x0020, "Back"),
+];
+
+/// Trigger value above which a trigger counts as pulled, as in XInput.
+pub const TRIGGER_THRESHOLD: u8 = 30;
+pub const LEFT_STICK_DEADZONE: i16 = 7849;
+pub const RIGHT_STICK_DEADZONE: i16 = 8689;
+
+const DIRECTIONS: [&str; 8] = ["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+
+/// The state of a gamepad, as reported by XInput.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Gamepad {
+    pub buttons: u16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    /// X and Y, with Y pointing up.
+    pub left_stick: [i16; 2],
+    pub right_stick: [i16; 2],
+}
+
+impl Gamepad {
+    pub fn is_pressed(&self, button: u16) -> bool {
+        self.buttons & button != 0
+    }
+
+    /// Names of the held buttons, pulled triggers and tilted sticks.
+    pub fn held(&self) -> Vec<String> {
+        let mut held = BUTTONS
+            .iter()
+            .filter(|(button, _)| self.is_pressed(*button))
+            .map(|(_, name)| name.to_string())
+            .collect::<Vec<_>>();
+
+        if self.left_trigger > TRIGGER_THRESHOLD {
+            held.push("LT".to_string());
+        }
+        if self.right_trigger > TRIGGER_THRESHOLD {
+            held.push("RT".to_string());
+        }
+        if let Some(direction) = stick_direction(self.left_stick, LEFT_STICK_DEADZONE) {
+            held.push(format!("LS {direction}"));
+        }
+        if let Some(direction) = stick_direction(self.right_stick, RIGHT_STICK_DEADZONE) {
+            held.push(format!("RS {direction}"));
+        }
+
+        held
+    }
+}
+
+/// The compass direction a stick is tilted towards, or `None` within the
+/// deadzone.
+pub fn stick_direction([x, y]: [i16; 2], deadzone: i16) -> Option<&'static str> {
+    let (x, y) = (x as f32, y as f32);
+    if x.hypot(y) <= deadzone as f32 {
+        return None;
+    }
+
+    let octant = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+    Some(DIRECTIONS[octant.rem_euclid(8) as usize])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub frame: u64,
+    /// Frames the inputs were held for, including the first one.
+    pub frames: u64,
+    /// Held inputs, empty for neutral.
+    pub inputs: Vec<String>,
+}
+
+/// The most recent changes in held inputs.
+#[derive(Debug)]
+pub struct InputHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+
+impl InputHistory {
+    pub fn new(capacity: usize) -> Self {
+        InputHistory { entries: VecDeque::new(), capacity }
+    }
+
+    /// Records the inputs held on a frame, dropping the oldest entry when
+    /// full.
+    pub fn sample(&mut self, frame: u64, inputs: Vec<String>) {
+        if let Some(last) = self.entries.back_mut().filter(|last| last.inputs == inputs) {
+            last.frames = frame.saturating_sub(last.frame) + 1;
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry { frame, frames: 1, inputs });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Entries from oldest to newest.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &HistoryEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_held() {
+        assert!(Gamepad::default().held().is_empty());
+
+        let gamepad = Gamepad {
+            buttons: 0x0200 | 0x1000,
+            left_trigger: 10,
+            right_trigger: 255,
+            left_stick: [20000, 20000],
+            right_stick: [-5000, 0],
+        };
+        assert_eq!(gamepad.held(), ["A", "RB", "RT", "LS NE"]);
+    }
+
+    #[test]
+    fn test_stick_direction() {
+        let direction = |x, y| stick_direction([x, y], LEFT_STICK_DEADZONE);
+        assert_eq!(direction(0, 0), None);
+        assert_eq!(direction(5000, 5000), None);
+        assert_eq!(direction(0, i16::MAX), Some("N"));
+        assert_eq!(direction(i16::MIN, 0), Some("W"));
+        assert_eq!(direction(20000, -20000), Some("SE"));
+        assert_eq!(direction(30000, -2000), Some("E"));
+    }
+
+    #[test]
+    fn test_history() {
+        let inputs = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut history = InputHistory::new(3);
+
+        history.sample(10, inputs(&[]));
+        history.sample(11, inputs(&["RB"]));
+        history.sample(12, inputs(&["RB"]));
+        history.sample(13, inputs(&["RB"]));
+        history.sample(14, inputs(&[]));
+        history.sample(20, inputs(&["RB", "LS N"]));
+
+        let entries = history.entries().map(|e| (e.frame, e.frames)).collect::<Vec<_>>();
+        assert_eq!(entries, [(11, 3), (14, 1), (20, 1)]);
+        assert_eq!(history.entries().next().unwrap().inputs, ["RB"]);
+
+        history.clear();
+        assert_eq!(history.entries().len(), 0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Gamepad inputs recorded one state per frame, and their playback.
+//!
+//! Recordings are saved as JSON, with consecutive frames holding the same
+//! state merged into runs:
+//!
+//! ```json
+//! { "runs": [{ "frames": 12, "buttons": 4096, "left_trigger": 0, ... }] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::input_history::Gamepad;
+
+/// The same state held for a number of frames.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub frames: u32,
+    #[serde(flatten)]
+    pub gamepad: Gamepad,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    runs: Vec<Run>,
+}
+
+impl Recording {
+    /// Appends the state of one frame.
+    pub fn push(&mut self, gamepad: Gamepad) {
+        match self.runs.last_mut() {
+            Some(run) if run.gamepad == gamepad => run.frames += 1,
+            _ => self.runs.push(Run { frames: 1, gamepad }),
+        }
+    }
+
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.runs.iter().map(|run| run.frames).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Recordings are always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Recording, String> {
+        let recording: Recording =
+            serde_json::from_str(json).map_err(|e| format!("Invalid input recording: {e}"))?;
+        if let Some(i) = recording.runs.iter().position(|run| run.frames == 0) {
+            return Err(format!("Invalid input recording: run {i} has no frames"));
+        }
+        Ok(recording)
+    }
+}
+
+/// Plays a recording back, one state per frame.
+#[derive(Debug)]
+pub struct Playback {
+    recording: Recording,
+    looping: bool,
+    run: usize,
+    frame_in_run: u32,
+    /// Frames played since the start of the current loop.
+    frame: u32,
+}
+
+impl Playback {
+    pub fn new(recording: Recording, looping: bool) -> Self {
+        Playback { recording, looping, run: 0, frame_in_run: 0, frame: 0 }
+    }
+
+    /// The state for the next frame, or `None` once the recording is over.
+    /// Looping playback starts over instead, unless the recording is empty.
+    pub fn next_frame(&mut self) -> Option<Gamepad> {
+        if self.run == self.recording.runs.len() {
+            if !self.looping || self.recording.is_empty() {
+                return None;
+            }
+            self.run = 0;
+            self.frame = 0;
+        }
+
+        let run = self.recording.runs[self.run];
+        self.frame_in_run += 1;
+        self.frame += 1;
+        if self.frame_in_run == run.frames {
+            self.run += 1;
+            self.frame_in_run = 0;
+        }
+
+        Some(run.gamepad)
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.recording.frames()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(buttons: u16) -> Gamepad {
+        Gamepad { buttons, ..Default::default() }
+    }
+
+    fn recording() -> Recording {
+        let mut recording = Recording::default();
+        for buttons in [0, 0, 0x2000, 0x2000, 0x2000, 0] {
+            recording.push(pad(buttons));
+        }
+        recording
+    }
+
+    #[test]
+    fn test_recording() {
+        let recording = recording();
+        let runs =
+            recording.runs().iter().map(|r| (r.frames, r.gamepad.buttons)).collect::<Vec<_>>();
+        assert_eq!(runs, [(2, 0), (3, 0x2000), (1, 0)]);
+        assert_eq!(recording.frames(), 6);
+
+        let json = recording.to_json();
+        assert_eq!(Recording::from_json(&json), Ok(recording));
+        assert!(Recording::from_json(r#"{ "runs": [{ "frames": 1 }] }"#).is_err());
+
+        let empty_run = r#"{ "runs": [{
+            "frames": 0, "buttons": 0, "left_trigger": 0, "right_trigger": 0,
+            "left_stick": [0, 0], "right_stick": [0, 0]
+        }] }"#;
+        assert_eq!(
+            Recording::from_json(empty_run),
+            Err("Invalid input recording: run 0 has no frames".to_string())
+        );
+    }
+
+    #[test]
+    fn test_playback() {
+        let mut playback = Playback::new(recording(), false);
+        let played =
+            std::iter::from_fn(|| playback.next_frame()).map(|g| g.buttons).collect::<Vec<_>>();
+        assert_eq!(played, [0, 0, 0x2000, 0x2000, 0x2000, 0]);
+        assert_eq!(playback.frame(), 6);
+        assert_eq!(playback.next_frame(), None);
+
+        let mut playback = Playback::new(recording(), true);
+        let played = (0..8).filter_map(|_| playback.next_frame()).count();
+        assert_eq!((played, playback.frame()), (8, 2));
+
+        let mut playback = Playback::new(Recording::default(), true);
+        assert_eq!(playback.next_frame(), None);
+    }
+}
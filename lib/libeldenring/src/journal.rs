@@ -0,0 +1,319 @@
+//! Keeps track of the original contents of every memory location the tool
+//! writes to, so that the game can be brought back to its vanilla state.
+//!
+//! [`PointerChain::write`](crate::memedit::PointerChain::write) records into
+//! the global [`JOURNAL`] automatically. Code that writes memory by other
+//! means, e.g. byte patches or raw param pointers, must call
+//! [`Journal::record`] before writing.
+//!
+//! Locations written through a pointer chain are usually in heap objects,
+//! like the player instance, which the game frees and reallocates on loading
+//! screens. They are recorded by chain and resolved again before use, and
+//! dropped once the chain leads somewhere else.
+
+use std::fmt::Write;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::memory::MemorySource;
+
+pub static JOURNAL: Lazy<Mutex<Journal>> = Lazy::new(|| Mutex::new(Journal::default()));
+
+/// How a recorded location was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Location {
+    /// A fixed address, e.g. in the executable image or a param.
+    Static,
+    /// The base and offsets of a pointer chain.
+    Chain(usize, Vec<usize>),
+}
+
+/// The original contents of a memory location, before the tool first wrote
+/// to it.
+#[derive(Debug)]
+struct JournalEntry {
+    source: Arc<dyn MemorySource>,
+    location: Location,
+    addr: usize,
+    original: Vec<u8>,
+    label: Option<String>,
+}
+
+impl JournalEntry {
+    fn is(&self, source: &Arc<dyn MemorySource>, location: &Location, addr: usize) -> bool {
+        source_id(&self.source) == source_id(source)
+            && self.location == *location
+            && (*location != Location::Static || self.addr == addr)
+    }
+
+    /// The address of the location, or `None` if its chain doesn't lead to
+    /// the recorded address anymore.
+    fn current_addr(&self) -> Option<usize> {
+        match &self.location {
+            Location::Static => Some(self.addr),
+            Location::Chain(base, offsets) => {
+                resolve(&self.source, *base, offsets).filter(|&addr| addr == self.addr)
+            },
+        }
+    }
+}
+
+/// A recorded memory location whose contents differ from the original.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirtyEntry {
+    pub addr: usize,
+    pub label: Option<String>,
+    pub original: Vec<u8>,
+    /// `None` if the location can't be read anymore.
+    pub current: Option<Vec<u8>>,
+}
+
+impl DirtyEntry {
+    /// Describes the entry, e.g. `No logo @ 0x140a1b2c3: 74 53 -> 90 90`.
+    /// Long byte strings are summarized by the number of changed bytes.
+    pub fn describe(&self) -> String {
+        let mut s = String::new();
+        match &self.label {
+            Some(label) => write!(s, "{label} @ {:#x}: ", self.addr),
+            None => write!(s, "{:#x}: ", self.addr),
+        }
+        .ok();
+
+        match &self.current {
+            Some(current) if self.original.len() <= 8 => {
+                write!(s, "{} -> {}", hex(&self.original), hex(current)).ok();
+            },
+            Some(current) => {
+                let changed = self.original.iter().zip(current).filter(|(a, b)| a != b).count();
+                write!(s, "{changed} of {} bytes changed", self.original.len()).ok();
+            },
+            None => s.push_str("unreadable"),
+        }
+
+        s
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Records the current contents of `len` bytes at `addr` in `source`.
+    /// Must be called before writing there. Only the first write to a given
+    /// location is recorded, so that the original contents are preserved.
+    pub fn record(
+        &mut self,
+        source: &Arc<dyn MemorySource>,
+        addr: usize,
+        len: usize,
+        label: Option<&str>,
+    ) {
+        self.insert(source, Location::Static, addr, len, label);
+    }
+
+    /// Records the current contents of `len` bytes at `addr`, which the
+    /// pointer chain made of `base` and `offsets` resolved to. If the chain
+    /// was recorded before but led elsewhere, the old entry is replaced.
+    pub fn record_chain(
+        &mut self,
+        source: &Arc<dyn MemorySource>,
+        base: usize,
+        offsets: &[usize],
+        addr: usize,
+        len: usize,
+    ) {
+        self.insert(source, Location::Chain(base, offsets.to_vec()), addr, len, None);
+    }
+
+    fn insert(
+        &mut self,
+        source: &Arc<dyn MemorySource>,
+        location: Location,
+        addr: usize,
+        len: usize,
+        label: Option<&str>,
+    ) {
+        if len == 0 {
+            return;
+        }
+
+        let existing = self
+            .entries
+            .iter()
+            .position(|entry| entry.is(source, &location, addr) && entry.original.len() == len);
+        if let Some(i) = existing {
+            if self.entries[i].addr == addr {
+                return;
+            }
+            self.entries.remove(i);
+        }
+
+        let mut original = vec![0u8; len];
+        if source.read(addr, &mut original).is_none() {
+            return;
+        }
+
+        self.entries.push(JournalEntry {
+            source: Arc::clone(source),
+            location,
+            addr,
+            original,
+            label: label.map(String::from),
+        });
+    }
+
+    /// Number of recorded locations.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Recorded locations whose current contents differ from the original.
+    /// Locations whose chain leads elsewhere are left out.
+    pub fn dirty(&self) -> Vec<DirtyEntry> {
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                let addr = entry.current_addr()?;
+                let mut current = vec![0u8; entry.original.len()];
+                let current = entry.source.read(addr, &mut current).map(|_| current);
+
+                (current.as_ref() != Some(&entry.original)).then(|| DirtyEntry {
+                    addr: entry.addr,
+                    label: entry.label.clone(),
+                    original: entry.original.clone(),
+                    current,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes back the original contents of every recorded location, most
+    /// recent first, and forgets about them. Locations whose chain leads
+    /// elsewhere are dropped without writing, as whatever they belonged to is
+    /// gone. Locations which couldn't be written are kept and their
+    /// addresses returned as an error.
+    pub fn restore_all(&mut self) -> Result<usize, Vec<usize>> {
+        let mut restored = 0;
+        let mut failed = Vec::new();
+
+        while let Some(entry) = self.entries.pop() {
+            let Some(addr) = entry.current_addr() else {
+                continue;
+            };
+            if entry.source.write(addr, &entry.original).is_some() {
+                restored += 1;
+            } else {
+                failed.push(entry);
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(restored)
+        } else {
+            let addrs = failed.iter().map(|entry| entry.addr).collect();
+            failed.reverse();
+            self.entries = failed;
+            Err(addrs)
+        }
+    }
+}
+
+/// Evaluates a pointer chain the same way as
+/// [`PointerChain::eval`](crate::memedit::PointerChain::eval).
+fn resolve(source: &Arc<dyn MemorySource>, base: usize, offsets: &[usize]) -> Option<usize> {
+    offsets
+        .iter()
+        .try_fold(base, |addr, &offset| source.read_usize(addr).map(|v| v.wrapping_add(offset)))
+}
+
+fn source_id(source: &Arc<dyn MemorySource>) -> usize {
+    Arc::as_ptr(source) as *const () as usize
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::FakeMemory;
+
+    #[test]
+    fn test_restore_keeps_first_original() {
+        let mem = Arc::new(FakeMemory::new());
+        mem.map(0x1000, [1u8, 2, 3, 4]);
+        let source: Arc<dyn MemorySource> = mem.clone();
+
+        let mut journal = Journal::default();
+        journal.record(&source, 0x1000, 2, Some("patch"));
+        mem.write(0x1000, &[9, 9]).unwrap();
+        journal.record(&source, 0x1000, 2, Some("patch"));
+        mem.write(0x1000, &[8, 8]).unwrap();
+        journal.record(&source, 0x1001, 2, None);
+        mem.write(0x1001, &[7, 7]).unwrap();
+
+        assert_eq!(journal.len(), 2);
+        let dirty = journal.dirty();
+        assert_eq!(dirty.len(), 2);
+        assert_eq!(dirty[0].describe(), "patch @ 0x1000: 01 02 -> 08 07");
+        assert_eq!(dirty[1].describe(), "0x1001: 08 03 -> 07 07");
+
+        assert_eq!(journal.restore_all(), Ok(2));
+        assert_eq!(mem.bytes(0x1000, 4), Some(vec![1, 2, 3, 4]));
+        assert!(journal.is_empty());
+        assert!(journal.dirty().is_empty());
+    }
+
+    #[test]
+    fn test_chains_are_resolved_again() {
+        let mem = Arc::new(FakeMemory::new());
+        mem.map_pointer(0x1000, 0x2000);
+        mem.map(0x2000, [1u8, 2]);
+        mem.map(0x3000, [3u8, 4]);
+        let source: Arc<dyn MemorySource> = mem.clone();
+
+        let mut journal = Journal::default();
+        journal.record_chain(&source, 0x1000, &[0], 0x2000, 2);
+        mem.write(0x2000, &[9, 9]).unwrap();
+        assert_eq!(journal.dirty().len(), 1);
+
+        // The object moved: the old entry must neither show up nor be
+        // written back over whatever lives there now.
+        mem.write(0x1000, &0x3000usize.to_le_bytes()).unwrap();
+        assert!(journal.dirty().is_empty());
+        assert_eq!(journal.restore_all(), Ok(0));
+        assert_eq!(mem.bytes(0x2000, 2), Some(vec![9, 9]));
+
+        // Writing through the chain again records the new object.
+        journal.record_chain(&source, 0x1000, &[0], 0x2000, 2);
+        journal.record_chain(&source, 0x1000, &[0], 0x3000, 2);
+        assert_eq!(journal.len(), 1);
+        mem.write(0x3000, &[7, 7]).unwrap();
+        assert_eq!(journal.restore_all(), Ok(1));
+        assert_eq!(mem.bytes(0x3000, 2), Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn test_clean_entries_are_not_dirty() {
+        let mem = Arc::new(FakeMemory::new());
+        mem.map(0x1000, [1u8, 2]);
+        let source: Arc<dyn MemorySource> = mem.clone();
+
+        let mut journal = Journal::default();
+        journal.record(&source, 0x1000, 2, None);
+        mem.write(0x1000, &[5, 5]).unwrap();
+        mem.write(0x1000, &[1, 2]).unwrap();
+
+        assert!(journal.dirty().is_empty());
+        assert_eq!(journal.len(), 1);
+    }
+}
@@ -0,0 +1,412 @@
+//! Consistency checks for characters about to go online.
+//!
+//! Edited stats and spawned items can leave a character in a state the game
+//! could never produce. Each check returns human-readable findings; an empty
+//! list means nothing looks wrong.
+
+use std::collections::HashSet;
+
+use crate::attack_rating::Weapon;
+use crate::matchmaking::{MAX_LEVEL, SOMBER_LEVELS};
+use crate::params::{
+    EquipParamAccessory, EquipParamProtector, EquipParamWeapon, Params, ReinforceParamWeapon,
+};
+use crate::pointers::{CharacterBlessings, CharacterStats, InventoryItem};
+
+/// Starting level and attributes of each class, from vigor to arcane. The
+/// level is always the sum of the attributes minus [`LEVEL_OFFSET`].
+pub const CLASSES: [(&str, i32, [i32; 8]); 10] = [
+    ("Vagabond", 9, [15, 10, 11, 14, 13, 9, 9, 7]),
+    ("Warrior", 8, [11, 12, 11, 10, 16, 10, 8, 9]),
+    ("Hero", 7, [14, 9, 12, 16, 9, 7, 8, 11]),
+    ("Bandit", 5, [10, 11, 10, 9, 13, 9, 8, 14]),
+    ("Astrologer", 6, [9, 15, 9, 8, 12, 16, 7, 9]),
+    ("Prophet", 7, [10, 14, 8, 11, 10, 7, 16, 10]),
+    ("Samurai", 9, [12, 11, 13, 12, 15, 9, 8, 8]),
+    ("Prisoner", 9, [11, 12, 11, 11, 14, 14, 6, 9]),
+    ("Confessor", 10, [10, 13, 10, 12, 12, 9, 14, 9]),
+    ("Wretch", 1, [10, 10, 10, 10, 10, 10, 10, 10]),
+];
+
+pub const LEVEL_OFFSET: i32 = 79;
+
+pub const MAX_ATTRIBUTE: i32 = 99;
+pub const MAX_RUNES: i32 = 999_999_999;
+pub const MAX_SCADUTREE_BLESSING: i8 = 20;
+pub const MAX_REVERED_SPIRIT_ASH: i8 = 10;
+
+const ATTRIBUTE_NAMES: [&str; 8] =
+    ["Vigor", "Mind", "Endurance", "Strength", "Dexterity", "Intelligence", "Faith", "Arcane"];
+
+/// What the checks look at. Missing parts are skipped.
+#[derive(Debug, Clone)]
+pub struct Character {
+    pub stats: CharacterStats,
+    pub blessings: Option<CharacterBlessings>,
+    /// Highest weapon level reached, as used for matchmaking.
+    pub weapon_level: Option<u32>,
+}
+
+fn attributes(stats: &CharacterStats) -> [i32; 8] {
+    [
+        stats.vigor,
+        stats.mind,
+        stats.endurance,
+        stats.strength,
+        stats.dexterity,
+        stats.intelligence,
+        stats.faith,
+        stats.arcane,
+    ]
+}
+
+/// Runes needed to go from a level to the next one.
+pub fn level_up_cost(level: i32) -> i64 {
+    let x = level as f64 + 81.;
+    let multiplier = ((x - 92.) * 0.02).max(0.) + 0.1;
+    (multiplier * x * x) as i64 + 1
+}
+
+/// Classes whose starting attributes are all at most the given ones.
+pub fn possible_classes(
+    stats: &CharacterStats,
+) -> impl Iterator<Item = &'static (&'static str, i32, [i32; 8])> {
+    let attributes = attributes(stats);
+    CLASSES.iter().filter(move |(_, _, start)| start.iter().zip(attributes).all(|(&s, a)| a >= s))
+}
+
+/// Checks the stats, runes and blessings.
+pub fn check(character: &Character) -> Vec<String> {
+    let mut findings = Vec::new();
+    let stats = &character.stats;
+    let attributes = attributes(stats);
+
+    for (name, value) in ATTRIBUTE_NAMES.iter().zip(attributes) {
+        if !(1..=MAX_ATTRIBUTE).contains(&value) {
+            findings.push(format!("{name} is {value}, outside 1-{MAX_ATTRIBUTE}"));
+        }
+    }
+
+    let expected_level = attributes.iter().sum::<i32>() - LEVEL_OFFSET;
+    if stats.level != expected_level {
+        findings.push(format!(
+            "Level is {}, but the attributes add up to level {expected_level}",
+            stats.level
+        ));
+    }
+    if !(1..=MAX_LEVEL).contains(&stats.level) {
+        findings.push(format!("Level {} is outside 1-{MAX_LEVEL}", stats.level));
+    }
+
+    // The cheapest way to the current level is from the highest starting
+    // level of the classes that fit.
+    let start = possible_classes(stats).map(|&(_, level, _)| level).max();
+    match start {
+        Some(start) => {
+            let spent = (start..stats.level.min(MAX_LEVEL)).map(level_up_cost).sum::<i64>();
+            if (stats.runes_tot as i64) < spent {
+                findings.push(format!(
+                    "{} runes acquired in total, but reaching level {} takes at least {spent}",
+                    stats.runes_tot, stats.level
+                ));
+            }
+        },
+        None => {
+            findings.push("Attributes are below the starting values of every class".to_string())
+        },
+    }
+
+    if !(0..=MAX_RUNES).contains(&stats.runes) {
+        findings.push(format!("Holding {} runes, outside 0-{MAX_RUNES}", stats.runes));
+    }
+    if stats.runes > stats.runes_tot {
+        findings.push(format!(
+            "Holding {} runes, more than the {} acquired in total",
+            stats.runes, stats.runes_tot
+        ));
+    }
+
+    if let Some(blessings) = &character.blessings {
+        if !(0..=MAX_SCADUTREE_BLESSING).contains(&blessings.scadutree) {
+            findings.push(format!(
+                "Scadutree blessing is {}, outside 0-{MAX_SCADUTREE_BLESSING}",
+                blessings.scadutree
+            ));
+        }
+        if !(0..=MAX_REVERED_SPIRIT_ASH).contains(&blessings.revered_spirit_ash) {
+            findings.push(format!(
+                "Revered spirit ash blessing is {}, outside 0-{MAX_REVERED_SPIRIT_ASH}",
+                blessings.revered_spirit_ash
+            ));
+        }
+    }
+
+    if let Some(weapon_level) = character.weapon_level.filter(|&l| l > 25) {
+        findings.push(format!("Weapon level is {weapon_level}, outside 0-25"));
+    }
+
+    findings
+}
+
+/// Tables an item's param can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKind {
+    Weapon,
+    Protector,
+    Accessory,
+}
+
+/// Checks that equipped and carried items exist and weapons aren't upgraded
+/// past what they allow, or past the character's weapon level. Takes the
+/// equipped param IDs in [`ChrAsm`](crate::pointers::ChrAsm) order.
+pub fn check_equipment(
+    params: &Params,
+    equip_param_ids: &[i32; 22],
+    inventory: &[InventoryItem],
+    weapon_level: Option<u32>,
+) -> Vec<String> {
+    let equipped = equip_param_ids.iter().enumerate().filter_map(|(slot, &id)| {
+        let kind = match slot {
+            0..=11 => ItemKind::Weapon,
+            12..=15 => ItemKind::Protector,
+            17..=20 => ItemKind::Accessory,
+            _ => return None,
+        };
+        Some(("Equipped", kind, u32::try_from(id).ok()?))
+    });
+    let carried = inventory.iter().filter_map(|item| {
+        let kind = match item.category() {
+            0 => ItemKind::Weapon,
+            1 => ItemKind::Protector,
+            2 => ItemKind::Accessory,
+            _ => return None,
+        };
+        Some(("Inventory", kind, item.param_id()?))
+    });
+
+    // Equipped items are in the inventory as well, so each item is only
+    // checked once.
+    let mut checked = HashSet::new();
+    let mut findings = Vec::new();
+    for (place, kind, id) in equipped.chain(carried) {
+        if checked.insert((kind, id)) {
+            check_item(params, place, kind, id, weapon_level, &mut findings);
+        }
+    }
+
+    findings
+}
+
+fn check_item(
+    params: &Params,
+    place: &str,
+    kind: ItemKind,
+    id: u32,
+    weapon_level: Option<u32>,
+    findings: &mut Vec<String>,
+) {
+    let row_id = id as u64;
+    let exists = match kind {
+        ItemKind::Weapon => params.row::<EquipParamWeapon>(row_id - row_id % 100).is_some(),
+        ItemKind::Protector => params.row::<EquipParamProtector>(row_id).is_some(),
+        ItemKind::Accessory => params.row::<EquipParamAccessory>(row_id).is_some(),
+    };
+    if !exists {
+        findings.push(format!("{place} item {id} doesn't exist"));
+        return;
+    }
+
+    // Ammo can't be upgraded, and nothing else is out of reach at +0.
+    let Some(weapon) = Weapon::from_equip_id(id as i32).filter(|_| kind == ItemKind::Weapon) else {
+        return;
+    };
+    if weapon.level == 0 {
+        return;
+    }
+    let row = params.row::<EquipParamWeapon>(weapon.param_id()).unwrap();
+    let reinforce = |level: u32| {
+        params.row::<ReinforceParamWeapon>(row.reinforce_type_id as u64 + level as u64)
+    };
+    if reinforce(weapon.level).is_none() {
+        findings.push(format!("Weapon {id} can't be upgraded to +{}", weapon.level));
+        return;
+    }
+
+    // Somber weapons go up to +10 and count as the equivalent regular level
+    // for matchmaking.
+    let somber = reinforce(10).is_some() && reinforce(11).is_none();
+    let regular_level = if somber {
+        match SOMBER_LEVELS.get(weapon.level as usize) {
+            Some(&level) => level,
+            None => {
+                findings.push(format!("Somber weapon {id} can't be upgraded to +{}", weapon.level));
+                return;
+            },
+        }
+    } else {
+        weapon.level
+    };
+    if let Some(weapon_level) = weapon_level.filter(|&l| regular_level > l) {
+        findings.push(format!(
+            "Weapon {id} is at +{}, above the character's weapon level of +{weapon_level}",
+            weapon.level
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::params::tests::param_file;
+    use crate::regulation::BinderFile;
+
+    fn stats(attributes: [i32; 8], runes: i32, runes_tot: i32) -> CharacterStats {
+        let [vigor, mind, endurance, strength, dexterity, intelligence, faith, arcane] = attributes;
+        CharacterStats {
+            vigor,
+            mind,
+            endurance,
+            strength,
+            dexterity,
+            intelligence,
+            faith,
+            arcane,
+            pad1: [0; 3],
+            level: attributes.iter().sum::<i32>() - LEVEL_OFFSET,
+            runes,
+            runes_tot,
+        }
+    }
+
+    fn character(stats: CharacterStats) -> Character {
+        Character { stats, blessings: None, weapon_level: None }
+    }
+
+    #[test]
+    fn test_classes() {
+        for (name, level, attributes) in CLASSES {
+            assert_eq!(attributes.iter().sum::<i32>() - LEVEL_OFFSET, level, "{name}");
+            let stats = stats(attributes, 0, 0);
+            assert!(possible_classes(&stats).any(|c| c.0 == name));
+            assert_eq!(check(&character(stats)), Vec::<String>::new(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_level_up_cost() {
+        assert_eq!(level_up_cost(1), 673);
+        assert_eq!(level_up_cost(11), 847);
+        assert!(level_up_cost(150) > level_up_cost(149));
+    }
+
+    #[test]
+    fn test_check() {
+        // A Wretch at level 2.
+        let wretch = stats([11, 10, 10, 10, 10, 10, 10, 10], 0, 673);
+        assert_eq!(check(&character(wretch.clone())), Vec::<String>::new());
+
+        let mut edited = wretch.clone();
+        edited.level = 30;
+        let findings = check(&character(edited));
+        assert!(findings[0].starts_with("Level is 30"), "{findings:?}");
+
+        let poor = stats([11, 10, 10, 10, 10, 10, 10, 10], 0, 672);
+        assert_eq!(check(&character(poor)).len(), 1);
+
+        let rich = stats([11, 10, 10, 10, 10, 10, 10, 10], 1000, 673);
+        assert_eq!(check(&character(rich)).len(), 1);
+
+        let weak = stats([40, 10, 10, 10, 10, 10, 10, 6], 0, 1_000_000);
+        let findings = check(&character(weak));
+        assert_eq!(findings, vec!["Attributes are below the starting values of every class"]);
+
+        let maxed = stats([100, 10, 10, 10, 10, 10, 10, 10], 0, i32::MAX);
+        assert_eq!(check(&character(maxed)), vec!["Vigor is 100, outside 1-99"]);
+
+        let blessed = Character {
+            blessings: Some(CharacterBlessings { scadutree: 21, revered_spirit_ash: 10 }),
+            weapon_level: Some(26),
+            ..character(wretch)
+        };
+        assert_eq!(check(&blessed), vec![
+            "Scadutree blessing is 21, outside 0-20",
+            "Weapon level is 26, outside 0-25",
+        ]);
+    }
+
+    fn bytes<T>(row: &T) -> Vec<u8> {
+        unsafe { std::slice::from_raw_parts(row as *const T as *const u8, mem::size_of::<T>()) }
+            .to_vec()
+    }
+
+    #[test]
+    fn test_check_equipment() {
+        // A regular weapon with reinforce rows 0-25 and a somber one with
+        // rows 2200-2210.
+        let regular: EquipParamWeapon = unsafe { mem::zeroed() };
+        let mut somber: EquipParamWeapon = unsafe { mem::zeroed() };
+        somber.reinforce_type_id = 2200;
+        let reinforce = bytes(&unsafe { mem::zeroed::<ReinforceParamWeapon>() });
+        // A broken row past the somber maximum.
+        let reinforce_rows = (0..=25).chain(2200..=2210).chain([2215]).collect::<Vec<u64>>();
+        let protector = bytes(&unsafe { mem::zeroed::<EquipParamProtector>() });
+
+        let file =
+            |name: &str, data: Vec<u8>| BinderFile { id: 0, name: format!("{name}.param"), data };
+        let params = Params::from_param_files(&[
+            file(
+                "EquipParamWeapon",
+                param_file(&[(1000000, &bytes(&regular)), (2000000, &bytes(&somber))]),
+            ),
+            file(
+                "ReinforceParamWeapon",
+                param_file(
+                    &reinforce_rows
+                        .iter()
+                        .map(|&id| (id, reinforce.as_slice()))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            file("EquipParamProtector", param_file(&[(10000, &protector)])),
+        ])
+        .unwrap();
+
+        let mut ids = [-1; 22];
+        ids[1] = 1000025;
+        ids[3] = 2000005;
+        ids[12] = 10000;
+        assert_eq!(check_equipment(&params, &ids, &[], Some(25)), Vec::<String>::new());
+        assert_eq!(check_equipment(&params, &ids, &[], Some(12)), vec![
+            "Weapon 1000025 is at +25, above the character's weapon level of +12"
+        ]);
+        assert_eq!(check_equipment(&params, &ids, &[], Some(11)).len(), 2);
+
+        ids[3] = 2000011;
+        ids[13] = 10100;
+        ids[0] = 3000000;
+        assert_eq!(check_equipment(&params, &ids, &[], None), vec![
+            "Equipped item 3000000 doesn't exist",
+            "Weapon 2000011 can't be upgraded to +11",
+            "Equipped item 10100 doesn't exist",
+        ]);
+
+        let item = |item_id| InventoryItem { gaitem_handle: 1, item_id, ..InventoryItem::EMPTY };
+        let inventory = [
+            item(2000011),
+            item(2000015),
+            item(0x1000_0000 | 10000),
+            item(0x2000_0000 | 1000),
+            item(0x4000_0000 | 1),
+            InventoryItem::EMPTY,
+        ];
+        assert_eq!(check_equipment(&params, &ids, &inventory, None), vec![
+            "Equipped item 3000000 doesn't exist",
+            "Weapon 2000011 can't be upgraded to +11",
+            "Equipped item 10100 doesn't exist",
+            "Somber weapon 2000015 can't be upgraded to +15",
+            "Inventory item 1000 doesn't exist",
+        ]);
+    }
+}
@@ -1,17 +1,43 @@
 use std::thread;
 use std::time::Duration;
 
+pub mod address_pack;
+pub mod aob;
+pub mod attack_rating;
 pub mod codegen;
+pub mod combat_log;
+pub mod frame_advance;
+pub mod frame_data;
+pub mod freezer;
+pub mod input_history;
+pub mod input_recording;
+pub mod journal;
+pub mod legality;
+pub mod matchmaking;
 pub mod memedit;
+pub mod memory;
+pub mod offsets;
+pub mod param_diff;
+pub mod param_io;
+pub mod param_meta;
+pub mod param_search;
 pub mod params;
+pub mod pe;
 pub mod pointers;
+pub mod regulation;
+pub mod session;
 pub mod version;
 
 pub mod prelude {
     pub use crate::codegen::*;
+    pub use crate::freezer::{FreezeId, FREEZER};
+    pub use crate::journal::*;
     pub use crate::memedit::*;
+    pub use crate::memory::*;
+    pub use crate::offsets::Offsets;
     pub use crate::params::*;
     pub use crate::pointers::*;
+    pub use crate::session::*;
     pub use crate::version::*;
     pub use crate::{wait_option, wait_for_option_in_thread, ParamStruct, ParamVisitor};
 }
@@ -62,6 +88,9 @@ pub trait ParamVisitor {
 
 /// Implemented by generated code. Visits all the fields of a `ParamVisitor`.
 pub trait ParamStruct {
+    /// Name of the param the struct is the row type of, as in `PARAM_VTABLE`.
+    const NAME: &'static str;
+
     fn visit<T: ParamVisitor + ?Sized>(&mut self, t: &mut T);
 }
 
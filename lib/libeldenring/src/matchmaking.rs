@@ -0,0 +1,136 @@
+//! Multiplayer matchmaking ranges.
+//!
+//! Players can meet if each one's rune level and weapon level are in the
+//! other's range. The weapon level is the highest upgrade the character ever
+//! reached on a regular weapon, or the regular equivalent of a somber one.
+
+/// Highest rune level a character can reach.
+pub const MAX_LEVEL: i32 = 713;
+
+/// Regular upgrade level equivalent to each somber upgrade level.
+pub const SOMBER_LEVELS: [u32; 11] = [0, 2, 5, 7, 10, 12, 15, 17, 20, 22, 25];
+
+/// Weapon levels that can be matched, by regular weapon level.
+pub const WEAPON_LEVEL_RANGES: [(u32, u32); 26] = [
+    (0, 3),
+    (0, 4),
+    (0, 5),
+    (0, 6),
+    (1, 7),
+    (2, 8),
+    (3, 9),
+    (4, 10),
+    (5, 12),
+    (6, 13),
+    (7, 14),
+    (8, 15),
+    (9, 16),
+    (10, 17),
+    (11, 18),
+    (12, 19),
+    (13, 21),
+    (14, 22),
+    (15, 23),
+    (16, 24),
+    (17, 25),
+    (18, 25),
+    (19, 25),
+    (20, 25),
+    (21, 25),
+    (22, 25),
+];
+
+/// Rune levels that can be matched: 10% below, and 10% plus 10 levels
+/// above.
+pub fn rune_level_range(level: i32) -> (i32, i32) {
+    let level = level.clamp(1, MAX_LEVEL);
+    ((level - level / 10).max(1), (level + level / 10 + 10).min(MAX_LEVEL))
+}
+
+/// Regular weapon levels that can be matched.
+pub fn weapon_level_range(weapon_level: u32) -> (u32, u32) {
+    WEAPON_LEVEL_RANGES[weapon_level.min(25) as usize]
+}
+
+/// Rune level and weapon level ranges of a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Brackets {
+    pub level: i32,
+    pub weapon_level: u32,
+    pub rune_levels: (i32, i32),
+    pub weapon_levels: (u32, u32),
+}
+
+impl Brackets {
+    pub fn new(level: i32, weapon_level: u32) -> Self {
+        Brackets {
+            level,
+            weapon_level,
+            rune_levels: rune_level_range(level),
+            weapon_levels: weapon_level_range(weapon_level),
+        }
+    }
+
+    /// Brackets after gaining the given number of levels.
+    pub fn level_up(&self, levels: i32) -> Self {
+        Brackets::new((self.level + levels).min(MAX_LEVEL), self.weapon_level)
+    }
+
+    /// Brackets after reaching the next regular weapon level, if any.
+    pub fn upgrade(&self) -> Option<Self> {
+        (self.weapon_level < 25).then(|| Brackets::new(self.level, self.weapon_level + 1))
+    }
+
+    /// Brackets after reaching the next somber weapon level, if any.
+    pub fn upgrade_somber(&self) -> Option<Self> {
+        let next = SOMBER_LEVELS.iter().find(|&&l| l > self.weapon_level)?;
+        Some(Brackets::new(self.level, *next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rune_level_range() {
+        assert_eq!(rune_level_range(1), (1, 11));
+        assert_eq!(rune_level_range(9), (9, 19));
+        assert_eq!(rune_level_range(30), (27, 43));
+        assert_eq!(rune_level_range(125), (113, 147));
+        assert_eq!(rune_level_range(150), (135, 175));
+        assert_eq!(rune_level_range(200), (180, 230));
+        assert_eq!(rune_level_range(700), (630, 713));
+        assert_eq!(rune_level_range(0), (1, 11));
+    }
+
+    #[test]
+    fn test_weapon_level_range() {
+        assert_eq!(weapon_level_range(0), (0, 3));
+        assert_eq!(weapon_level_range(8), (5, 12));
+        assert_eq!(weapon_level_range(16), (13, 21));
+        assert_eq!(weapon_level_range(25), (22, 25));
+        assert_eq!(weapon_level_range(99), (22, 25));
+
+        // The ranges never shrink as the weapon level goes up.
+        for pair in WEAPON_LEVEL_RANGES.windows(2) {
+            assert!(pair[0].0 <= pair[1].0 && pair[0].1 <= pair[1].1);
+        }
+    }
+
+    #[test]
+    fn test_brackets() {
+        let brackets = Brackets::new(125, 17);
+        assert_eq!(brackets.rune_levels, (113, 147));
+        assert_eq!(brackets.weapon_levels, (14, 22));
+
+        assert_eq!(brackets.level_up(1).rune_levels, (114, 148));
+        assert_eq!(brackets.upgrade().unwrap().weapon_levels, (15, 23));
+        assert_eq!(brackets.upgrade_somber().unwrap().weapon_level, 20);
+
+        let maxed = Brackets::new(MAX_LEVEL, 25);
+        assert_eq!(maxed.upgrade(), None);
+        assert_eq!(maxed.upgrade_somber(), None);
+        assert_eq!(maxed.level_up(1).level, MAX_LEVEL);
+    }
+}
@@ -1,9 +1,9 @@
 use std::fmt::Debug;
 use std::ops::{BitAnd, BitOr, BitXor, Not};
+use std::sync::Arc;
 
-use windows::Win32::Foundation::HANDLE;
-use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
-use windows::Win32::System::Threading::GetCurrentProcess;
+use crate::journal::JOURNAL;
+use crate::memory::{self, MemorySource};
 
 /// Wraps CheatEngine's concept of pointer with nested offsets. Evaluates,
 /// if the evaluation does not fail, to a mutable pointer of type `T`.
@@ -12,21 +12,25 @@ use windows::Win32::System::Threading::GetCurrentProcess;
 /// base pointer, then recursively reading the next memory address in the
 /// chain at an offset from there. For example,
 ///
-/// ```
+/// ```ignore
 /// PointerChain::<T>::new(&[a, b, c, d, e])
 /// ```
 ///
 /// evaluates to
 ///
-/// ```
+/// ```text
 /// *(*(*(*(*a + b) + c) + d) + e)
 /// ```
 ///
 /// This is useful for managing reverse engineered structures which are not
 /// fully known.
+///
+/// All memory accesses go through a [`MemorySource`], which is the current
+/// process unless a different one is supplied via
+/// [`PointerChain::with_source`].
 #[derive(Clone, Debug)]
 pub struct PointerChain<T> {
-    proc: HANDLE,
+    source: Arc<dyn MemorySource>,
     base: *mut T,
     offsets: Vec<usize>,
 }
@@ -36,33 +40,32 @@ unsafe impl<T> Sync for PointerChain<T> {}
 impl<T> PointerChain<T> {
     /// Creates a new pointer chain given an array of addresses.
     pub fn new(chain: &[usize]) -> PointerChain<T> {
+        Self::with_source(memory::default_source(), chain)
+    }
+
+    /// Creates a new pointer chain which is evaluated against `source`.
+    pub fn with_source(source: Arc<dyn MemorySource>, chain: &[usize]) -> PointerChain<T> {
         let mut it = chain.iter();
         let base = *it.next().unwrap() as *mut T;
         PointerChain {
-            proc: unsafe { GetCurrentProcess() },
+            source,
             base,
             offsets: it.copied().collect(), // it.map(|x| *x).collect(),
         }
     }
 
+    /// The memory source this chain is evaluated against.
+    pub fn source(&self) -> &Arc<dyn MemorySource> {
+        &self.source
+    }
+
     fn safe_read(&self, addr: usize, offs: usize) -> Option<usize> {
-        let mut value = 0usize;
-        unsafe {
-            ReadProcessMemory(
-                self.proc,
-                addr as _,
-                &mut value as *mut usize as _,
-                std::mem::size_of::<usize>(),
-                None,
-            )
-            .ok()
-            .map(|_| value + offs)
-        }
+        self.source.read_usize(addr).map(|value| value.wrapping_add(offs))
     }
 
     /// Safely evaluates the pointer chain.
-    /// Relies on `ReadProcessMemory` instead of pointer dereferencing for crash
-    /// safety.  Returns `None` if the evaluation failed.
+    /// Reads through the memory source instead of dereferencing pointers for
+    /// crash safety.  Returns `None` if the evaluation failed.
     pub fn eval(&self) -> Option<*mut T> {
         self.offsets
             .iter()
@@ -75,38 +78,54 @@ impl<T> PointerChain<T> {
     pub fn read(&self) -> Option<T> {
         let ptr = self.eval()?;
         let mut value: T = unsafe { std::mem::zeroed() };
-        unsafe {
-            ReadProcessMemory(
-                self.proc,
-                ptr as _,
-                &mut value as *mut _ as _,
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut value as *mut T as *mut u8,
                 std::mem::size_of::<T>(),
-                None,
             )
-            .ok()
-            .map(|_| value)
-        }
+        };
+        self.source.read(ptr as usize, buf).map(|_| value)
     }
 
     /// Evaluates the pointer chain and attempts to write the datum.
     /// Returns `None` if either the evaluation or the write failed.
-    pub fn write(&self, mut value: T) -> Option<()> {
+    ///
+    /// The previous contents are recorded in the [`JOURNAL`] the first time a
+    /// location is written to, keyed by this chain so that they're dropped
+    /// once it resolves somewhere else.
+    pub fn write(&self, value: T) -> Option<()> {
         let ptr = self.eval()?;
-        unsafe {
-            WriteProcessMemory(
-                self.proc,
-                ptr as _,
-                &mut value as *mut _ as _,
-                std::mem::size_of::<T>(),
-                None,
-            )
-            .ok()
-            .map(|_| ())
-        }
+        JOURNAL.lock().record_chain(
+            &self.source,
+            self.base as usize,
+            &self.offsets,
+            ptr as usize,
+            std::mem::size_of::<T>(),
+        );
+        self.write_at(ptr, value)
+    }
+
+    /// Like [`PointerChain::write`], without recording into the [`JOURNAL`].
+    /// Meant for values which are written every frame, and for the player's
+    /// position, which restoring would teleport the player back to.
+    pub fn write_untracked(&self, value: T) -> Option<()> {
+        let ptr = self.eval()?;
+        self.write_at(ptr, value)
+    }
+
+    fn write_at(&self, ptr: *mut T, value: T) -> Option<()> {
+        let buf = unsafe {
+            std::slice::from_raw_parts(&value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.source.write(ptr as usize, buf)
     }
 
     pub fn cast<S>(&self) -> PointerChain<S> {
-        PointerChain { proc: self.proc, base: self.base as *mut S, offsets: self.offsets.clone() }
+        PointerChain {
+            source: Arc::clone(&self.source),
+            base: self.base as *mut S,
+            offsets: self.offsets.clone(),
+        }
     }
 }
 
@@ -115,7 +134,7 @@ pub struct Bitflag<T>(PointerChain<T>, T);
 
 // impl<T: Display + Debug> Debug for PointerChain<T> {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         write!(f, "PointerChain({} @ {:p}", self.proc.0, self.base)?;
+//         write!(f, "PointerChain({:?} @ {:p}", self.source, self.base)?;
 //         for o in &self.offsets {
 //             write!(f, ", {:x}", o)?;
 //         }
@@ -173,3 +192,60 @@ macro_rules! bitflag {
 }
 
 pub use {bitflag, pointer_chain};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::FakeMemory;
+
+    fn fixture() -> Arc<FakeMemory> {
+        let mem = Arc::new(FakeMemory::new());
+        mem.map_pointer(0x1000, 0x2000);
+        mem.map_zeroed(0x2000, 0x20);
+        mem.write(0x2010, &0x3000usize.to_le_bytes()).unwrap();
+        mem.map_zeroed(0x3000, 0x10);
+        mem
+    }
+
+    #[test]
+    fn test_eval_follows_offsets() {
+        let mem = fixture();
+        let chain = PointerChain::<u32>::with_source(mem, &[0x1000, 0x10, 0x8]);
+
+        assert_eq!(chain.eval(), Some(0x3008 as *mut u32));
+    }
+
+    #[test]
+    fn test_eval_fails_on_unmapped_address() {
+        let mem = fixture();
+        let chain = PointerChain::<u32>::with_source(mem, &[0x1000, 0x8, 0x8, 0x0]);
+
+        assert_eq!(chain.eval(), None);
+        assert_eq!(chain.read(), None);
+        assert_eq!(chain.write(1), None);
+    }
+
+    #[test]
+    fn test_read_write_roundtrip() {
+        let mem = fixture();
+        let chain = PointerChain::<f32>::with_source(mem.clone(), &[0x1000, 0x10, 0x4]);
+
+        chain.write(12.5).unwrap();
+        assert_eq!(chain.read(), Some(12.5));
+        assert_eq!(mem.bytes(0x3004, 4), Some(12.5f32.to_le_bytes().to_vec()));
+        assert_eq!(chain.cast::<u32>().read(), Some(12.5f32.to_bits()));
+    }
+
+    #[test]
+    fn test_bitflag() {
+        let mem = fixture();
+        let flag = Bitflag::new(PointerChain::<u8>::with_source(mem.clone(), &[0x1000, 0x1]), 0x4);
+
+        assert_eq!(flag.get(), Some(false));
+        flag.set(true);
+        assert_eq!(flag.get(), Some(true));
+        assert_eq!(mem.bytes(0x2001, 1), Some(vec![0x4]));
+        flag.toggle();
+        assert_eq!(flag.get(), Some(false));
+    }
+}
@@ -0,0 +1,38 @@
+use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows::Win32::System::Threading::GetCurrentProcess;
+
+use super::MemorySource;
+
+/// The address space of the process the library is loaded into.
+///
+/// Relies on `ReadProcessMemory` and `WriteProcessMemory` instead of pointer
+/// dereferencing for crash safety.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CurrentProcess;
+
+impl MemorySource for CurrentProcess {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        unsafe {
+            ReadProcessMemory(
+                GetCurrentProcess(),
+                addr as _,
+                buf.as_mut_ptr() as _,
+                buf.len(),
+                None,
+            )
+            .ok()
+        }
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        unsafe {
+            WriteProcessMemory(GetCurrentProcess(), addr as _, buf.as_ptr() as _, buf.len(), None)
+                .ok()
+        }
+    }
+
+    fn module_base(&self) -> Option<usize> {
+        unsafe { GetModuleHandleA(None) }.ok().map(|module| module.0 as usize)
+    }
+}
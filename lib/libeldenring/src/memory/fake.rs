@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use parking_lot::RwLock;
+
+use super::MemorySource;
+
+/// A sparse in-memory address space made of byte regions mapped at
+/// arbitrary addresses. Reads and writes fail unless they fall entirely
+/// within a single region.
+#[derive(Debug, Default)]
+pub struct FakeMemory {
+    regions: RwLock<BTreeMap<usize, Vec<u8>>>,
+    module_base: Option<usize>,
+}
+
+impl FakeMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty address space which reports `base` as the game
+    /// module's base address.
+    pub fn with_module_base(base: usize) -> Self {
+        Self { module_base: Some(base), ..Default::default() }
+    }
+
+    /// Maps `data` at `addr`, replacing any region starting there.
+    pub fn map<D: Into<Vec<u8>>>(&self, addr: usize, data: D) {
+        self.regions.write().insert(addr, data.into());
+    }
+
+    /// Maps `len` zero bytes at `addr`.
+    pub fn map_zeroed(&self, addr: usize, len: usize) {
+        self.map(addr, vec![0u8; len]);
+    }
+
+    /// Maps a pointer-sized region at `addr` which points to `target`.
+    pub fn map_pointer(&self, addr: usize, target: usize) {
+        self.map(addr, target.to_le_bytes());
+    }
+
+    /// Maps the in-memory representation of `value` at `addr`.
+    pub fn map_value<T: Copy>(&self, addr: usize, value: T) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&value as *const T as *const u8, std::mem::size_of::<T>())
+        };
+        self.map(addr, bytes);
+    }
+
+    /// Returns a copy of `len` bytes at `addr`.
+    pub fn bytes(&self, addr: usize, len: usize) -> Option<Vec<u8>> {
+        let mut buf = vec![0u8; len];
+        self.read(addr, &mut buf).map(|_| buf)
+    }
+}
+
+impl MemorySource for FakeMemory {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        let regions = self.regions.read();
+        let (start, data) = regions.range(..=addr).next_back()?;
+        let offset = addr - start;
+        buf.copy_from_slice(data.get(offset..offset.checked_add(buf.len())?)?);
+        Some(())
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        let mut regions = self.regions.write();
+        let (start, data) = regions.range_mut(..=addr).next_back()?;
+        let offset = addr - *start;
+        data.get_mut(offset..offset.checked_add(buf.len())?)?.copy_from_slice(buf);
+        Some(())
+    }
+
+    fn module_base(&self) -> Option<usize> {
+        self.module_base
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_within_region() {
+        let mem = FakeMemory::new();
+        mem.map(0x1000, [1u8, 2, 3, 4]);
+
+        let mut buf = [0u8; 2];
+        assert_eq!(mem.read(0x1001, &mut buf), Some(()));
+        assert_eq!(buf, [2, 3]);
+    }
+
+    #[test]
+    fn test_rejects_out_of_bounds() {
+        let mem = FakeMemory::new();
+        mem.map(0x1000, [1u8, 2, 3, 4]);
+
+        assert_eq!(mem.read(0x0fff, &mut [0u8; 1]), None);
+        assert_eq!(mem.read(0x1003, &mut [0u8; 2]), None);
+        assert_eq!(mem.write(0x1004, &[0u8]), None);
+    }
+
+    #[test]
+    fn test_write_then_read() {
+        let mem = FakeMemory::new();
+        mem.map_zeroed(0x2000, 8);
+        mem.write(0x2004, &7u32.to_le_bytes()).unwrap();
+
+        assert_eq!(mem.bytes(0x2000, 8), Some(vec![0, 0, 0, 0, 7, 0, 0, 0]));
+    }
+}
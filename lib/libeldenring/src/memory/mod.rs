@@ -0,0 +1,69 @@
+//! Address spaces that pointer chains can be evaluated against.
+//!
+//! Every [`PointerChain`](crate::memedit::PointerChain) reads and writes
+//! through a [`MemorySource`]. Inside the game process this is
+//! [`CurrentProcess`]; tests can use a [`FakeMemory`] filled with fixture
+//! bytes instead. On Linux, [`ProcessMemory`] attaches to the game running
+//! under Proton from outside of it.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+#[cfg(windows)]
+mod current_process;
+mod fake;
+#[cfg(target_os = "linux")]
+mod proc_mem;
+
+#[cfg(windows)]
+pub use current_process::CurrentProcess;
+pub use fake::FakeMemory;
+#[cfg(target_os = "linux")]
+pub use proc_mem::{ProcessMemory, GAME_EXECUTABLE};
+
+/// A readable and writable address space.
+pub trait MemorySource: Debug + Send + Sync {
+    /// Reads `buf.len()` bytes starting at `addr`. Returns `None` if any of
+    /// the bytes could not be read.
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()>;
+
+    /// Writes `buf` starting at `addr`. Returns `None` if any of the bytes
+    /// could not be written.
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()>;
+
+    /// Base address of the game's main module, if known.
+    fn module_base(&self) -> Option<usize>;
+
+    /// Reads a pointer-sized value at `addr`.
+    fn read_usize(&self, addr: usize) -> Option<usize> {
+        let mut buf = [0u8; std::mem::size_of::<usize>()];
+        self.read(addr, &mut buf)?;
+        Some(usize::from_le_bytes(buf))
+    }
+}
+
+static DEFAULT_SOURCE: Lazy<RwLock<Arc<dyn MemorySource>>> =
+    Lazy::new(|| RwLock::new(platform_source()));
+
+/// The source new pointer chains are created against when none is specified.
+pub fn default_source() -> Arc<dyn MemorySource> {
+    DEFAULT_SOURCE.read().clone()
+}
+
+/// Replaces the default source. Only chains created afterwards are affected.
+pub fn set_default_source(source: Arc<dyn MemorySource>) {
+    *DEFAULT_SOURCE.write() = source;
+}
+
+#[cfg(windows)]
+fn platform_source() -> Arc<dyn MemorySource> {
+    Arc::new(CurrentProcess)
+}
+
+#[cfg(not(windows))]
+fn platform_source() -> Arc<dyn MemorySource> {
+    Arc::new(FakeMemory::new())
+}
@@ -0,0 +1,156 @@
+use std::fs::{self, File, OpenOptions};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use super::MemorySource;
+
+/// Name of the game's executable as it appears under Proton.
+pub const GAME_EXECUTABLE: &str = "eldenring.exe";
+
+/// The address space of another process, accessed through `/proc/<pid>/mem`.
+///
+/// Requires ptrace access to the target process: either run as the same user
+/// with `kernel.yama.ptrace_scope` set to 0, or as root.
+#[derive(Debug)]
+pub struct ProcessMemory {
+    pid: u32,
+    mem: File,
+    module_base: Option<usize>,
+}
+
+impl ProcessMemory {
+    /// Attaches to the game running under Proton with the given pid.
+    pub fn attach(pid: u32) -> Result<Self, String> {
+        Self::attach_module(pid, GAME_EXECUTABLE)
+    }
+
+    /// Attaches to the process with the given pid, using the lowest address
+    /// `module` is mapped at as the module base.
+    pub fn attach_module(pid: u32, module: &str) -> Result<Self, String> {
+        let proc_dir = Path::new("/proc").join(pid.to_string());
+
+        let mem = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(proc_dir.join("mem"))
+            .or_else(|_| File::open(proc_dir.join("mem")))
+            .map_err(|e| format!("Couldn't open memory of process {pid}: {e}"))?;
+
+        let maps = fs::read_to_string(proc_dir.join("maps"))
+            .map_err(|e| format!("Couldn't read memory maps of process {pid}: {e}"))?;
+
+        Ok(Self { pid, mem, module_base: parse_module_base(&maps, module) })
+    }
+
+    /// Finds the pid of the first process whose name is `name`.
+    pub fn find_pid(name: &str) -> Option<u32> {
+        fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let comm = fs::read_to_string(entry.path().join("comm")).ok()?;
+            comm.trim_end().eq_ignore_ascii_case(name).then_some(pid)
+        })
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+}
+
+impl MemorySource for ProcessMemory {
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Option<()> {
+        self.mem.read_exact_at(buf, addr as u64).ok()
+    }
+
+    fn write(&self, addr: usize, buf: &[u8]) -> Option<()> {
+        self.mem.write_all_at(buf, addr as u64).ok()
+    }
+
+    fn module_base(&self) -> Option<usize> {
+        self.module_base
+    }
+}
+
+/// Finds the lowest start address among the mappings of the file named
+/// `module` in the contents of a `/proc/<pid>/maps` file.
+fn parse_module_base(maps: &str, module: &str) -> Option<usize> {
+    maps.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(6, ' ');
+            let range = fields.next()?;
+            let path = fields.nth(4)?.trim_start();
+            let file_name = path.rsplit(['/', '\\']).next()?;
+
+            if !file_name.eq_ignore_ascii_case(module) {
+                return None;
+            }
+
+            usize::from_str_radix(range.split('-').next()?, 16).ok()
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Child, Command};
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    const MAPS: &str = "\
+00010000-00020000 rw-p 00000000 00:00 0 
+140000000-140001000 r--p 00000000 00:2a 1234       /games/ELDEN RING/Game/eldenring.exe
+140001000-142000000 r-xp 00001000 00:2a 1234       /games/ELDEN RING/Game/eldenring.exe
+7f0000000000-7f0000001000 r--p 00000000 00:2a 5678 /usr/lib/libc.so.6
+";
+
+    struct KillOnDrop(Child);
+
+    impl Drop for KillOnDrop {
+        fn drop(&mut self) {
+            self.0.kill().ok();
+            self.0.wait().ok();
+        }
+    }
+
+    #[test]
+    fn test_parse_module_base() {
+        assert_eq!(parse_module_base(MAPS, "eldenring.exe"), Some(0x140000000));
+        assert_eq!(parse_module_base(MAPS, "EldenRing.exe"), Some(0x140000000));
+        assert_eq!(parse_module_base(MAPS, "libc.so.6"), Some(0x7f0000000000));
+        assert_eq!(parse_module_base(MAPS, "start_protected_game.exe"), None);
+    }
+
+    #[test]
+    fn test_child_process() {
+        let child = KillOnDrop(Command::new("sleep").arg("30").spawn().unwrap());
+        let pid = child.0.id();
+
+        // Wait for the exec to happen so that the child's image is mapped.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let (mem, base) = loop {
+            let exe = fs::read_link(format!("/proc/{pid}/exe")).unwrap();
+            if exe != std::env::current_exe().unwrap() {
+                let module = exe.file_name().unwrap().to_str().unwrap();
+                let mem = ProcessMemory::attach_module(pid, module).unwrap();
+                if let Some(base) = mem.module_base() {
+                    break (mem, base);
+                }
+            }
+            assert!(Instant::now() < deadline, "The child process never started");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        // Every executable image starts with the ELF magic.
+        let mut magic = [0u8; 4];
+        mem.read(base, &mut magic).unwrap();
+        assert_eq!(&magic, b"\x7fELF");
+
+        // The padding at the end of the ELF identification is unused.
+        mem.write(base + 0xc, &[0xaa, 0x55]).unwrap();
+        let mut padding = [0u8; 2];
+        mem.read(base + 0xc, &mut padding).unwrap();
+        assert_eq!(padding, [0xaa, 0x55]);
+
+        assert_eq!(mem.read(0, &mut magic), None);
+    }
+}
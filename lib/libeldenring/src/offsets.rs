@@ -0,0 +1,236 @@
+//! Offsets within game structures that change between game versions.
+//!
+//! Each offset is a table of the versions where it changed, and the value it
+//! took from then on: `(1, 8, 0) => 0x6d0` reads as "1.08.0 and later use
+//! 0x6d0". Unknown versions newer than every entry use the latest values.
+//! Numeric offsets can be overridden by name from an address pack.
+
+use std::collections::BTreeMap;
+
+/// A value that changes between game versions. Entries are sorted by
+/// version, and each one applies up to the next.
+#[derive(Debug)]
+pub struct VersionTable<T: 'static>(pub &'static [((u32, u32, u32), T)]);
+
+impl<T: Copy> VersionTable<T> {
+    /// The value for the given version, or `None` if it predates the table.
+    pub fn resolve(&self, version: (u32, u32, u32)) -> Option<T> {
+        self.0.iter().rev().find(|(since, _)| *since <= version).map(|(_, value)| *value)
+    }
+
+    #[cfg(test)]
+    fn is_sorted(&self) -> bool {
+        self.0.windows(2).all(|w| w[0].0 < w[1].0)
+    }
+}
+
+/// Offset values that can be set from an address pack.
+pub trait OffsetValue: Copy {
+    fn from_override(offset: usize) -> Option<Self>;
+}
+
+impl OffsetValue for usize {
+    fn from_override(offset: usize) -> Option<Self> {
+        Some(offset)
+    }
+}
+
+impl OffsetValue for Option<usize> {
+    fn from_override(offset: usize) -> Option<Self> {
+        Some(Some(offset))
+    }
+}
+
+impl OffsetValue for (u8, u8) {
+    fn from_override(_: usize) -> Option<Self> {
+        None
+    }
+}
+
+impl OffsetValue for &'static [usize] {
+    fn from_override(_: usize) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! offsets {
+    ($(
+        $(#[doc = $doc:expr])*
+        $field:ident: $ty:ty = [$(($maj:literal, $min:literal, $patch:literal) => $value:expr),* $(,)?],
+    )*) => {
+        #[derive(Debug, Clone, Copy)]
+        pub struct Offsets {
+            $($(#[doc = $doc])* pub $field: $ty,)*
+        }
+
+        /// Names of the offsets, as used in address packs.
+        pub const OFFSET_NAMES: &[&str] = &[$(stringify!($field),)*];
+
+        impl Offsets {
+            /// Resolves every offset for the given game version, then applies
+            /// the overrides.
+            pub fn resolve(
+                version: (u32, u32, u32),
+                overrides: &BTreeMap<&'static str, usize>,
+            ) -> Result<Offsets, String> {
+                if let Some(name) = overrides.keys().find(|name| !OFFSET_NAMES.contains(name)) {
+                    return Err(format!("Unknown offset {name}"));
+                }
+
+                let (major, minor, patch) = version;
+
+                Ok(Offsets {
+                    $($field: {
+                        const TABLE: VersionTable<$ty> =
+                            VersionTable(&[$((($maj, $min, $patch), $value),)*]);
+
+                        match overrides.get(stringify!($field)) {
+                            Some(&offset) => <$ty as OffsetValue>::from_override(offset)
+                                .ok_or_else(|| {
+                                    format!("Offset {} can't be overridden", stringify!($field))
+                                })?,
+                            None => TABLE.resolve(version).ok_or_else(|| {
+                                format!(
+                                    "No {} offset for {major}.{minor}.{patch}",
+                                    stringify!($field)
+                                )
+                            })?,
+                        }
+                    },)*
+                })
+            }
+
+            #[cfg(test)]
+            fn tables_are_sorted() -> bool {
+                true $(&& {
+                    const TABLE: VersionTable<$ty> =
+                        VersionTable(&[$((($maj, $min, $patch), $value),)*]);
+                    TABLE.is_sorted()
+                })*
+            }
+        }
+    };
+}
+
+offsets! {
+    /// WorldChrMan -> player instance.
+    player_ins: usize = [(1, 2, 0) => 0x18468, (1, 7, 0) => 0x1e508],
+    /// WorldChrMan -> net players.
+    net_players_ins: usize = [(1, 2, 0) => 0xb658, (1, 7, 0) => 0x10ef8],
+    /// WorldChrMan -> Torrent.
+    torrent_enemy_ins: usize = [
+        (1, 2, 0) => 0x18390,
+        (1, 6, 0) => 0x18378,
+        (1, 7, 0) => 0x1e1a0,
+        (1, 8, 0) => 0x1e1b8,
+        (2, 2, 0) => 0x1cc90,
+    ],
+    /// Player instance -> map id.
+    map_id: usize = [(1, 2, 0) => 0x6c8, (1, 4, 0) => 0x6c0, (1, 8, 0) => 0x6d0],
+    /// Player instance -> global position.
+    global_position: usize = [(1, 2, 0) => 0x6b8, (1, 4, 0) => 0x6b0, (1, 8, 0) => 0x6c0],
+    /// Player instance -> display stable position flag.
+    display_stable_pos: usize = [(1, 2, 0) => 0x6fd, (1, 4, 0) => 0x6f5, (1, 8, 0) => 0x735],
+    /// CSMenuManImp -> menu timer.
+    menu_timer: usize = [
+        (1, 2, 0) => 0x708 + 0x24,
+        (1, 3, 0) => 0x718 + 0x24,
+        (2, 2, 0) => 0x720 + 0x24,
+    ],
+    /// GameDataMan -> player game data -> blessings. Only in 2.02 and later.
+    character_blessings: Option<usize> = [(1, 2, 0) => None, (2, 2, 0) => Some(0xfc)],
+    /// GameDataMan -> player game data -> highest weapon level reached, as
+    /// used for matchmaking. The byte right before the blessings, as in
+    /// `PlayerGameData` from vswarte/fromsoftware-rs.
+    matchmaking_weapon_level: usize = [(1, 2, 0) => 0xfb],
+    /// GameDataMan -> player game data -> equipment, starting with the arm
+    /// style. Left armament 1 is then at +0x39c, as in `ChrAsm` from
+    /// vswarte/fromsoftware-rs and the TGA cheat table.
+    chr_asm: usize = [(1, 2, 0) => 0x328],
+    /// GameDataMan -> player game data -> inventory item list. Equipment data
+    /// is at +0x2b0, with its `ChrAsm` at +0x78 and its inventory at +0x158,
+    /// whose item list starts at +0x10, as in vswarte/fromsoftware-rs.
+    equip_inventory: usize = [(1, 2, 0) => 0x418],
+    /// CSFlipper -> global game speed.
+    game_speed: usize = [(1, 2, 0) => 0x2d4],
+    /// Subtracted from the GroupMask base address.
+    group_mask_shift: usize = [(1, 2, 0) => 0, (1, 5, 0) => 8, (1, 6, 0) => 0],
+    /// Geometry group flags, relative to the shifted GroupMask.
+    show_geom: &'static [usize] = [
+        (1, 2, 0) => &[2, 3, 4, 5, 6, 7, 8, 0, 0xa, 0xb, 0xc, 0xd, 0xf, 0x10, 0x11, 0x12],
+        (1, 5, 0) => &[0, 1, 2, 3, 5, 6, 8, 0xa, 0xb, 0xc, 0xd, 0xe, 0xf],
+        (1, 6, 0) => &[2, 3, 4, 5, 6, 7, 8, 0, 0xa, 0xb, 0xc, 0xd, 0xf, 0x10, 0x11, 0x12],
+    ],
+    /// Character group flag, relative to the shifted GroupMask.
+    show_chr: usize = [(1, 2, 0) => 0xe, (1, 5, 0) => 4, (1, 6, 0) => 0xe],
+    /// Values written by the force state debug action.
+    dbg_action_force_state_values: (u8, u8) = [(1, 2, 0) => (0xb1, 0xb2), (1, 8, 0) => (0xc1, 0xc2)],
+}
+
+/// Canonical name of an offset, or `None` if it isn't known.
+pub fn offset_name(name: &str) -> Option<&'static str> {
+    OFFSET_NAMES.iter().find(|n| **n == name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::pointers::ChrAsm;
+    use crate::version::Version;
+
+    /// Every [`Version`], found by trying all plausible version numbers.
+    fn all_versions() -> Vec<Version> {
+        (1..4)
+            .flat_map(|major| (0..20).map(move |minor| (major, minor)))
+            .flat_map(|(major, minor)| (0..10).map(move |patch| (major, minor, patch)))
+            .filter_map(|v| Version::try_from(v).ok())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_version_resolves_every_offset() {
+        assert!(Offsets::tables_are_sorted());
+
+        let versions = all_versions();
+        assert_eq!(versions.len(), 24);
+
+        for version in versions {
+            let (major, minor, patch) = version.into();
+            if let Err(e) = Offsets::resolve((major, minor, patch), &BTreeMap::new()) {
+                panic!("{major}.{minor}.{patch}: {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        let offsets = |v| Offsets::resolve(v, &BTreeMap::new()).unwrap();
+
+        assert_eq!(offsets((1, 7, 0)).map_id, 0x6c0);
+        assert_eq!(offsets((1, 8, 0)).map_id, 0x6d0);
+        assert_eq!(offsets((1, 5, 0)).group_mask_shift, 8);
+        assert_eq!(offsets((1, 6, 0)).show_geom.len(), 16);
+        assert_eq!(offsets((2, 0, 1)).character_blessings, None);
+        assert_eq!(offsets((2, 6, 0)).character_blessings, Some(0xfc));
+        let chr_asm = offsets((1, 2, 0)).chr_asm;
+        assert_eq!(chr_asm + mem::offset_of!(ChrAsm, equip_param_ids), 0x39c);
+        // Unknown newer versions use the latest values.
+        assert_eq!(offsets((9, 0, 0)).torrent_enemy_ins, 0x1cc90);
+        assert!(Offsets::resolve((1, 0, 0), &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let overrides = BTreeMap::from([("player_ins", 0x1f000), ("character_blessings", 0x100)]);
+        let offsets = Offsets::resolve((2, 0, 0), &overrides).unwrap();
+        assert_eq!(offsets.player_ins, 0x1f000);
+        assert_eq!(offsets.character_blessings, Some(0x100));
+        assert_eq!(offsets.map_id, 0x6d0);
+
+        let overrides = BTreeMap::from([("show_geom", 0x10)]);
+        assert!(Offsets::resolve((2, 0, 0), &overrides).is_err());
+        assert_eq!(offset_name("not_an_offset"), None);
+    }
+}
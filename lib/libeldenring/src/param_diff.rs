@@ -0,0 +1,196 @@
+//! Edits made to the params since they were first read.
+//!
+//! [`Params`] keeps a snapshot of every param file from the first time the
+//! params are read. Rows are compared with it field by field, and can be
+//! reverted to it one field, row or param at a time. Reverts are written
+//! through [`FieldWriter`], so they are journaled like any other param edit.
+
+use std::collections::BTreeMap;
+
+use crate::param_io::{FieldReader, FieldWriter, Value};
+use crate::params::Params;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// The edited fields of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowChange {
+    pub param: String,
+    pub id: u64,
+    pub fields: Vec<FieldChange>,
+}
+
+fn row_ids(params: &Params, param: &str) -> Vec<u64> {
+    unsafe { params.iter_param_ids(param) }.map(Iterator::collect).unwrap_or_default()
+}
+
+fn row_index(params: &Params, param: &str, id: u64) -> Result<usize, String> {
+    params.row_index(param, id).ok_or_else(|| format!("{param}: unknown row {id}"))
+}
+
+/// Compares floats by their bits, so that NaN is equal to itself.
+fn same_value(a: Value, b: Value) -> bool {
+    match (a, b) {
+        (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+        (a, b) => a == b,
+    }
+}
+
+fn changed_fields(params: &Params, param: &str, idx: usize) -> Vec<FieldChange> {
+    let Some(original) = params.original_fields(param, idx) else {
+        return Vec::new();
+    };
+
+    let mut current = FieldReader::default();
+    params.visit_param_item(param, idx, &mut current);
+
+    original
+        .into_iter()
+        .zip(current.0)
+        .filter(|((_, old), (_, new))| !same_value(*old, *new))
+        .map(|((field, old), (_, new))| FieldChange { field, old, new })
+        .collect()
+}
+
+fn revert_fields(params: &Params, param: &str, idx: usize, changes: &[FieldChange]) {
+    let values = changes.iter().map(|c| (c.field.clone(), c.old)).collect::<BTreeMap<_, _>>();
+    params.visit_param_item_mut(param, idx, &mut FieldWriter::new(&values));
+}
+
+/// Edited fields of a row.
+pub fn row_changes(params: &Params, param: &str, id: u64) -> Result<Vec<FieldChange>, String> {
+    Ok(changed_fields(params, param, row_index(params, param, id)?))
+}
+
+/// Edited rows of a param.
+pub fn param_changes(params: &Params, param: &str) -> Vec<RowChange> {
+    if !params.is_param_modified(param) {
+        return Vec::new();
+    }
+
+    row_ids(params, param)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, id)| {
+            let fields = changed_fields(params, param, idx);
+            (!fields.is_empty()).then(|| RowChange { param: param.to_string(), id, fields })
+        })
+        .collect()
+}
+
+/// Edited rows of every param.
+pub fn all_changes(params: &Params) -> Vec<RowChange> {
+    params.keys().flat_map(|param| param_changes(params, param)).collect()
+}
+
+/// Reverts a field. Returns whether it was edited.
+pub fn revert_field(params: &Params, param: &str, id: u64, field: &str) -> Result<bool, String> {
+    let idx = row_index(params, param, id)?;
+    let changes = changed_fields(params, param, idx)
+        .into_iter()
+        .filter(|c| c.field == field)
+        .collect::<Vec<_>>();
+
+    revert_fields(params, param, idx, &changes);
+    params.flush()?;
+    Ok(!changes.is_empty())
+}
+
+/// Reverts every field of a row. Returns the number of fields reverted.
+pub fn revert_row(params: &Params, param: &str, id: u64) -> Result<usize, String> {
+    let idx = row_index(params, param, id)?;
+    let changes = changed_fields(params, param, idx);
+
+    revert_fields(params, param, idx, &changes);
+    params.flush()?;
+    Ok(changes.len())
+}
+
+/// Reverts every row of a param. Returns the number of rows reverted.
+pub fn revert_param(params: &Params, param: &str) -> Result<usize, String> {
+    let changes = param_changes(params, param);
+    revert_changes(params, &changes)?;
+    Ok(changes.len())
+}
+
+/// Reverts every param. Returns the number of rows reverted.
+pub fn revert_all(params: &Params) -> Result<usize, String> {
+    let changes = all_changes(params);
+    revert_changes(params, &changes)?;
+    Ok(changes.len())
+}
+
+fn revert_changes(params: &Params, changes: &[RowChange]) -> Result<(), String> {
+    for change in changes {
+        let idx = row_index(params, &change.param, change.id)?;
+        revert_fields(params, &change.param, idx, &change.fields);
+    }
+    params.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::param_io::tests::{goods, params};
+    use crate::params::EquipParamGoods;
+
+    fn edit(params: &Params, id: u64, weight: f32, sort_id: i32) {
+        for p in unsafe { params.iter_param::<EquipParamGoods>("EquipParamGoods") }.unwrap() {
+            if p.id == id {
+                let row = p.param.unwrap();
+                row.weight = weight;
+                row.sort_id = sort_id;
+            }
+        }
+    }
+
+    #[test]
+    fn test_changes() {
+        let params = params();
+        assert!(all_changes(&params).is_empty());
+        assert!(!params.is_param_modified("EquipParamGoods"));
+
+        edit(&params, 200, 3.5, 7);
+        assert!(params.is_param_modified("EquipParamGoods"));
+        assert_eq!(all_changes(&params), vec![RowChange {
+            param: "EquipParamGoods".to_string(),
+            id: 200,
+            fields: vec![FieldChange {
+                field: "weight".to_string(),
+                old: Value::Float(2.5),
+                new: Value::Float(3.5),
+            }],
+        }]);
+        assert!(row_changes(&params, "EquipParamGoods", 100).unwrap().is_empty());
+        assert!(row_changes(&params, "EquipParamGoods", 300).is_err());
+
+        // Changing a value back is not an edit.
+        edit(&params, 200, 2.5, 7);
+        assert!(all_changes(&params).is_empty());
+    }
+
+    #[test]
+    fn test_revert() {
+        let params = params();
+        edit(&params, 100, 9., 1);
+        edit(&params, 200, 9., 2);
+
+        assert_eq!(revert_field(&params, "EquipParamGoods", 100, "sort_id"), Ok(true));
+        assert_eq!(revert_field(&params, "EquipParamGoods", 100, "sort_id"), Ok(false));
+        assert_eq!(goods(&params), vec![(100, 9., 5), (200, 9., 2)]);
+
+        assert_eq!(revert_row(&params, "EquipParamGoods", 200), Ok(2));
+        assert_eq!(goods(&params), vec![(100, 9., 5), (200, 2.5, 7)]);
+
+        edit(&params, 200, 9., 2);
+        assert_eq!(revert_all(&params), Ok(2));
+        assert_eq!(goods(&params), vec![(100, 0.1, 5), (200, 2.5, 7)]);
+        assert!(!params.is_param_modified("EquipParamGoods"));
+        assert_eq!(revert_param(&params, "EquipParamGoods"), Ok(0));
+    }
+}
@@ -0,0 +1,547 @@
+//! Export and import of param rows as JSON or CSV, keyed by row ID and field
+//! name.
+//!
+//! Rows are read and written through [`ParamVisitor`]s, so every param in
+//! [`PARAM_VTABLE`] is supported. Imports are checked in full before anything
+//! is written: every param, row and field must exist, and every value must
+//! fit the type of its field.
+//!
+//! JSON dumps hold any number of params:
+//!
+//! ```json
+//! {
+//!   "EquipParamWeapon": {
+//!     "1000000": { "name": "Dagger", "fields": { "weight": 1.5, "sort_id": 100 } }
+//!   }
+//! }
+//! ```
+//!
+//! CSV files hold a single param, with one row per line and one field per
+//! column after `ID` and `Name`. Empty cells are left untouched on import.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::params::{Params, PARAM_NAMES, PARAM_VTABLE};
+use crate::ParamVisitor;
+
+/// A field value, independent of the field's type.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+impl Value {
+    pub(crate) fn parse(s: &str) -> Option<Value> {
+        match s {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            s => s.parse().map(Value::Int).or_else(|_| s.parse().map(Value::Float)).ok(),
+        }
+    }
+
+    /// Widens an `f32` keeping its shortest decimal representation, so that
+    /// e.g. `0.1f32` is exported as `0.1`.
+    pub(crate) fn from_f32(v: f32) -> Value {
+        Value::Float(v.to_string().parse().unwrap_or(v as f64))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Float(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowDump {
+    /// Row name from [`PARAM_NAMES`]. Ignored on import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub fields: BTreeMap<String, Value>,
+}
+
+/// The rows of a param, keyed by row ID.
+pub type ParamDump = BTreeMap<u64, RowDump>;
+
+/// Params keyed by name.
+pub type ParamsDump = BTreeMap<String, ParamDump>;
+
+/// Reads every field of a row, in declaration order.
+#[derive(Debug, Default)]
+pub struct FieldReader(pub Vec<(String, Value)>);
+
+impl FieldReader {
+    fn push(&mut self, name: &str, value: Value) {
+        self.0.push((name.to_string(), value));
+    }
+}
+
+impl ParamVisitor for FieldReader {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.push(name, Value::Int(*v as i64));
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        self.push(name, Value::from_f32(*v));
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        self.push(name, Value::Bool(*v));
+    }
+}
+
+/// Checks values against the fields of a row and, unless created with
+/// [`FieldWriter::check`], writes them.
+#[derive(Debug)]
+pub struct FieldWriter<'a> {
+    values: &'a BTreeMap<String, Value>,
+    write: bool,
+    visited: BTreeSet<String>,
+    errors: Vec<String>,
+}
+
+impl<'a> FieldWriter<'a> {
+    pub fn new(values: &'a BTreeMap<String, Value>) -> Self {
+        FieldWriter { values, write: true, visited: BTreeSet::new(), errors: Vec::new() }
+    }
+
+    /// A writer that only checks the values.
+    pub fn check(values: &'a BTreeMap<String, Value>) -> Self {
+        FieldWriter { write: false, ..FieldWriter::new(values) }
+    }
+
+    /// Errors found while visiting, including values for fields the row
+    /// doesn't have.
+    pub fn finish(self) -> Vec<String> {
+        let unknown = self
+            .values
+            .keys()
+            .filter(|name| !self.visited.contains(*name))
+            .map(|name| format!("{name}: unknown field"));
+
+        self.errors.into_iter().chain(unknown).collect()
+    }
+
+    fn value(&mut self, name: &str) -> Option<Value> {
+        let value = self.values.get(name).copied()?;
+        self.visited.insert(name.to_string());
+        Some(value)
+    }
+
+    fn set<T>(&mut self, name: &str, v: &mut T, value: Result<T, String>) {
+        match value {
+            Ok(value) if self.write => *v = value,
+            Ok(_) => {},
+            Err(e) => self.errors.push(format!("{name}: {e}")),
+        }
+    }
+
+    fn visit_int<T: TryFrom<i64>>(&mut self, name: &str, v: &mut T, ty: &str) {
+        let Some(value) = self.value(name) else {
+            return;
+        };
+
+        let value = match value {
+            Value::Int(i) => T::try_from(i).map_err(|_| format!("{i} is out of range for {ty}")),
+            value => Err(format!("{value} is not a valid {ty}")),
+        };
+        self.set(name, v, value);
+    }
+}
+
+impl ParamVisitor for FieldWriter<'_> {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.visit_int(name, v, "u8");
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.visit_int(name, v, "u16");
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.visit_int(name, v, "u32");
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.visit_int(name, v, "i8");
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.visit_int(name, v, "i16");
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.visit_int(name, v, "i32");
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        let Some(value) = self.value(name) else {
+            return;
+        };
+
+        let value = match value {
+            Value::Int(i) => Ok(i as f32),
+            Value::Float(f) if f.is_finite() && f.abs() <= f32::MAX as f64 => Ok(f as f32),
+            Value::Float(f) => Err(format!("{f} is out of range for f32")),
+            value => Err(format!("{value} is not a valid f32")),
+        };
+        self.set(name, v, value);
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        let Some(value) = self.value(name) else {
+            return;
+        };
+
+        let value = match value {
+            Value::Bool(b) => Ok(b),
+            Value::Int(i @ (0 | 1)) => Ok(i == 1),
+            value => Err(format!("{value} is not a valid bool")),
+        };
+        self.set(name, v, value);
+    }
+}
+
+/// Row IDs of a param, in order.
+fn row_ids(params: &Params, param: &str) -> Result<Vec<u64>, String> {
+    if !PARAM_VTABLE.contains_key(param) {
+        return Err(format!("Unknown param {param}"));
+    }
+
+    unsafe { params.iter_param_ids(param) }
+        .map(Iterator::collect)
+        .ok_or_else(|| format!("Param {param} is not loaded"))
+}
+
+pub(crate) fn row_name(param: &str, id: u64) -> Option<String> {
+    PARAM_NAMES.get(param)?.get(&(id as usize)).cloned()
+}
+
+fn read_rows(params: &Params, param: &str) -> Result<Vec<(u64, FieldReader)>, String> {
+    Ok(row_ids(params, param)?
+        .into_iter()
+        .enumerate()
+        .map(|(idx, id)| {
+            let mut reader = FieldReader::default();
+            params.visit_param_item(param, idx, &mut reader);
+            (id, reader)
+        })
+        .collect())
+}
+
+pub fn export_param(params: &Params, param: &str) -> Result<ParamDump, String> {
+    Ok(read_rows(params, param)?
+        .into_iter()
+        .map(|(id, reader)| {
+            (id, RowDump { name: row_name(param, id), fields: reader.0.into_iter().collect() })
+        })
+        .collect())
+}
+
+/// Exports every loaded param that has a generated struct.
+pub fn export_all(params: &Params) -> ParamsDump {
+    params
+        .keys()
+        .filter_map(|param| Some((param.clone(), export_param(params, param).ok()?)))
+        .collect()
+}
+
+/// Checks the values in `dump` against the params without writing them.
+pub fn check(params: &Params, dump: &ParamsDump) -> Vec<String> {
+    check_rows(params, dump).1
+}
+
+/// Writes the values in `dump` to the params, then flushes them. Nothing is
+/// written if any value fails to check. Returns the number of rows written.
+pub fn import(params: &Params, dump: &ParamsDump) -> Result<usize, Vec<String>> {
+    let (rows, errors) = check_rows(params, dump);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    for &(param, idx, _, fields) in &rows {
+        params.visit_param_item_mut(param, idx, &mut FieldWriter::new(fields));
+    }
+    params.flush().map_err(|e| vec![e])?;
+
+    Ok(rows.len())
+}
+
+type CheckedRow<'a> = (&'a str, usize, u64, &'a BTreeMap<String, Value>);
+
+/// The rows of `dump` with their index in the params, and the errors found
+/// looking them up and checking their values.
+fn check_rows<'a>(params: &Params, dump: &'a ParamsDump) -> (Vec<CheckedRow<'a>>, Vec<String>) {
+    let mut errors = Vec::new();
+    let mut rows = Vec::new();
+
+    for (param, param_dump) in dump {
+        let ids = match row_ids(params, param) {
+            Ok(ids) => ids,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            },
+        };
+
+        // Row ids to indices. The first row wins if an id is repeated.
+        let mut indices = HashMap::with_capacity(ids.len());
+        for (idx, id) in ids.into_iter().enumerate() {
+            indices.entry(id).or_insert(idx);
+        }
+
+        for (id, row) in param_dump {
+            match indices.get(id) {
+                Some(&idx) => rows.push((param.as_str(), idx, *id, &row.fields)),
+                None => errors.push(format!("{param}: unknown row {id}")),
+            }
+        }
+    }
+
+    for &(param, idx, id, fields) in &rows {
+        let mut writer = FieldWriter::check(fields);
+        params.visit_param_item(param, idx, &mut writer);
+        errors.extend(writer.finish().into_iter().map(|e| format!("{param}[{id}].{e}")));
+    }
+
+    (rows, errors)
+}
+
+pub fn to_json(dump: &ParamsDump) -> String {
+    serde_json::to_string_pretty(dump).expect("Param dumps are always serializable")
+}
+
+pub fn from_json(json: &str) -> Result<ParamsDump, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid param dump: {e}"))
+}
+
+/// Exports a param as CSV, with fields in declaration order.
+pub fn param_to_csv(params: &Params, param: &str) -> Result<String, String> {
+    let rows = read_rows(params, param)?;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_error = |e: csv::Error| format!("Couldn't write CSV: {e}");
+
+    let fields = rows.first().map(|(_, reader)| &reader.0[..]).unwrap_or_default();
+    writer
+        .write_record(
+            ["ID", "Name"].into_iter().chain(fields.iter().map(|(name, _)| name.as_str())),
+        )
+        .map_err(csv_error)?;
+
+    for (id, reader) in &rows {
+        writer
+            .write_record(
+                [id.to_string(), row_name(param, *id).unwrap_or_default()]
+                    .into_iter()
+                    .chain(reader.0.iter().map(|(_, value)| value.to_string())),
+            )
+            .map_err(csv_error)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| format!("Couldn't write CSV: {e}"))?;
+    String::from_utf8(bytes).map_err(|e| format!("Couldn't write CSV: {e}"))
+}
+
+/// Parses a param exported by [`param_to_csv`], for [`import`].
+pub fn param_from_csv(csv: &str) -> Result<ParamDump, String> {
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Invalid CSV: {e}"))?.clone();
+
+    if headers.get(0) != Some("ID") || headers.get(1) != Some("Name") {
+        return Err("CSV must start with the ID and Name columns".to_string());
+    }
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(line, record)| {
+            let line = line + 2;
+            let record = record.map_err(|e| format!("Invalid CSV at line {line}: {e}"))?;
+            let id = record
+                .get(0)
+                .and_then(|id| id.parse::<u64>().ok())
+                .ok_or_else(|| format!("Invalid row ID at line {line}"))?;
+
+            let fields = headers
+                .iter()
+                .zip(record.iter())
+                .skip(2)
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(name, cell)| {
+                    Value::parse(cell)
+                        .map(|value| (name.to_string(), value))
+                        .ok_or_else(|| format!("Invalid value {cell} for {name} at line {line}"))
+                })
+                .collect::<Result<_, String>>()?;
+
+            let name = record.get(1).filter(|name| !name.is_empty()).map(str::to_string);
+            Ok((id, RowDump { name, fields }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::params::tests::param_file;
+    use crate::params::{EquipParamGoods, Params};
+    use crate::regulation::BinderFile;
+    use crate::ParamStruct;
+
+    fn goods_row(weight: f32, sort_id: i32) -> Vec<u8> {
+        let mut goods: EquipParamGoods = unsafe { mem::zeroed() };
+        goods.weight = weight;
+        goods.sort_id = sort_id;
+        unsafe {
+            std::slice::from_raw_parts(
+                &goods as *const EquipParamGoods as *const u8,
+                mem::size_of::<EquipParamGoods>(),
+            )
+        }
+        .to_vec()
+    }
+
+    pub(crate) fn params() -> Params {
+        let file = param_file(&[(100, &goods_row(0.1, 5)), (200, &goods_row(2.5, 7))]);
+        Params::from_param_files(&[BinderFile {
+            id: 0,
+            name: "EquipParamGoods.param".to_string(),
+            data: file,
+        }])
+        .unwrap()
+    }
+
+    pub(crate) fn goods(params: &Params) -> Vec<(u64, f32, i32)> {
+        unsafe { params.iter_param::<EquipParamGoods>("EquipParamGoods") }
+            .unwrap()
+            .map(|p| {
+                let row = p.param.unwrap();
+                (p.id, row.weight, row.sort_id)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_visitors() {
+        let mut row: EquipParamGoods = unsafe { mem::zeroed() };
+        row.weight = 0.1;
+
+        let mut reader = FieldReader::default();
+        row.visit(&mut reader);
+        assert!(reader.0.contains(&("weight".to_string(), Value::Float(0.1))));
+
+        let values = BTreeMap::from([
+            ("weight".to_string(), Value::Int(3)),
+            ("sort_id".to_string(), Value::Int(-4)),
+        ]);
+        let mut writer = FieldWriter::new(&values);
+        row.visit(&mut writer);
+        assert_eq!(writer.finish(), Vec::<String>::new());
+        assert_eq!((row.weight, row.sort_id), (3., -4));
+
+        let values = BTreeMap::from([
+            ("sort_id".to_string(), Value::Float(1.5)),
+            ("nope".to_string(), Value::Int(1)),
+        ]);
+        let mut writer = FieldWriter::check(&values);
+        row.visit(&mut writer);
+        assert_eq!(writer.finish(), vec!["sort_id: 1.5 is not a valid i32", "nope: unknown field"]);
+        assert_eq!(row.sort_id, -4);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let params = params();
+        let mut dump = from_json(&to_json(&export_all(&params))).unwrap();
+        assert_eq!(dump["EquipParamGoods"][&100].fields["weight"], Value::Float(0.1));
+
+        let row = dump.get_mut("EquipParamGoods").unwrap().get_mut(&200).unwrap();
+        row.fields.insert("weight".to_string(), Value::Float(4.25));
+        row.fields.insert("sort_id".to_string(), Value::Int(9));
+        assert_eq!(import(&params, &dump), Ok(2));
+        assert_eq!(goods(&params), vec![(100, 0.1, 5), (200, 4.25, 9)]);
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let params = params();
+        let csv = param_to_csv(&params, "EquipParamGoods").unwrap();
+        assert!(csv.starts_with("ID,Name,"));
+
+        let mut dump = param_from_csv(&csv).unwrap();
+        assert_eq!(dump[&100].fields["weight"], Value::Float(0.1));
+        dump.get_mut(&100).unwrap().fields.insert("weight".to_string(), Value::Float(1.75));
+
+        let dump = BTreeMap::from([("EquipParamGoods".to_string(), dump)]);
+        assert_eq!(import(&params, &dump), Ok(2));
+        assert_eq!(goods(&params), vec![(100, 1.75, 5), (200, 2.5, 7)]);
+
+        let partial = param_from_csv("ID,Name,sort_id,weight\n200,,,8\n").unwrap();
+        assert_eq!(partial[&200].fields.len(), 1);
+        assert!(param_from_csv("Row,sort_id\n1,2\n").is_err());
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let params = params();
+        let fields = |name: &str, value| {
+            BTreeMap::from([(200, RowDump {
+                name: None,
+                fields: BTreeMap::from([(name.to_string(), value)]),
+            })])
+        };
+
+        let dump = BTreeMap::from([
+            ("EquipParamGoods".to_string(), fields("sort_id", Value::Int(1 << 40))),
+            ("NotAParam".to_string(), BTreeMap::new()),
+        ]);
+        let errors = import(&params, &dump).unwrap_err();
+        assert_eq!(errors, vec![
+            "Unknown param NotAParam",
+            "EquipParamGoods[200].sort_id: 1099511627776 is out of range for i32",
+        ]);
+
+        let mut dump =
+            BTreeMap::from([("EquipParamGoods".to_string(), fields("weight", Value::Int(1)))]);
+        dump.get_mut("EquipParamGoods")
+            .unwrap()
+            .insert(300, RowDump { name: None, fields: BTreeMap::new() });
+        assert_eq!(import(&params, &dump).unwrap_err(), vec!["EquipParamGoods: unknown row 300"]);
+        assert_eq!(check(&params, &dump), vec!["EquipParamGoods: unknown row 300"]);
+
+        // Nothing was written.
+        assert_eq!(goods(&params), vec![(100, 0.1, 5), (200, 2.5, 7)]);
+    }
+}
@@ -0,0 +1,177 @@
+//! Field metadata from [Paramdex](https://github.com/soulsmods/Paramdex):
+//! display names, descriptions, valid ranges and enum labels.
+//!
+//! The table is generated by `cargo xtask codegen` along with the param
+//! layouts, and is keyed by param and field name as passed to
+//! [`ParamVisitor`](crate::ParamVisitor). Fields without any metadata are
+//! left out.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+pub static PARAM_META: Lazy<ParamMeta> =
+    Lazy::new(|| serde_json::from_str(include_str!("codegen/param_meta.json")).unwrap());
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ParamMeta {
+    /// Labels of the enum values, by enum name.
+    #[serde(default)]
+    enums: HashMap<String, Vec<EnumOption>>,
+    #[serde(default)]
+    params: HashMap<String, HashMap<String, FieldMeta>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EnumOption {
+    pub value: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FieldMeta {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    #[serde(rename = "enum")]
+    pub enum_name: Option<String>,
+}
+
+impl ParamMeta {
+    pub fn field(&self, param: &str, field: &str) -> Option<&FieldMeta> {
+        self.params.get(param)?.get(field)
+    }
+
+    /// Values of the field's enum, if it has one.
+    pub fn enum_options(&self, field: &FieldMeta) -> Option<&[EnumOption]> {
+        self.enums.get(field.enum_name.as_ref()?).map(Vec::as_slice)
+    }
+}
+
+impl FieldMeta {
+    /// Clamps a value to the field's range. Either bound may be missing.
+    pub fn clamp(&self, v: f64) -> f64 {
+        let v = self.min.map_or(v, |min| v.max(min));
+        self.max.map_or(v, |max| v.min(max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use super::*;
+    use crate::params::EquipParamWeapon;
+    use crate::{ParamStruct, ParamVisitor};
+
+    #[test]
+    fn test_meta() {
+        let meta: ParamMeta = serde_json::from_str(
+            r#"{
+                "enums": {
+                    "GOODS_TYPE": [
+                        { "value": 0, "name": "Normal Item" },
+                        { "value": 1, "name": "Key Item" }
+                    ]
+                },
+                "params": {
+                    "EquipParamGoods": {
+                        "goods_type": { "display_name": "Goods Type", "enum": "GOODS_TYPE" },
+                        "weight": { "min": 0.0, "max": 99.0 }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let goods_type = meta.field("EquipParamGoods", "goods_type").unwrap();
+        assert_eq!(goods_type.display_name.as_deref(), Some("Goods Type"));
+        assert_eq!(meta.enum_options(goods_type).unwrap()[1].name, "Key Item");
+        assert_eq!(goods_type.clamp(-5.), -5.);
+
+        let weight = meta.field("EquipParamGoods", "weight").unwrap();
+        assert_eq!(meta.enum_options(weight), None);
+        assert_eq!(weight.clamp(-5.), 0.);
+        assert_eq!(weight.clamp(100.), 99.);
+        assert_eq!(weight.clamp(2.5), 2.5);
+
+        assert_eq!(meta.field("EquipParamGoods", "sort_id"), None);
+        assert_eq!(meta.field("EquipParamWeapon", "weight"), None);
+    }
+
+    #[test]
+    fn test_generated_meta() {
+        // Every param in the generated table must exist.
+        assert!(PARAM_META.params.keys().all(|k| crate::params::PARAM_VTABLE.contains_key(k)));
+    }
+
+    /// Field names of a param row, in visiting order.
+    struct FieldNames(Vec<String>);
+
+    impl ParamVisitor for FieldNames {
+        fn visit_u8(&mut self, name: &str, _: &mut u8) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_u16(&mut self, name: &str, _: &mut u16) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_u32(&mut self, name: &str, _: &mut u32) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_i8(&mut self, name: &str, _: &mut i8) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_i16(&mut self, name: &str, _: &mut i16) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_i32(&mut self, name: &str, _: &mut i32) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_f32(&mut self, name: &str, _: &mut f32) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_bool(&mut self, name: &str, _: &mut bool) {
+            self.0.push(name.to_string());
+        }
+    }
+
+    /// Checks the lookups against a checked-in table in the shape that
+    /// `cargo xtask codegen` writes, keyed by real EquipParamWeapon fields.
+    #[test]
+    fn test_fixture_meta() {
+        let meta: ParamMeta =
+            serde_json::from_str(include_str!("fixtures/param_meta.json")).unwrap();
+
+        let mut row: EquipParamWeapon = unsafe { mem::zeroed() };
+        let mut names = FieldNames(Vec::new());
+        row.visit(&mut names);
+        for field in meta.params["EquipParamWeapon"].keys() {
+            assert!(names.0.contains(field), "{field}");
+        }
+
+        let category = meta.field("EquipParamWeapon", "weapon_category").unwrap();
+        assert_eq!(category.display_name.as_deref(), Some("Weapon Category"));
+        assert!(category.description.is_some());
+        let options = meta.enum_options(category).unwrap();
+        assert_eq!(options.iter().map(|o| o.value).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(options[1].name, "Straight Sword");
+        assert_eq!(category.clamp(5.), 2.);
+
+        let weight = meta.field("EquipParamWeapon", "weight").unwrap();
+        assert_eq!(meta.enum_options(weight), None);
+        assert_eq!(weight.clamp(-1.), 0.);
+
+        let sort_id = meta.field("EquipParamWeapon", "sort_id").unwrap();
+        assert_eq!((sort_id.min, sort_id.max), (None, None));
+        assert_eq!(sort_id.clamp(-1.), -1.);
+    }
+}
@@ -0,0 +1,195 @@
+//! Search across every param for fields by name and/or value.
+//!
+//! Rows are scanned with a [`ParamVisitor`] that keeps the matching fields
+//! only. Field names match if they contain the query, ignoring case. Values
+//! match if they are equal as numbers, so `12` finds both integer and float
+//! fields.
+
+use crate::param_io::{self, Value};
+use crate::params::{Params, PARAM_VTABLE};
+use crate::ParamVisitor;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Part of the field name, lowercase.
+    field: Option<String>,
+    value: Option<Value>,
+}
+
+impl Query {
+    /// Builds a query from user input. Empty strings match anything, but
+    /// at least one of them must be set.
+    pub fn new(field: &str, value: &str) -> Result<Query, String> {
+        let field = field.trim();
+        let value = value.trim();
+
+        if field.is_empty() && value.is_empty() {
+            return Err("Enter a field name or a value".to_string());
+        }
+
+        Ok(Query {
+            field: (!field.is_empty()).then(|| field.to_lowercase()),
+            value: if value.is_empty() {
+                None
+            } else {
+                Some(Value::parse(value).ok_or_else(|| format!("\"{value}\" is not a value"))?)
+            },
+        })
+    }
+
+    fn matches(&self, name: &str, value: Value) -> bool {
+        self.field.as_ref().is_none_or(|field| name.to_lowercase().contains(field))
+            && self.value.is_none_or(|v| same_value(v, value))
+    }
+}
+
+fn same_value(a: Value, b: Value) -> bool {
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        (Value::Bool(_), _) | (_, Value::Bool(_)) => false,
+        (a, b) => as_f64(a) == as_f64(b),
+    }
+}
+
+fn as_f64(v: Value) -> f64 {
+    match v {
+        Value::Bool(v) => v as u8 as f64,
+        Value::Int(v) => v as f64,
+        Value::Float(v) => v,
+    }
+}
+
+/// A field that matched a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub param: String,
+    pub id: u64,
+    /// Position of the row in its param.
+    pub index: usize,
+    pub name: Option<String>,
+    pub field: String,
+    pub value: Value,
+}
+
+/// Keeps the fields of a visited row that match the query.
+struct Matcher<'a> {
+    query: &'a Query,
+    matches: Vec<(String, Value)>,
+}
+
+impl Matcher<'_> {
+    fn check(&mut self, name: &str, value: Value) {
+        if self.query.matches(name, value) {
+            self.matches.push((name.to_string(), value));
+        }
+    }
+}
+
+impl ParamVisitor for Matcher<'_> {
+    fn visit_u8(&mut self, name: &str, v: &mut u8) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_u16(&mut self, name: &str, v: &mut u16) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_u32(&mut self, name: &str, v: &mut u32) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i8(&mut self, name: &str, v: &mut i8) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i16(&mut self, name: &str, v: &mut i16) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_i32(&mut self, name: &str, v: &mut i32) {
+        self.check(name, Value::Int(*v as i64));
+    }
+
+    fn visit_f32(&mut self, name: &str, v: &mut f32) {
+        self.check(name, Value::from_f32(*v));
+    }
+
+    fn visit_bool(&mut self, name: &str, v: &mut bool) {
+        self.check(name, Value::Bool(*v));
+    }
+}
+
+/// Scans every loaded param that has a generated struct. Stops after
+/// `limit` results.
+pub fn search(params: &Params, query: &Query, limit: usize) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+
+    for param in params.keys().filter(|param| PARAM_VTABLE.contains_key(*param)) {
+        let Some(ids) = (unsafe { params.iter_param_ids(param) }) else {
+            continue;
+        };
+
+        for (index, id) in ids.enumerate() {
+            let mut matcher = Matcher { query, matches: Vec::new() };
+            params.visit_param_item(param, index, &mut matcher);
+
+            for (field, value) in matcher.matches {
+                if results.len() == limit {
+                    return results;
+                }
+                results.push(SearchResult {
+                    param: param.clone(),
+                    id,
+                    index,
+                    name: param_io::row_name(param, id),
+                    field,
+                    value,
+                });
+            }
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::param_io::tests::params;
+
+    fn found(results: &[SearchResult]) -> Vec<(u64, usize, &str, Value)> {
+        results.iter().map(|r| (r.id, r.index, r.field.as_str(), r.value)).collect()
+    }
+
+    #[test]
+    fn test_query() {
+        assert!(Query::new(" ", "").is_err());
+        assert!(Query::new("", "abc").is_err());
+        assert_eq!(Query::new("Sort", "").unwrap().field.as_deref(), Some("sort"));
+        assert_eq!(Query::new("", "2.5").unwrap().value, Some(Value::Float(2.5)));
+    }
+
+    #[test]
+    fn test_search() {
+        let params = params();
+
+        let results = search(&params, &Query::new("SORT_ID", "").unwrap(), 100);
+        assert_eq!(found(&results), vec![
+            (100, 0, "sort_id", Value::Int(5)),
+            (200, 1, "sort_id", Value::Int(7)),
+        ]);
+        assert!(results.iter().all(|r| r.param == "EquipParamGoods"));
+
+        // Integers match floats and the other way around.
+        let results = search(&params, &Query::new("", "2.5").unwrap(), 100);
+        assert_eq!(found(&results), vec![(200, 1, "weight", Value::Float(2.5))]);
+        let results = search(&params, &Query::new("", "7.0").unwrap(), 100);
+        assert_eq!(found(&results), vec![(200, 1, "sort_id", Value::Int(7))]);
+
+        let results = search(&params, &Query::new("weight", "0.1").unwrap(), 100);
+        assert_eq!(found(&results), vec![(100, 0, "weight", Value::Float(0.1))]);
+
+        let results = search(&params, &Query::new("", "0").unwrap(), 3);
+        assert_eq!(results.len(), 3);
+    }
+}
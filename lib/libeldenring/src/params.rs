@@ -1,21 +1,36 @@
 use std::collections::{BTreeMap, HashMap};
 use std::ffi::c_void;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::Arc;
+#[cfg(windows)]
+use std::thread;
+#[cfg(windows)]
 use std::time::Duration;
-use std::{mem, thread};
 
 use log::*;
 use once_cell::sync::Lazy;
-use parking_lot::RwLock;
+use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use widestring::U16CStr;
-use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+#[cfg(windows)]
 use windows::Win32::System::Memory::{VirtualQuery, MEMORY_BASIC_INFORMATION, PAGE_READWRITE};
 
 pub use crate::codegen::param_data::*;
-use crate::prelude::base_addresses::BaseAddresses;
+use crate::journal::JOURNAL;
+use crate::memory::{self, MemorySource};
+use crate::param_io::{FieldReader, Value};
+#[cfg(windows)]
+use crate::pointer_chain;
 use crate::prelude::*;
-use crate::{pointer_chain, version};
+use crate::regulation::{self, BinderFile};
+use crate::version;
 // TODO: Rename to have a more descriptive name
 const SHARED_POINTER_OFFSET: isize = 0x40;
+const PARAM_BIG_ENDIAN_OFFSET: usize = 0x2c;
+const PARAM_FORMAT_OFFSET: usize = 0x2d;
+/// Set in the PARAM format byte when rows have 64-bit offsets, as in memory.
+const PARAM_LONG_DATA_OFFSET: u8 = 0x04;
 
 pub static PARAMS: Lazy<RwLock<Params>> = Lazy::new(|| unsafe {
     let mut params = Params::new();
@@ -29,6 +44,50 @@ pub static PARAMS: Lazy<RwLock<Params>> = Lazy::new(|| unsafe {
     RwLock::new(params)
 });
 
+/// Locks [`PARAMS`] for reading and looks up a row by ID. The lock is held
+/// as long as the guard is.
+pub fn read_row<T: ParamStruct>(id: u64) -> Option<MappedRwLockReadGuard<'static, T>> {
+    RwLockReadGuard::try_map(PARAMS.read(), |params| params.row::<T>(id)).ok()
+}
+
+/// Locks [`PARAMS`] for writing and looks up a row by ID. The lock is held
+/// as long as the guard is, and when the params are a copy of another
+/// process' the row is written back to it when the guard is dropped.
+pub fn write_row<T: ParamStruct>(id: u64) -> Option<RowWriteGuard<T>> {
+    let mut params = PARAMS.write();
+    let row = params.row_mut::<T>(id)? as *mut T;
+    Some(RowWriteGuard { params, row })
+}
+
+/// A row of [`PARAMS`] locked for writing, from [`write_row`].
+pub struct RowWriteGuard<T: ParamStruct> {
+    params: RwLockWriteGuard<'static, Params>,
+    row: *mut T,
+}
+
+impl<T: ParamStruct> Deref for RowWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // The row lives in the params, which the guard keeps locked.
+        unsafe { &*self.row }
+    }
+}
+
+impl<T: ParamStruct> DerefMut for RowWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.row }
+    }
+}
+
+impl<T: ParamStruct> Drop for RowWriteGuard<T> {
+    fn drop(&mut self) {
+        if let Err(e) = self.params.flush_param(T::NAME) {
+            error!("Couldn't write {} back: {e}", T::NAME);
+        }
+    }
+}
+
 pub static PARAM_NAMES: Lazy<HashMap<String, HashMap<usize, String>>> =
     Lazy::new(|| serde_json::from_str(include_str!("codegen/param_names.json")).unwrap());
 
@@ -63,6 +122,7 @@ struct ParamEntry {
     param_length: u64,
 }
 
+#[cfg(windows)]
 impl ParamEntry {
     unsafe fn name(&self) -> Result<&U16CStr, String> {
         U16CStr::from_slice_truncate(if self.param_length <= 7 {
@@ -91,37 +151,258 @@ pub struct Param<T: 'static> {
     pub param: Option<&'static mut T>,
 }
 
-pub struct Params(BTreeMap<String, (*const c_void, isize)>);
+#[cfg(windows)]
+unsafe fn is_read_write(ptr: *const c_void) -> bool {
+    let mut memory_basic_info = MEMORY_BASIC_INFORMATION::default();
+    VirtualQuery(Some(ptr), &mut memory_basic_info, mem::size_of::<MEMORY_BASIC_INFORMATION>());
+    memory_basic_info.Protect.contains(PAGE_READWRITE)
+}
+
+fn read_at<T>(source: &Arc<dyn MemorySource>, addr: usize) -> Option<T> {
+    PointerChain::with_source(Arc::clone(source), &[addr]).read()
+}
+
+/// Size of the param file at `addr`: its header, row table and rows. The
+/// first header field is the offset of the row names rather than a size, so
+/// the row size is taken from the distance between the first two rows, or
+/// between the only row and the names.
+fn param_len(source: &Arc<dyn MemorySource>, addr: usize) -> Option<usize> {
+    let count = read_at::<u16>(source, addr + 0x0a)? as usize;
+    let table_end = SHARED_POINTER_OFFSET as usize + count * mem::size_of::<ParamEntryOffset>();
+    if count == 0 {
+        return Some(table_end);
+    }
+
+    let row_offset = |i: usize| {
+        let entry = SHARED_POINTER_OFFSET as usize + i * mem::size_of::<ParamEntryOffset>();
+        read_at::<u64>(source, addr + entry + 8).map(|offset| offset as usize)
+    };
+    let first = row_offset(0)?;
+    let next = if count > 1 { row_offset(1)? } else { read_at::<u32>(source, addr)? as usize };
+    let row_size = next.checked_sub(first).filter(|&size| size > 0)?;
+    if first < table_end {
+        return None;
+    }
+
+    first.checked_add(row_size.checked_mul(count)?)
+}
+
+/// Local copy of a param file read from another process.
+struct ParamCopy {
+    name: String,
+    addr: usize,
+    len: usize,
+    // Stored as `u64`s so that the rows are as aligned as they are in the game.
+    buf: Vec<u64>,
+}
+
+impl ParamCopy {
+    /// Reads the name, row count and contents of the param file referenced by
+    /// the `ParamEntry` pointer at `entry_ptr_addr`.
+    fn read(
+        source: &Arc<dyn MemorySource>,
+        entry_ptr_addr: usize,
+    ) -> Result<(String, isize, ParamCopy), String> {
+        let entry_addr = source
+            .read_usize(entry_ptr_addr)
+            .ok_or_else(|| format!("Wrong ptr {entry_ptr_addr:#x}"))?;
+        let entry: ParamEntry =
+            read_at(source, entry_addr).ok_or_else(|| format!("Wrong ptr {entry_addr:#x}"))?;
+
+        let name: [u16; 90] = if entry.param_length <= 7 {
+            let mut name = [0u16; 90];
+            name[..8].copy_from_slice(unsafe { &entry.param_name.direct });
+            name
+        } else {
+            let indirect = unsafe { entry.param_name.indirect };
+            read_at(source, indirect as usize)
+                .ok_or_else(|| format!("Wrong string ptr: {:p}", indirect))?
+        };
+        let name = U16CStr::from_slice_truncate(&name)
+            .map_err(|_| "Missing NUL terminator".to_string())?
+            .to_string()
+            .map_err(|e| format!("{}", e))?;
+
+        let addr =
+            PointerChain::<c_void>::with_source(Arc::clone(source), &[entry_addr + 0x80, 0x80, 0])
+                .eval()
+                .ok_or_else(|| format!("Wrong param file ptr for {name}"))? as usize;
+
+        let count: u16 =
+            read_at(source, addr + 0x0a).ok_or_else(|| format!("Wrong row count for {name}"))?;
+        let len = param_len(source, addr)
+            .ok_or_else(|| format!("Couldn't tell the size of the rows of {name}"))?;
+        if len >= 0x1000_0000 {
+            return Err(format!("Invalid param size {len:#x} for {name}"));
+        }
+
+        let mut copy =
+            ParamCopy { name: name.clone(), addr, len, buf: vec![0u64; len.div_ceil(8)] };
+        source
+            .read(addr, copy.bytes_mut())
+            .ok_or_else(|| format!("Couldn't read param {name} at {addr:#x}"))?;
+
+        Ok((name, count as isize, copy))
+    }
+
+    /// Copies a PARAM file read from disk. Only files with the same layout
+    /// as in memory are accepted.
+    fn from_file(name: &str, data: &[u8]) -> Result<(isize, ParamCopy), String> {
+        let len = data.len();
+        let table_start = SHARED_POINTER_OFFSET as usize;
+        if len < table_start {
+            return Err(format!("Truncated param {name}"));
+        }
+
+        if data[PARAM_BIG_ENDIAN_OFFSET] != 0
+            || data[PARAM_FORMAT_OFFSET] & PARAM_LONG_DATA_OFFSET == 0
+        {
+            return Err(format!("Unsupported layout for param {name}"));
+        }
+
+        let count = u16::from_le_bytes([data[0x0a], data[0x0b]]) as usize;
+        let table_end = table_start + count * mem::size_of::<ParamEntryOffset>();
+        let rows = data
+            .get(table_start..table_end)
+            .ok_or_else(|| format!("Truncated row table for param {name}"))?;
+
+        for row in rows.chunks_exact(mem::size_of::<ParamEntryOffset>()) {
+            let id = u64::from_le_bytes(row[0..8].try_into().unwrap());
+            let offset = u64::from_le_bytes(row[8..16].try_into().unwrap()) as usize;
+            if !(table_end..len).contains(&offset) {
+                return Err(format!("Row {id} of param {name} is out of bounds"));
+            }
+        }
+
+        Ok((count as isize, ParamCopy::from_bytes(name, 0, data)))
+    }
+
+    fn from_bytes(name: &str, addr: usize, data: &[u8]) -> ParamCopy {
+        let len = data.len();
+        let mut copy =
+            ParamCopy { name: name.to_string(), addr, len, buf: vec![0u64; len.div_ceil(8)] };
+        copy.bytes_mut().copy_from_slice(data);
+        copy
+    }
+
+    /// Address of the `i`-th row, if it lies within the copy.
+    fn row_ptr(&self, i: usize) -> Option<*const c_void> {
+        let entry = SHARED_POINTER_OFFSET as usize + i * mem::size_of::<ParamEntryOffset>();
+        let offset = self.bytes().get(entry + 8..entry + 16)?;
+        let offset = u64::from_le_bytes(offset.try_into().unwrap()) as usize;
+        (offset < self.len).then(|| unsafe { (self.buf.as_ptr() as *const u8).add(offset) } as _)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
+
+/// Where the params were read from.
+enum Origin {
+    /// The game's own params, accessed in-process.
+    Game,
+    /// Copies of the params of another process, written back by
+    /// [`Params::flush`].
+    Process(Arc<dyn MemorySource>),
+    /// Copies of the params in a regulation file. Changes are never written
+    /// back.
+    File,
+}
+
+pub struct Params {
+    entries: BTreeMap<String, (*const c_void, isize)>,
+    origin: Origin,
+    copies: Vec<ParamCopy>,
+    /// Length of every param file in the game's memory, found when the
+    /// params are first read. Only used with [`Origin::Game`].
+    lens: BTreeMap<String, usize>,
+    /// Contents of every param file when the params were first read, to
+    /// tell which rows were edited since.
+    snapshot: BTreeMap<String, ParamCopy>,
+}
 unsafe impl Send for Params {}
 unsafe impl Sync for Params {}
 
 impl Params {
     fn new() -> Self {
-        Params(BTreeMap::new())
+        Params {
+            entries: BTreeMap::new(),
+            origin: Origin::Game,
+            copies: Vec::new(),
+            lens: BTreeMap::new(),
+            snapshot: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the params from a `regulation.bin`, or from the one in the
+    /// given game folder. Works without the game running, on any OS.
+    pub fn load_regulation(path: &Path) -> Result<Params, String> {
+        Params::from_param_files(&regulation::load(path)?)
+    }
+
+    /// Reads the params from the PARAM files of an unpacked regulation.
+    /// Other files, and PARAM files that can't be read, are skipped.
+    pub fn from_param_files(files: &[BinderFile]) -> Result<Params, String> {
+        let mut entries = BTreeMap::new();
+        let mut copies = Vec::new();
+
+        for file in files.iter().filter(|file| file.is_param()) {
+            match ParamCopy::from_file(file.stem(), &file.data) {
+                Ok((count, copy)) => {
+                    entries.insert(copy.name.clone(), (copy.buf.as_ptr() as *const c_void, count));
+                    copies.push(copy);
+                },
+                Err(e) => error!("{}", e),
+            }
+        }
+
+        if entries.is_empty() {
+            return Err("No params found in regulation".to_string());
+        }
+
+        let mut params = Params {
+            entries,
+            origin: Origin::File,
+            copies,
+            lens: BTreeMap::new(),
+            snapshot: BTreeMap::new(),
+        };
+        params.take_snapshot();
+        Ok(params)
     }
 
     /// # Safety
     ///
     /// Accesses raw pointers. Should never crash as the param pointers are
     /// static.
+    #[cfg(not(windows))]
     pub unsafe fn refresh(&mut self) -> Result<(), String> {
-        let addresses: BaseAddresses = version::get_version().into();
-        let mut memory_basic_info = MEMORY_BASIC_INFORMATION::default();
+        self.refresh_from(memory::default_source())
+    }
 
-        let module_base_addr = GetModuleHandleA(None).unwrap().0 as usize;
+    /// # Safety
+    ///
+    /// Accesses raw pointers. Should never crash as the param pointers are
+    /// static.
+    #[cfg(windows)]
+    pub unsafe fn refresh(&mut self) -> Result<(), String> {
+        let addresses = version::base_addresses();
+
+        let module_base_addr = memory::default_source()
+            .module_base()
+            .ok_or_else(|| "Couldn't find the game module".to_string())?;
 
         let base_ptr: PointerChain<ParamMaster> =
             pointer_chain!(addresses.cs_regulation_manager + module_base_addr, 0x18);
 
         let base_ptr: *const ParamMaster = loop {
             if let Some(base_ptr) = base_ptr.eval() {
-                VirtualQuery(
-                    Some(base_ptr as _),
-                    &mut memory_basic_info,
-                    mem::size_of::<MEMORY_BASIC_INFORMATION>(),
-                );
-
-                if memory_basic_info.Protect.contains(PAGE_READWRITE) {
+                if is_read_write(base_ptr as _) {
                     break base_ptr;
                 }
             }
@@ -132,10 +413,98 @@ impl Params {
             base_ptr.as_ref().ok_or_else(|| "Invalid param base address".to_string())?;
 
         let m = Params::param_entries_from_master(base)?;
-        self.0 = m;
+        let source = memory::default_source();
+        self.lens = m
+            .iter()
+            .filter_map(|(name, &(param_ptr, _))| {
+                let len = param_len(&source, param_ptr as usize)?;
+                (len < 0x1000_0000).then(|| (name.clone(), len))
+            })
+            .collect();
+        self.entries = m;
+        self.origin = Origin::Game;
+        self.copies.clear();
+        self.take_snapshot();
         Ok(())
     }
 
+    /// Reads the params through `source`, keeping a local copy of every
+    /// param file. Changes to the copies are written back by
+    /// [`Params::flush`].
+    ///
+    /// # Safety
+    ///
+    /// The rows are accessed through raw pointers into the copies, so the
+    /// param files in `source` must be well-formed.
+    pub unsafe fn refresh_from(&mut self, source: Arc<dyn MemorySource>) -> Result<(), String> {
+        let addresses = version::base_addresses();
+
+        let module_base_addr =
+            source.module_base().ok_or_else(|| "Couldn't find the game module".to_string())?;
+
+        let base: ParamMaster = PointerChain::with_source(Arc::clone(&source), &[
+            addresses.cs_regulation_manager + module_base_addr,
+            0x18,
+        ])
+        .read()
+        .ok_or_else(|| "Invalid param base address".to_string())?;
+
+        let count = (base.end as usize).wrapping_sub(base.start as usize) / mem::size_of::<usize>();
+
+        if !(100..0x10000).contains(&count) {
+            return Err("Invalid entries count".to_string());
+        }
+
+        let mut entries = BTreeMap::new();
+        let mut copies = Vec::with_capacity(count);
+
+        for i in 0..count {
+            match ParamCopy::read(&source, base.start as usize + i * mem::size_of::<usize>()) {
+                Ok((name, count, copy)) => {
+                    entries.insert(name, (copy.buf.as_ptr() as *const c_void, count));
+                    copies.push(copy);
+                },
+                Err(e) => error!("{}", e),
+            }
+        }
+
+        self.entries = entries;
+        self.origin = Origin::Process(source);
+        self.copies = copies;
+        self.lens.clear();
+        self.take_snapshot();
+        Ok(())
+    }
+
+    /// Writes the local param copies back to the process they were read
+    /// from. Does nothing if the params are accessed in-process or were read
+    /// from a file.
+    pub fn flush(&self) -> Result<(), String> {
+        self.copies.iter().try_for_each(|copy| self.flush_copy(copy))
+    }
+
+    /// Writes the local copy of one param back to the process it was read
+    /// from, like [`Params::flush`] does for all of them.
+    pub fn flush_param(&self, s: &str) -> Result<(), String> {
+        match self.copies.iter().find(|copy| copy.name == s) {
+            Some(copy) => self.flush_copy(copy),
+            None => Ok(()),
+        }
+    }
+
+    fn flush_copy(&self, copy: &ParamCopy) -> Result<(), String> {
+        let Origin::Process(source) = &self.origin else {
+            return Ok(());
+        };
+
+        JOURNAL.lock().record(source, copy.addr, copy.len, Some(&copy.name));
+        source
+            .write(copy.addr, copy.bytes())
+            .ok_or_else(|| format!("Couldn't write param at {:#x}", copy.addr))?;
+        Ok(())
+    }
+
+    #[cfg(windows)]
     unsafe fn param_entries_from_master(
         base: &ParamMaster,
     ) -> Result<BTreeMap<String, (*const c_void, isize)>, String> {
@@ -173,14 +542,157 @@ impl Params {
         Ok(m)
     }
 
+    /// Current contents of a param file.
+    fn param_bytes(&self, s: &str) -> Option<&[u8]> {
+        if let Some(copy) = self.copies.iter().find(|copy| copy.name == s) {
+            return Some(copy.bytes());
+        }
+
+        let (param_ptr, _) = self.get_param_ptr(s)?;
+        let len = *self.lens.get(s)?;
+        Some(unsafe { std::slice::from_raw_parts(param_ptr as *const u8, len) })
+    }
+
+    /// Copies every param file, unless it was done already: the snapshot is
+    /// taken the first time the params are read, before any edit.
+    fn take_snapshot(&mut self) {
+        if !self.snapshot.is_empty() {
+            return;
+        }
+
+        self.snapshot = self
+            .entries
+            .keys()
+            .filter_map(|name| {
+                let bytes = self.param_bytes(name)?;
+                Some((name.clone(), ParamCopy::from_bytes(name, 0, bytes)))
+            })
+            .collect();
+    }
+
+    /// Whether a param file differs from its snapshot. Params without a
+    /// snapshot are never modified.
+    pub fn is_param_modified(&self, s: &str) -> bool {
+        match (self.snapshot.get(s), self.param_bytes(s)) {
+            (Some(snapshot), Some(current)) => snapshot.bytes() != current,
+            _ => false,
+        }
+    }
+
+    /// The fields of a row as they were in the snapshot.
+    pub fn original_fields(&self, param: &str, param_idx: usize) -> Option<Vec<(String, Value)>> {
+        let lambda = PARAM_VTABLE.get(param)?;
+        let ptr = self.snapshot.get(param)?.row_ptr(param_idx)?;
+
+        // The reader never writes, so the snapshot stays as it was.
+        let mut reader = FieldReader::default();
+        lambda(ptr, &mut reader);
+        Some(reader.0)
+    }
+
     pub fn keys(&self) -> impl Iterator<Item = &String> {
-        self.0.keys()
+        self.entries.keys()
     }
 
     fn get_param_ptr(&self, s: &str) -> Option<(*const c_void, isize)> {
-        self.0.get(s).cloned()
+        self.entries.get(s).cloned()
+    }
+
+    /// Offset of a row of `size` bytes, if it lies within the param file
+    /// and after its row table.
+    fn checked_row_offset(
+        param_len: usize,
+        count: isize,
+        entry: &ParamEntryOffset,
+        size: usize,
+    ) -> Option<usize> {
+        let table_end =
+            SHARED_POINTER_OFFSET as usize + count as usize * mem::size_of::<ParamEntryOffset>();
+        let offset = usize::try_from(entry.param_offset).ok()?;
+        (offset >= table_end && offset.checked_add(size)? <= param_len).then_some(offset)
+    }
+
+    /// Size of the rows of a param, the same way [`param_len`] finds it: the
+    /// distance between the first two rows, or between the only row and the
+    /// row names.
+    fn row_size(param_ptr: *const c_void, entries: &[ParamEntryOffset]) -> Option<usize> {
+        let first = entries.first()?.param_offset;
+        let next = match entries.get(1) {
+            Some(entry) => entry.param_offset,
+            None => unsafe { (param_ptr as *const u32).read_unaligned() as isize },
+        };
+        usize::try_from(next.checked_sub(first)?).ok().filter(|&size| size > 0)
     }
 
+    /// Row table of a param, if it lies within the param file.
+    fn row_table(&self, s: &str) -> Option<(*const c_void, usize, &[ParamEntryOffset])> {
+        let (param_ptr, count) = self.get_param_ptr(s)?;
+        let param_len = self.param_bytes(s)?.len();
+
+        let table_end =
+            SHARED_POINTER_OFFSET as usize + count as usize * mem::size_of::<ParamEntryOffset>();
+        if table_end > param_len {
+            return None;
+        }
+
+        let vec_ptr = unsafe { param_ptr.offset(SHARED_POINTER_OFFSET) } as *const ParamEntryOffset;
+        if !vec_ptr.is_aligned() {
+            return None;
+        }
+        Some((param_ptr, param_len, unsafe { std::slice::from_raw_parts(vec_ptr, count as usize) }))
+    }
+
+    /// Looks up a row by ID. The rows are sorted by ID, so this is a binary
+    /// search.
+    fn row_ptr<T: ParamStruct>(&self, id: u64) -> Option<*mut T> {
+        let (param_ptr, param_len, entries) = self.row_table(T::NAME)?;
+        let idx = entries.binary_search_by_key(&id, |entry| entry.param_id).ok()?;
+        let offset = Self::checked_row_offset(
+            param_len,
+            entries.len() as isize,
+            &entries[idx],
+            mem::size_of::<T>(),
+        )?;
+
+        let ptr = unsafe { param_ptr.add(offset) } as *mut T;
+        ptr.is_aligned().then_some(ptr)
+    }
+
+    /// Index of the row with the given ID, for [`Params::visit_param_item`].
+    /// The rows are sorted by ID, so this is a binary search.
+    pub fn row_index(&self, s: &str, id: u64) -> Option<usize> {
+        let (_, _, entries) = self.row_table(s)?;
+        entries.binary_search_by_key(&id, |entry| entry.param_id).ok()
+    }
+
+    /// The row of `T`'s param with the given ID.
+    pub fn row<T: ParamStruct>(&self, id: u64) -> Option<&T> {
+        self.row_ptr::<T>(id).and_then(|ptr| unsafe { ptr.as_ref() })
+    }
+
+    /// The row of `T`'s param with the given ID, for writing. Like every
+    /// other param write, it is recorded in the journal first.
+    pub fn row_mut<T: ParamStruct>(&mut self, id: u64) -> Option<&mut T> {
+        let ptr = self.row_ptr::<T>(id)?;
+        self.record_param(T::NAME);
+        unsafe { ptr.as_mut() }
+    }
+
+    /// Records the whole param file in the journal before handing out
+    /// mutable access to its rows. Copies of params read from another
+    /// process are recorded when they are flushed instead, and copies read
+    /// from a file aren't game memory.
+    fn record_param(&self, s: &str) {
+        if !matches!(self.origin, Origin::Game) {
+            return;
+        }
+
+        if let (Some((param_ptr, _)), Some(&len)) = (self.get_param_ptr(s), self.lens.get(s)) {
+            JOURNAL.lock().record(&memory::default_source(), param_ptr as usize, len, Some(s));
+        }
+    }
+
+    /// Visits a row with a visitor which only reads from it.
     pub fn visit_param_item<T: ParamVisitor>(
         &self,
         param: &str,
@@ -194,16 +706,31 @@ impl Params {
         };
     }
 
+    /// Visits a row with a visitor which may write to it. Like every other
+    /// param write, the param is recorded in the journal first.
+    pub fn visit_param_item_mut<T: ParamVisitor>(
+        &self,
+        param: &str,
+        param_idx: usize,
+        visitor: &mut T,
+    ) {
+        if let Some((lambda, ptr)) = PARAM_VTABLE.get(param).and_then(|lambda| {
+            unsafe { self.get_param_idx_ptr(param, param_idx) }.map(|v| (lambda, v))
+        }) {
+            self.record_param(param);
+            lambda(ptr, visitor);
+        };
+    }
+
     /// # Safety
     ///
     /// Accesses raw pointers. Ensure that the param is properly initialized
     /// (e.g. with the params well-formed and loaded into memory) before
     /// invoking.
     pub unsafe fn iter_param_ids(&self, s: &str) -> Option<impl Iterator<Item = u64>> {
-        let (param_ptr, count) = self.get_param_ptr(s)?;
-
-        let vec_ptr = param_ptr.offset(SHARED_POINTER_OFFSET) as *const ParamEntryOffset;
-        let param_entries = std::slice::from_raw_parts(vec_ptr, count as usize);
+        let (_, _, param_entries) = self.row_table(s)?;
+        // Not tied to the borrow of `self`, like the rows of `iter_param`.
+        let param_entries = std::slice::from_raw_parts(param_entries.as_ptr(), param_entries.len());
 
         Some(param_entries.iter().map(|ent| ent.param_id))
     }
@@ -216,15 +743,19 @@ impl Params {
     ///
     /// This is somewhat expensive as it calculates each param's offset at every
     /// iteration. If you only need the param IDs, use `iter_param_ids`.
+    ///
+    /// Rows that don't lie within the param file have no `param`.
     pub unsafe fn iter_param<T: 'static>(&self, s: &str) -> Option<impl Iterator<Item = Param<T>>> {
-        let (param_ptr, count) = self.get_param_ptr(s)?;
-
-        let vec_ptr = param_ptr.offset(SHARED_POINTER_OFFSET) as *const ParamEntryOffset;
-        let param_entries = std::slice::from_raw_parts(vec_ptr, count as usize);
+        let (param_ptr, param_len, param_entries) = self.row_table(s)?;
+        let param_entries = std::slice::from_raw_parts(param_entries.as_ptr(), param_entries.len());
+        let count = param_entries.len() as isize;
 
-        Some(param_entries.iter().map(move |ent| Param {
-            id: ent.param_id,
-            param: (param_ptr.offset(ent.param_offset) as *mut T).as_mut(),
+        Some(param_entries.iter().map(move |ent| {
+            Param {
+                id: ent.param_id,
+                param: Self::checked_row_offset(param_len, count, ent, mem::size_of::<T>())
+                    .and_then(|offset| (param_ptr.add(offset) as *mut T).as_mut()),
+            }
         }))
     }
 
@@ -234,16 +765,15 @@ impl Params {
     /// (e.g. with the params well-formed and loaded into memory) before
     /// invoking.
     unsafe fn get_param_idx_ptr(&self, s: &str, i: usize) -> Option<*const c_void> {
-        let (param_ptr, count) = self.get_param_ptr(s)?;
+        let (param_ptr, param_len, param_entries) = self.row_table(s)?;
+        let offset = Self::checked_row_offset(
+            param_len,
+            param_entries.len() as isize,
+            param_entries.get(i)?,
+            Self::row_size(param_ptr, param_entries)?,
+        )?;
 
-        if i >= (count as usize) {
-            return None;
-        }
-
-        let vec_ptr = param_ptr.offset(SHARED_POINTER_OFFSET) as *const ParamEntryOffset;
-        let param_entries = std::slice::from_raw_parts(vec_ptr, count as usize);
-
-        Some(param_ptr.offset(param_entries[i].param_offset))
+        Some(param_ptr.add(offset))
     }
 
     /// # Safety
@@ -253,18 +783,273 @@ impl Params {
     /// invoking.
     #[allow(unused)]
     unsafe fn get_param_idx<T: 'static>(&self, s: &str, i: usize) -> Option<Param<T>> {
-        let (param_ptr, count) = self.get_param_ptr(s)?;
+        let (param_ptr, param_len, param_entries) = self.row_table(s)?;
+        let entry = param_entries.get(i)?;
 
-        if i >= (count as usize) {
-            return None;
+        Some(Param {
+            id: entry.param_id,
+            param: Self::checked_row_offset(
+                param_len,
+                param_entries.len() as isize,
+                entry,
+                mem::size_of::<T>(),
+            )
+            .and_then(|offset| (param_ptr.add(offset) as *mut T).as_mut()),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::memory::FakeMemory;
+
+    pub(crate) const MODULE_BASE: usize = 0x1_4000_0000;
+
+    /// A param row: its id and its raw bytes.
+    pub(crate) type Row<'a> = (u64, &'a [u8]);
+
+    /// Lays out a param table in `mem` like the game does, with one param
+    /// file per `(name, rows)` pair and filler params to pass the sanity
+    /// checks. Each row is made of raw bytes.
+    pub(crate) fn map_params(mem: &FakeMemory, params: &[(&str, &[Row])]) {
+        let addresses = version::base_addresses();
+        let regulation_manager = 0x10_0000usize;
+        let table = 0x20_0000usize;
+        let count = params.len().max(100);
+
+        mem.map_pointer(MODULE_BASE + addresses.cs_regulation_manager, regulation_manager);
+        let mut master = vec![0u8; 0x18 + mem::size_of::<ParamMaster>()];
+        master[0x18..0x20].copy_from_slice(&table.to_le_bytes());
+        master[0x20..0x28].copy_from_slice(&(table + count * 8).to_le_bytes());
+        mem.map(regulation_manager, master);
+
+        for i in 0..count {
+            let (name, rows) = params.get(i).copied().unwrap_or(("Filler", &[]));
+            let entry = 0x30_0000 + i * 0x100;
+            let name_ptr = 0x38_0000 + i * 0x100;
+            let indirect = 0x40_0000 + i * 0x100;
+            let file = 0x100_0000 + i * 0x1_0000;
+
+            mem.map_pointer(table + i * 8, entry);
+
+            let name_utf16 = name.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+            let mut entry_bytes = vec![0u8; 0x88];
+            if name.len() <= 7 {
+                for (j, c) in name_utf16.iter().enumerate() {
+                    entry_bytes[0x18 + j * 2..0x1a + j * 2].copy_from_slice(&c.to_le_bytes());
+                }
+            } else {
+                entry_bytes[0x18..0x20].copy_from_slice(&name_ptr.to_le_bytes());
+                let mut name_bytes = vec![0u8; 180];
+                for (j, c) in name_utf16.iter().enumerate() {
+                    name_bytes[j * 2..j * 2 + 2].copy_from_slice(&c.to_le_bytes());
+                }
+                mem.map(name_ptr, name_bytes);
+            }
+            entry_bytes[0x28..0x30].copy_from_slice(&(name.len() as u64).to_le_bytes());
+            entry_bytes[0x80..0x88].copy_from_slice(&indirect.to_le_bytes());
+            mem.map(entry, entry_bytes);
+
+            let mut indirect_bytes = vec![0u8; 0x88];
+            indirect_bytes[0x80..0x88].copy_from_slice(&file.to_le_bytes());
+            mem.map(indirect, indirect_bytes);
+
+            mem.map(file, param_file(rows));
+        }
+    }
+
+    /// A param file with the given rows, laid out like in memory and in
+    /// `regulation.bin`.
+    pub(crate) fn param_file(rows: &[Row]) -> Vec<u8> {
+        let row_table_len = rows.len() * mem::size_of::<ParamEntryOffset>();
+        let mut file_bytes = vec![0u8; SHARED_POINTER_OFFSET as usize + row_table_len];
+        file_bytes[0x0a..0x0c].copy_from_slice(&(rows.len() as u16).to_le_bytes());
+        file_bytes[PARAM_FORMAT_OFFSET] = PARAM_LONG_DATA_OFFSET;
+        for (j, (id, data)) in rows.iter().enumerate() {
+            let row = SHARED_POINTER_OFFSET as usize + j * mem::size_of::<ParamEntryOffset>();
+            let offset = file_bytes.len();
+            file_bytes[row..row + 8].copy_from_slice(&id.to_le_bytes());
+            file_bytes[row + 8..row + 16].copy_from_slice(&offset.to_le_bytes());
+            file_bytes.extend_from_slice(data);
+            file_bytes.resize(file_bytes.len().next_multiple_of(8), 0);
         }
+        // The row names follow the rows, and the header points to them.
+        let names_offset = file_bytes.len() as u32;
+        file_bytes[0..4].copy_from_slice(&names_offset.to_le_bytes());
+        file_bytes.extend_from_slice(&[0u8; 0x20]);
+        file_bytes
+    }
 
-        let vec_ptr = param_ptr.offset(SHARED_POINTER_OFFSET) as *const ParamEntryOffset;
-        let param_entries = std::slice::from_raw_parts(vec_ptr, count as usize);
+    #[test]
+    fn test_refresh_from_source() {
+        let mem = Arc::new(FakeMemory::with_module_base(MODULE_BASE));
+        map_params(&mem, &[
+            ("EquipParamWeapon", &[(10, &1u32.to_le_bytes()), (20, &2u32.to_le_bytes())]),
+            ("Short", &[(5, &3u32.to_le_bytes())]),
+        ]);
 
-        Some(Param {
-            id: param_entries[i].param_id,
-            param: (param_ptr.offset(param_entries[i].param_offset) as *mut T).as_mut(),
-        })
+        let mut params = Params::new();
+        unsafe { params.refresh_from(mem.clone()) }.unwrap();
+
+        assert!(params.keys().any(|k| k == "Short"));
+        // The header, one row table entry and one row, without the names.
+        let short = params.copies.iter().find(|copy| copy.name == "Short").unwrap();
+        assert_eq!(short.len, 0x40 + 0x18 + 8);
+        let ids = unsafe { params.iter_param_ids("EquipParamWeapon") }.unwrap().collect::<Vec<_>>();
+        assert_eq!(ids, vec![10, 20]);
+
+        let values = unsafe { params.iter_param::<u32>("EquipParamWeapon") }
+            .unwrap()
+            .map(|p| *p.param.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 2]);
+
+        for p in unsafe { params.iter_param::<u32>("Short") }.unwrap() {
+            *p.param.unwrap() = 42;
+        }
+        let short_row = 0x100_0000 + 0x1_0000 + 0x58;
+        assert_eq!(mem.bytes(short_row, 4), Some(3u32.to_le_bytes().to_vec()));
+
+        params.flush().unwrap();
+        assert_eq!(mem.bytes(short_row, 4), Some(42u32.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_flush_param() {
+        let mem = Arc::new(FakeMemory::with_module_base(MODULE_BASE));
+        map_params(&mem, &[
+            ("EquipParamWeapon", &[(10, &1u32.to_le_bytes()), (20, &2u32.to_le_bytes())]),
+            ("Short", &[(5, &3u32.to_le_bytes())]),
+        ]);
+
+        let mut params = Params::new();
+        unsafe { params.refresh_from(mem.clone()) }.unwrap();
+
+        for name in ["EquipParamWeapon", "Short"] {
+            for p in unsafe { params.iter_param::<u32>(name) }.unwrap() {
+                *p.param.unwrap() = 42;
+            }
+        }
+        params.flush_param("Short").unwrap();
+
+        let short_row = 0x100_0000 + 0x1_0000 + 0x58;
+        assert_eq!(mem.bytes(short_row, 4), Some(42u32.to_le_bytes().to_vec()));
+        let weapon_row = 0x100_0000 + 0x70;
+        assert_eq!(mem.bytes(weapon_row, 4), Some(1u32.to_le_bytes().to_vec()));
+
+        // Both rows span up to the row names, which follow the last row.
+        let weapon = params.row_table("EquipParamWeapon").unwrap();
+        assert_eq!(Params::row_size(weapon.0, weapon.2), Some(8));
+        let short = params.row_table("Short").unwrap();
+        assert_eq!(Params::row_size(short.0, short.2), Some(8));
+    }
+
+    /// Collects the `f32` fields of a visited row.
+    struct F32Fields(Vec<(String, f32)>);
+
+    impl ParamVisitor for F32Fields {
+        fn visit_u8(&mut self, _: &str, _: &mut u8) {}
+
+        fn visit_u16(&mut self, _: &str, _: &mut u16) {}
+
+        fn visit_u32(&mut self, _: &str, _: &mut u32) {}
+
+        fn visit_i8(&mut self, _: &str, _: &mut i8) {}
+
+        fn visit_i16(&mut self, _: &str, _: &mut i16) {}
+
+        fn visit_i32(&mut self, _: &str, _: &mut i32) {}
+
+        fn visit_f32(&mut self, name: &str, v: &mut f32) {
+            self.0.push((name.to_string(), *v));
+        }
+
+        fn visit_bool(&mut self, _: &str, _: &mut bool) {}
+    }
+
+    #[test]
+    fn test_load_regulation() {
+        let mut goods: EquipParamGoods = unsafe { mem::zeroed() };
+        goods.weight = 1.5;
+        let goods = unsafe {
+            std::slice::from_raw_parts(
+                &goods as *const EquipParamGoods as *const u8,
+                mem::size_of::<EquipParamGoods>(),
+            )
+        };
+
+        let goods_param = param_file(&[(100, goods)]);
+        let weapons_param = param_file(&[(10, &1u32.to_le_bytes()), (20, &2u32.to_le_bytes())]);
+        let mut broken_param = param_file(&[(1, &[0; 4])]);
+        broken_param[0x48] = 0xff;
+
+        let dir = std::env::temp_dir().join(format!("er-regulation-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(regulation::REGULATION_FILE_NAME),
+            regulation::tests::regulation(&[
+                (0, r"N:\GR\data\Param\param\GameParam\EquipParamGoods.param", &goods_param),
+                (1, r"N:\GR\data\Param\param\GameParam\EquipParamWeapon.param", &weapons_param),
+                (2, r"N:\GR\data\Param\param\GameParam\Broken.param", &broken_param),
+                (3, r"N:\GR\data\Param\param\GameParam\readme.txt", b"not a param"),
+            ]),
+        )
+        .unwrap();
+        let params = Params::load_regulation(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let params = params.unwrap();
+
+        assert_eq!(params.keys().collect::<Vec<_>>(), ["EquipParamGoods", "EquipParamWeapon"]);
+        let ids = unsafe { params.iter_param_ids("EquipParamWeapon") }.unwrap().collect::<Vec<_>>();
+        assert_eq!(ids, vec![10, 20]);
+
+        let mut fields = F32Fields(Vec::new());
+        params.visit_param_item("EquipParamGoods", 0, &mut fields);
+        assert!(fields.0.contains(&("weight".to_string(), 1.5)));
+
+        // Edits stay in the copies.
+        for p in unsafe { params.iter_param::<u32>("EquipParamWeapon") }.unwrap() {
+            *p.param.unwrap() = 42;
+        }
+        params.flush().unwrap();
+        let values = unsafe { params.iter_param::<u32>("EquipParamWeapon") }
+            .unwrap()
+            .map(|p| *p.param.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![42, 42]);
+
+        assert!(Params::from_param_files(&[]).is_err());
+    }
+
+    #[test]
+    fn test_row_lookup() {
+        let mut params = crate::param_io::tests::params();
+
+        assert_eq!(params.row::<EquipParamGoods>(200).map(|row| row.weight), Some(2.5));
+        assert!(params.row::<EquipParamGoods>(150).is_none());
+        assert!(params.row::<EquipParamWeapon>(200).is_none());
+        assert_eq!(params.row_index("EquipParamGoods", 200), Some(1));
+        assert_eq!(params.row_index("EquipParamGoods", 150), None);
+
+        params.row_mut::<EquipParamGoods>(100).unwrap().sort_id = 9;
+        assert_eq!(crate::param_io::tests::goods(&params), vec![(100, 0.1, 9), (200, 2.5, 7)]);
+
+        // A row that starts within the file but doesn't fit in it.
+        let mut file = param_file(&[(1, &[0; 8])]);
+        let offset = (file.len() - 4) as u64;
+        file[0x48..0x50].copy_from_slice(&offset.to_le_bytes());
+        let params = Params::from_param_files(&[BinderFile {
+            id: 0,
+            name: "EquipParamGoods.param".to_string(),
+            data: file,
+        }])
+        .unwrap();
+
+        assert!(params.row::<EquipParamGoods>(1).is_none());
+        let rows = unsafe { params.iter_param::<EquipParamGoods>("EquipParamGoods") }.unwrap();
+        assert!(rows.map(|p| p.param).all(|row| row.is_none()));
+        let rows = unsafe { params.iter_param::<u32>("EquipParamGoods") }.unwrap();
+        assert!(rows.map(|p| p.param).all(|row| row.is_some()));
     }
 }
@@ -0,0 +1,47 @@
+//! Reading the version of the game module mapped in memory.
+//!
+//! The PE parsing itself lives in `address-scan`, shared with the codegen
+//! task that reads executables from disk.
+
+pub use address_scan::pe::*;
+
+use crate::memory::MemorySource;
+
+/// The `(major, minor, patch)` file version of the module mapped in `source`.
+pub fn module_version(source: &dyn MemorySource) -> Result<(u32, u32, u32), String> {
+    let base = source.module_base().ok_or_else(|| "Couldn't find the game module".to_string())?;
+
+    let mut headers = vec![0u8; 0x1000];
+    source.read(base, &mut headers).ok_or_else(|| "Couldn't read the PE headers".to_string())?;
+    let headers = PeHeaders::parse(&headers)?;
+
+    let (rva, size) = headers.resource_directory.ok_or_else(|| "No resources".to_string())?;
+    let mut resources = vec![0u8; size as usize];
+    source
+        .read(base + rva as usize, &mut resources)
+        .ok_or_else(|| "Couldn't read the resource directory".to_string())?;
+
+    version_from_resources(&resources, rva)
+}
+
+#[cfg(test)]
+mod tests {
+    use address_scan::pe::fixtures::{synthetic_image, version_resources, CODE, DATA};
+
+    use super::*;
+    use crate::memory::FakeMemory;
+
+    #[test]
+    fn test_module_version() {
+        let image = synthetic_image(&[
+            (".text", 0x1000, CODE, &[0xcc; 0x10]),
+            (".data", 0x2000, DATA, &[0; 0x10]),
+            (".rsrc", 0x3000, DATA, &version_resources(0x3000, (1, 2, 3))),
+        ]);
+
+        let mem = FakeMemory::with_module_base(0x1_4000_0000);
+        mem.map(0x1_4000_0000, image);
+
+        assert_eq!(module_version(&mem), Ok((1, 2, 3)));
+    }
+}
@@ -1,14 +1,15 @@
 #![allow(clippy::new_without_default)]
 
 use std::fmt::Display;
-
-use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use std::sync::Arc;
 
 use crate::codegen::base_addresses::BASE_ADDRESSES_2_06_0;
 use crate::memedit::*;
+use crate::memory::MemorySource;
+use crate::offsets::Offsets;
 use crate::prelude::base_addresses::BaseAddresses;
-use crate::prelude::Version;
-use crate::version;
+use crate::session::SessionState;
+use crate::{memory, version};
 
 #[derive(Debug)]
 pub struct Pointers {
@@ -39,6 +40,17 @@ pub struct Pointers {
     pub character_stats: PointerChain<CharacterStats>,
     pub character_points: PointerChain<CharacterPoints>,
     pub character_blessings: Option<PointerChain<CharacterBlessings>>,
+    pub chr_asm: PointerChain<ChrAsm>,
+    pub inventory: PointerChain<InventoryItems>,
+    pub matchmaking_weapon_level: PointerChain<u8>,
+
+    // WorldChrMan -> first net player -> ChrDataModule, as in CharacterPoints
+    pub hp: PointerChain<i32>,
+    pub max_hp: PointerChain<i32>,
+    pub fp: PointerChain<i32>,
+    pub max_fp: PointerChain<i32>,
+    pub stamina: PointerChain<i32>,
+    pub max_stamina: PointerChain<i32>,
 
     pub runes: PointerChain<u32>,
     pub igt: PointerChain<usize>,
@@ -68,6 +80,9 @@ pub struct Pointers {
     pub animation_speed: PointerChain<f32>,
     pub torrent_animation_speed: PointerChain<f32>,
 
+    // CSFlipper
+    pub game_speed: PointerChain<f32>,
+
     // CSLuaEventManager
     pub func_warp: usize,
     pub warp1: PointerChain<u64>,
@@ -103,6 +118,9 @@ pub struct Pointers {
     pub show_all_map_layers: Bitflag<u8>,
     pub show_all_graces: Bitflag<u8>,
 
+    // CSSessionManager
+    pub lobby_state: PointerChain<u32>,
+
     pub base_addresses: BaseAddresses,
 }
 
@@ -134,16 +152,16 @@ impl Position {
     }
 
     pub fn write(&self, [x, y, z, r1, r2]: [f32; 5]) {
-        self.x.write(x);
-        self.y.write(y);
-        self.z.write(z);
-        self.angle1.write(r1);
-        self.angle2.write(r2);
+        self.x.write_untracked(x);
+        self.y.write_untracked(y);
+        self.z.write_untracked(z);
+        self.angle1.write_untracked(r1);
+        self.angle2.write_untracked(r2);
     }
 
     pub fn write_map_id(&self, map_id: u32) {
         if let Some(m) = self.map_id.as_ref() {
-            m.write(map_id);
+            m.write_untracked(map_id);
         }
     }
 }
@@ -207,14 +225,105 @@ impl Display for CharacterBlessings {
     }
 }
 
+/// Equipped items. The 22 equipment slots are, in order: left and right
+/// weapons for each of the three weapon slots, arrows and bolts for each of
+/// three ammo slots (the game only uses two), the four armor pieces, an
+/// unused slot, the four talismans and another unused slot.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct ChrAsm {
+    /// 0 empty-handed, 1 one-handed, 2 left weapon two-handed, 3 right
+    /// weapon two-handed.
+    pub arm_style: u32,
+    pub left_weapon_slot: u32,
+    pub right_weapon_slot: u32,
+    pub ammo_slots: [u32; 4],
+    pub gaitem_handles: [u32; 22],
+    /// Param IDs of the equipped items. Negative if the slot is empty.
+    pub equip_param_ids: [i32; 22],
+}
+
+impl ChrAsm {
+    /// Param ID of the weapon in a slot, left or right hand, or `None` if
+    /// there's no such slot.
+    pub fn weapon_id(&self, left: bool, slot: usize) -> Option<i32> {
+        self.equip_param_ids[..6].get(slot * 2 + !left as usize).copied()
+    }
+
+    /// Whether the weapon in the active slot of a hand is held in both hands.
+    pub fn two_handed(&self, left: bool) -> bool {
+        self.arm_style == if left { 2 } else { 3 }
+    }
+}
+
+/// The player's inventory item list.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct InventoryItems {
+    pub capacity: u32,
+    pub items: usize,
+    pub count: u32,
+}
+
+impl InventoryItems {
+    /// Most entries read, in case the header is garbage.
+    const MAX_CAPACITY: u32 = 0x1000;
+
+    /// Reads the non-empty entries of the list. Removed items leave holes, so
+    /// every entry up to the capacity is read.
+    pub fn read_items(&self, source: &Arc<dyn MemorySource>) -> Option<Vec<InventoryItem>> {
+        let len = self.capacity.min(Self::MAX_CAPACITY) as usize;
+        let mut items = vec![InventoryItem::EMPTY; len];
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(
+                items.as_mut_ptr() as *mut u8,
+                items.len() * std::mem::size_of::<InventoryItem>(),
+            )
+        };
+        source.read(self.items, buf)?;
+        items.retain(|item| item.param_id().is_some());
+        Some(items)
+    }
+}
+
+/// An entry of the inventory item list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct InventoryItem {
+    pub gaitem_handle: u32,
+    /// Param ID, with the item category in the top four bits.
+    pub item_id: u32,
+    pub quantity: u32,
+    pub display_id: u32,
+    pub unk10: u32,
+}
+
+impl InventoryItem {
+    pub const EMPTY: InventoryItem = InventoryItem {
+        gaitem_handle: 0,
+        item_id: u32::MAX,
+        quantity: 0,
+        display_id: 0,
+        unk10: 0,
+    };
+
+    /// 0 for weapons and ammo, 1 for armor, 2 for talismans, 4 for goods and
+    /// 8 for ashes of war.
+    pub fn category(&self) -> u32 {
+        self.item_id >> 28
+    }
+
+    /// Param ID of the item, or `None` if the entry is empty.
+    pub fn param_id(&self) -> Option<u32> {
+        (self.gaitem_handle != 0 && self.item_id != u32::MAX).then_some(self.item_id & 0x0fff_ffff)
+    }
+}
+
 impl Pointers {
     pub fn new() -> Self {
-        let version = version::get_version();
-        use Version::*;
-
-        let base_module_address = unsafe { GetModuleHandleA(None).unwrap() }.0 as usize;
-        let base_addresses =
-            BaseAddresses::from(version).with_module_base_addr(base_module_address);
+        let base_module_address =
+            memory::default_source().module_base().expect("Couldn't find the game module");
+        let base_addresses = version::base_addresses().with_module_base_addr(base_module_address);
 
         let BaseAddresses {
             chr_dbg_flags,
@@ -234,108 +343,34 @@ impl Pointers {
             base_fps,
             base_anim,
             dbg_event_man_off,
+            cs_session_manager,
+            cs_flipper,
             ..
         } = base_addresses;
 
-        // Special cases
-
-        let map_id_offset = {
-            match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6c8,
-                V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6c0,
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x6d0,
-            }
-        };
-
-        let global_position_offset = {
-            match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6b8,
-                V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6b0,
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x6c0,
-            }
-        };
-
-        let group_mask = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 => group_mask,
-            V1_05_0 => group_mask - 8,
-            V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1
-            | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => group_mask,
-        };
-
-        let show_geom = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0
-            | V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                vec![
-                    bitflag!(0b1; group_mask + 2),
-                    bitflag!(0b1; group_mask + 3),
-                    bitflag!(0b1; group_mask + 4),
-                    bitflag!(0b1; group_mask + 5),
-                    bitflag!(0b1; group_mask + 6),
-                    bitflag!(0b1; group_mask + 7),
-                    bitflag!(0b1; group_mask + 8),
-                    bitflag!(0b1; group_mask),
-                    bitflag!(0b1; group_mask + 0xa),
-                    bitflag!(0b1; group_mask + 0xb),
-                    bitflag!(0b1; group_mask + 0xc),
-                    bitflag!(0b1; group_mask + 0xd),
-                    bitflag!(0b1; group_mask + 0xf),
-                    bitflag!(0b1; group_mask + 0x10),
-                    bitflag!(0b1; group_mask + 0x11),
-                    bitflag!(0b1; group_mask + 0x12),
-                ]
-            },
-            V1_05_0 => vec![
-                bitflag!(0b1; group_mask),
-                bitflag!(0b1; group_mask + 1),
-                bitflag!(0b1; group_mask + 2),
-                bitflag!(0b1; group_mask + 3),
-                bitflag!(0b1; group_mask + 5),
-                bitflag!(0b1; group_mask + 6),
-                bitflag!(0b1; group_mask + 8),
-                bitflag!(0b1; group_mask + 0xa),
-                bitflag!(0b1; group_mask + 0xb),
-                bitflag!(0b1; group_mask + 0xc),
-                bitflag!(0b1; group_mask + 0xd),
-                bitflag!(0b1; group_mask + 0xe),
-                bitflag!(0b1; group_mask + 0xf),
-            ],
-        };
-
-        let show_chr = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0
-            | V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                bitflag!(0b1; group_mask + 0xe)
-            },
-            V1_05_0 => bitflag!(0b1; group_mask + 4),
-        };
-
-        let net_players_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 | V1_06_0 => 0xB658,
-            V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0
-            | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x10EF8,
-        };
-
-        let player_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 | V1_06_0 => 0x18468,
-            V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0
-            | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x1E508,
-        };
+        let Offsets {
+            player_ins,
+            net_players_ins,
+            torrent_enemy_ins,
+            map_id: map_id_offset,
+            global_position: global_position_offset,
+            display_stable_pos,
+            menu_timer,
+            character_blessings,
+            matchmaking_weapon_level,
+            chr_asm,
+            equip_inventory,
+            game_speed,
+            group_mask_shift,
+            show_geom,
+            show_chr,
+            dbg_action_force_state_values,
+        } = version::offsets();
 
-        let torrent_enemy_ins = match version {
-            V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-            | V1_04_1 | V1_05_0 => 0x18390,
-            V1_06_0 => 0x18378,
-            V1_07_0 => 0x1E1A0,
-            V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 => 0x1e1b8,
-            V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x1cc90,
-        };
+        let group_mask = group_mask - group_mask_shift;
+        let show_geom =
+            show_geom.iter().map(|&offset| bitflag!(0b1; group_mask + offset)).collect();
+        let show_chr = bitflag!(0b1; group_mask + show_chr);
 
         // TODO 1.08.x
         // - show stable position is broken
@@ -374,14 +409,17 @@ impl Pointers {
                 0,
                 0x138
             ),
-            character_blessings: match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-                | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1
-                | V2_00_0 | V2_00_1 => None,
-                V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => {
-                    Some(pointer_chain!(game_data_man, 0x8, 0xfc))
-                },
-            },
+            character_blessings: character_blessings
+                .map(|offset| pointer_chain!(game_data_man, 0x8, offset)),
+            chr_asm: pointer_chain!(game_data_man, 0x8, chr_asm),
+            inventory: pointer_chain!(game_data_man, 0x8, equip_inventory),
+            matchmaking_weapon_level: pointer_chain!(game_data_man, 0x8, matchmaking_weapon_level),
+            hp: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x138),
+            max_hp: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x144),
+            fp: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x148),
+            max_fp: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x150),
+            stamina: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x154),
+            max_stamina: pointer_chain!(world_chr_man, net_players_ins, 0, 0x190, 0, 0x15c),
             runes: pointer_chain!(game_data_man, 0x8, 0x6C),
             igt: pointer_chain!(game_data_man, 0xA0),
 
@@ -395,21 +433,10 @@ impl Pointers {
 
             quitout: pointer_chain!(cs_menu_man_imp, 0x8, 0x5d),
             cursor_show: bitflag!(0b1; cs_menu_man_imp, 0xAC),
-            menu_timer: pointer_chain!(cs_menu_man_imp, match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 => 0x708 + 0x24,
-                V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0
-                | V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 => 0x718 + 0x24,
-                V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => 0x720 + 0x24,
-            }),
+            menu_timer: pointer_chain!(cs_menu_man_imp, menu_timer),
 
             gravity: bitflag!(0b1; world_chr_man, player_ins, 0x190, 0x68, 0x1d3),
-            display_stable_pos: bitflag!(0b1; world_chr_man, player_ins,
-                match version {
-                    V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 => 0x6FD,
-                    V1_04_0 | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => 0x6F5,
-                    V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1  | V2_00_0| V2_00_1 | V2_02_0 | V2_02_3 | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0  => 0x735
-                }
-            ),
+            display_stable_pos: bitflag!(0b1; world_chr_man, player_ins, display_stable_pos),
             global_position: Position {
                 x: pointer_chain!(world_chr_man, player_ins, global_position_offset),
                 y: pointer_chain!(world_chr_man, player_ins, global_position_offset + 0x4),
@@ -474,6 +501,7 @@ impl Pointers {
                 0x28,
                 0x17C8
             ),
+            game_speed: pointer_chain!(cs_flipper, game_speed),
 
             deathcam: (
                 bitflag!(0b100; world_chr_man, player_ins, 0x1c8),
@@ -503,18 +531,62 @@ impl Pointers {
             func_item_spawn,
             func_item_inject,
             func_dbg_action_force: pointer_chain!(base_addresses.func_dbg_action_force + 7),
-            func_dbg_action_force_state_values: match version {
-                V1_02_0 | V1_02_1 | V1_02_2 | V1_02_3 | V1_03_0 | V1_03_1 | V1_03_2 | V1_04_0
-                | V1_04_1 | V1_05_0 | V1_06_0 | V1_07_0 => (0xB1, 0xB2),
-                V1_08_0 | V1_08_1 | V1_09_0 | V1_09_1 | V2_00_0 | V2_00_1 | V2_02_0 | V2_02_3
-                | V2_03_0 | V2_04_0 | V2_05_0 | V2_06_0 => (0xC1, 0xC2),
-            },
+            func_dbg_action_force_state_values: dbg_action_force_state_values,
             current_target: pointer_chain!(current_target),
             show_all_map_layers: bitflag!(0b1; func_check_graces),
             show_all_graces: bitflag!(0b1; func_check_graces + 0x1),
+            lobby_state: pointer_chain!(cs_session_manager, 0xc),
             base_addresses,
         }
     }
+
+    /// Current multiplayer session state, or `None` if the session manager
+    /// can't be read, e.g. during loading screens.
+    pub fn session_state(&self) -> Option<SessionState> {
+        self.lobby_state.read().map(SessionState::from_lobby_state)
+    }
+
+    /// Debug flags that must not be active in a multiplayer session, with
+    /// their config name and vanilla value.
+    pub fn practice_flags(&self) -> Vec<(&'static str, &Bitflag<u8>, bool)> {
+        let mut flags = vec![
+            ("one_shot", &self.one_shot, false),
+            ("no_damage", &self.no_damage, false),
+            ("no_dead", &self.no_dead, false),
+            ("no_hit", &self.no_hit, false),
+            ("no_goods_consume", &self.no_goods_consume, false),
+            ("no_stamina_consume", &self.no_stamina_consume, false),
+            ("no_fp_consume", &self.no_fp_consume, false),
+            ("no_arrows_consume", &self.no_arrows_consume, false),
+            ("no_attack", &self.no_attack, false),
+            ("no_move", &self.no_move, false),
+            ("no_update_ai", &self.no_update_ai, false),
+            ("no_trigger_event", &self.no_trigger_event, false),
+            ("no_ashes_of_war_fp_consume", &self.no_ashes_of_war_fp_consume, false),
+            ("all_no_dead", &self.all_no_dead, false),
+            ("torrent_no_dead", &self.torrent_no_dead, false),
+            ("gravity", &self.gravity, false),
+            ("torrent_gravity", &self.torrent_gravity, false),
+            ("collision", &self.collision, false),
+            ("torrent_collision", &self.torrent_collision, false),
+            ("display_stable_pos", &self.display_stable_pos, false),
+            ("deathcam", &self.deathcam.0, false),
+            ("deathcam_torrent", &self.deathcam.1, false),
+            ("weapon_hitbox1", &self.weapon_hitbox1, false),
+            ("weapon_hitbox2", &self.weapon_hitbox2, false),
+            ("weapon_hitbox3", &self.weapon_hitbox3, false),
+            ("hitbox_high", &self.hitbox_high, false),
+            ("hitbox_low", &self.hitbox_low, false),
+            ("hitbox_f", &self.hitbox_f, false),
+            ("hitbox_character", &self.hitbox_character, false),
+            ("hitbox_event", &self.hitbox_event, false),
+            ("show_all_map_layers", &self.show_all_map_layers, false),
+            ("show_all_graces", &self.show_all_graces, false),
+            ("show_chr", &self.show_chr, true),
+        ];
+        flags.extend(self.show_geom.iter().map(|f| ("show_map", f, true)));
+        flags
+    }
 }
 
 impl Default for Pointers {
@@ -542,6 +614,15 @@ impl Default for Pointers {
             character_stats: pointer_chain!(0x0),
             character_points: pointer_chain!(0x0),
             character_blessings: None,
+            chr_asm: pointer_chain!(0x0),
+            inventory: pointer_chain!(0x0),
+            matchmaking_weapon_level: pointer_chain!(0x0),
+            hp: pointer_chain!(0x0),
+            max_hp: pointer_chain!(0x0),
+            fp: pointer_chain!(0x0),
+            max_fp: pointer_chain!(0x0),
+            stamina: pointer_chain!(0x0),
+            max_stamina: pointer_chain!(0x0),
             runes: pointer_chain!(0x0),
             igt: pointer_chain!(0x0),
             fps: pointer_chain!(0x0),
@@ -591,6 +672,7 @@ impl Default for Pointers {
             },
             animation_speed: pointer_chain!(0x0),
             torrent_animation_speed: pointer_chain!(0x0),
+            game_speed: pointer_chain!(0x0),
             deathcam: (bitflag!(0b1; 0x0), bitflag!(0b1; 0x0), pointer_chain!(0x0)),
             field_area_direction: bitflag!(0b1; 0x0),
             field_area_altimeter: bitflag!(0b1; 0x0),
@@ -612,6 +694,7 @@ impl Default for Pointers {
             func_dbg_action_force_state_values: (0, 0),
             show_all_map_layers: bitflag!(0b1; 0x0),
             show_all_graces: bitflag!(0b1; 0x0),
+            lobby_state: pointer_chain!(0x0),
             base_addresses: BASE_ADDRESSES_2_06_0,
         }
     }
@@ -0,0 +1,429 @@
+//! Offline reader for `regulation.bin`, the file the game loads its params
+//! from.
+//!
+//! The file is encrypted with AES-256-CBC and holds a DCX-compressed BND4
+//! archive, with one PARAM file per param. PARAM files are stored with the
+//! same layout the game uses in memory, so
+//! [`Params::load_regulation`](crate::params::Params::load_regulation) can
+//! hand their rows to the generated structs and to any `ParamVisitor`.
+
+use std::io::Read;
+use std::path::Path;
+
+use aes::cipher::block_padding::NoPadding;
+use aes::cipher::{BlockDecryptMut, KeyIvInit};
+use flate2::read::ZlibDecoder;
+
+/// Name of the regulation file in the game folder.
+pub const REGULATION_FILE_NAME: &str = "regulation.bin";
+
+const REGULATION_KEY: [u8; 32] = [
+    0x99, 0xbf, 0xfc, 0x36, 0x6a, 0x6b, 0xc8, 0xc6, 0xf5, 0x82, 0x7d, 0x09, 0x36, 0x02, 0xad, 0x76,
+    0xc4, 0xdb, 0x02, 0xc3, 0xe8, 0x48, 0xaf, 0x14, 0xeb, 0x79, 0xdf, 0x1c, 0xd4, 0x8f, 0x03, 0xfd,
+];
+
+const AES_BLOCK_SIZE: usize = 16;
+
+// BND4 format flags, after undoing the bit order of the header byte.
+const BND4_IDS: u8 = 0x02;
+const BND4_NAMES: u8 = 0x04 | 0x08;
+const BND4_LONG_OFFSETS: u8 = 0x10;
+const BND4_COMPRESSION: u8 = 0x20;
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+/// A file stored in a BND4 archive.
+#[derive(Debug, Clone)]
+pub struct BinderFile {
+    pub id: i32,
+    /// Path as stored in the archive, e.g.
+    /// `N:\GR\data\Param\param\GameParam\EquipParamWeapon.param`.
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl BinderFile {
+    /// File name without directories and extension, e.g. `EquipParamWeapon`.
+    pub fn stem(&self) -> &str {
+        let name = self.name.rsplit(['\\', '/']).next().unwrap_or(&self.name);
+        name.split_once('.').map_or(name, |(stem, _)| stem)
+    }
+
+    pub fn is_param(&self) -> bool {
+        self.name.to_ascii_lowercase().ends_with(".param")
+    }
+}
+
+/// Reads the files in a `regulation.bin`, or in the one in the given game
+/// folder.
+pub fn load(path: &Path) -> Result<Vec<BinderFile>, String> {
+    let path = if path.is_dir() { path.join(REGULATION_FILE_NAME) } else { path.to_path_buf() };
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+    read_regulation(&bytes).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Decrypts, decompresses and unpacks the contents of a `regulation.bin`.
+pub fn read_regulation(bytes: &[u8]) -> Result<Vec<BinderFile>, String> {
+    read_bnd4(&decompress_dcx(&decrypt(bytes)?)?)
+}
+
+/// Decrypts a `regulation.bin`. The first block of the file is the IV.
+pub fn decrypt(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    if bytes.len() <= AES_BLOCK_SIZE || !bytes.len().is_multiple_of(AES_BLOCK_SIZE) {
+        return Err(format!("Invalid regulation size {:#x}", bytes.len()));
+    }
+
+    let (iv, data) = bytes.split_at(AES_BLOCK_SIZE);
+    let mut buf = data.to_vec();
+    Aes256CbcDec::new_from_slices(&REGULATION_KEY, iv)
+        .map_err(|e| format!("Invalid regulation key: {e}"))?
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .map_err(|e| format!("Couldn't decrypt regulation: {e}"))?;
+
+    Ok(buf)
+}
+
+/// Decompresses a DCX file. Only zlib (`DFLT`) compression is supported;
+/// other compressions are reported by name rather than failing to inflate.
+pub fn decompress_dcx(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    expect_magic(bytes, 0, b"DCX\0")?;
+
+    let dcs = be_u32_at(bytes, 0x08)? as usize;
+    let dcp = be_u32_at(bytes, 0x0c)? as usize;
+    let data_start = be_u32_at(bytes, 0x14)? as usize;
+
+    expect_magic(bytes, dcs, b"DCS\0")?;
+    let uncompressed_size = be_u32_at(bytes, dcs + 4)? as usize;
+    let compressed_size = be_u32_at(bytes, dcs + 8)? as usize;
+
+    expect_magic(bytes, dcp, b"DCP\0")?;
+    let compression = bytes.get(dcp + 4..dcp + 8).ok_or("Truncated DCX header")?;
+    match compression {
+        b"DFLT" => {},
+        b"KRAK" | b"ZSTD" | b"EDGE" => {
+            return Err(format!(
+                "Unsupported DCX compression DCX_{}, only DCX_DFLT can be read",
+                String::from_utf8_lossy(compression)
+            ));
+        },
+        _ => {
+            return Err(format!(
+                "Unknown DCX compression {:?}",
+                String::from_utf8_lossy(compression)
+            ));
+        },
+    }
+
+    let data = bytes
+        .get(data_start..data_start + compressed_size)
+        .ok_or_else(|| format!("Truncated DCX data ({compressed_size:#x} bytes)"))?;
+
+    // Zlib streams start with the deflate method in the low nibble.
+    if data.first().is_none_or(|cmf| cmf & 0x0f != 8) {
+        return Err(format!("DCX_DFLT data at {data_start:#x} isn't a zlib stream"));
+    }
+
+    let mut out = Vec::with_capacity(uncompressed_size);
+    ZlibDecoder::new(data)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("Couldn't decompress DCX: {e}"))?;
+
+    if out.len() != uncompressed_size {
+        return Err(format!(
+            "DCX decompressed to {:#x} bytes, expected {uncompressed_size:#x}",
+            out.len()
+        ));
+    }
+
+    Ok(out)
+}
+
+/// Unpacks a little-endian BND4 archive. Compressed files are not supported.
+pub fn read_bnd4(bytes: &[u8]) -> Result<Vec<BinderFile>, String> {
+    expect_magic(bytes, 0, b"BND4")?;
+
+    if u8_at(bytes, 0x09)? != 0 {
+        return Err("Big-endian BND4 archives are not supported".to_string());
+    }
+
+    let bit_big_endian = u8_at(bytes, 0x0a)? == 0;
+    let file_count = u32_at(bytes, 0x0c)? as usize;
+    let file_header_size = u64_at(bytes, 0x20)? as usize;
+    let unicode = u8_at(bytes, 0x30)? != 0;
+    let format = bnd4_format(u8_at(bytes, 0x31)?, bit_big_endian);
+
+    (0..file_count)
+        .map(|i| {
+            let header = 0x40 + i * file_header_size;
+            let size = u64_at(bytes, header + 0x08)? as usize;
+            let mut pos = header + 0x10;
+
+            if format & BND4_COMPRESSION != 0 {
+                let uncompressed_size = u64_at(bytes, pos)? as usize;
+                if uncompressed_size != size {
+                    return Err(format!("File {i} in BND4 is compressed"));
+                }
+                pos += 8;
+            }
+
+            let data_offset = if format & BND4_LONG_OFFSETS != 0 {
+                pos += 8;
+                u64_at(bytes, pos - 8)? as usize
+            } else {
+                pos += 4;
+                u32_at(bytes, pos - 4)? as usize
+            };
+
+            let id = if format & BND4_IDS != 0 {
+                pos += 4;
+                u32_at(bytes, pos - 4)? as i32
+            } else {
+                -1
+            };
+
+            let name = if format & BND4_NAMES != 0 {
+                read_string(bytes, u32_at(bytes, pos)? as usize, unicode)?
+            } else {
+                String::new()
+            };
+
+            let data = bytes
+                .get(data_offset..data_offset + size)
+                .ok_or_else(|| format!("Truncated BND4 file {name}"))?
+                .to_vec();
+
+            Ok(BinderFile { id, name, data })
+        })
+        .collect()
+}
+
+/// The header byte is stored with its bits reversed, unless the archive or
+/// the byte itself says otherwise.
+fn bnd4_format(raw: u8, bit_big_endian: bool) -> u8 {
+    if bit_big_endian || (raw & 0x01 != 0 && raw & 0x80 == 0) {
+        raw
+    } else {
+        raw.reverse_bits()
+    }
+}
+
+fn read_string(bytes: &[u8], offset: usize, unicode: bool) -> Result<String, String> {
+    let tail = bytes.get(offset..).ok_or_else(|| format!("Invalid name offset {offset:#x}"))?;
+
+    if unicode {
+        let chars = tail
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect::<Vec<_>>();
+        Ok(String::from_utf16_lossy(&chars))
+    } else {
+        let len = tail.iter().position(|&c| c == 0).unwrap_or(tail.len());
+        Ok(String::from_utf8_lossy(&tail[..len]).into_owned())
+    }
+}
+
+fn expect_magic(bytes: &[u8], offset: usize, magic: &[u8; 4]) -> Result<(), String> {
+    if bytes.get(offset..offset + 4) == Some(magic) {
+        Ok(())
+    } else {
+        Err(format!("Missing {} signature at {offset:#x}", String::from_utf8_lossy(&magic[..3])))
+    }
+}
+
+fn bytes_at<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], String> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| format!("Truncated file at {offset:#x}"))
+}
+
+fn u8_at(bytes: &[u8], offset: usize) -> Result<u8, String> {
+    bytes_at::<1>(bytes, offset).map(|[b]| b)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes_at(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn be_u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes_at(bytes, offset).map(u32::from_be_bytes)
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    bytes_at(bytes, offset).map(u64::from_le_bytes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Write;
+
+    use aes::cipher::BlockEncryptMut;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    /// Packs `files` into a BND4 archive laid out like the regulation's.
+    pub(crate) fn bnd4(files: &[(i32, &str, &[u8])]) -> Vec<u8> {
+        const FILE_HEADER_SIZE: usize = 0x24;
+
+        let names_start = 0x40 + files.len() * FILE_HEADER_SIZE;
+        let names = files
+            .iter()
+            .map(|(_, name, _)| {
+                name.encode_utf16().chain(Some(0)).flat_map(u16::to_le_bytes).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let data_start = names_start + names.iter().map(Vec::len).sum::<usize>();
+
+        let mut out = vec![0u8; 0x40];
+        out[0..4].copy_from_slice(b"BND4");
+        out[0x0a] = 1;
+        out[0x0c..0x10].copy_from_slice(&(files.len() as u32).to_le_bytes());
+        out[0x10..0x18].copy_from_slice(&0x40u64.to_le_bytes());
+        out[0x18..0x20].copy_from_slice(b"07D7R6\0\0");
+        out[0x20..0x28].copy_from_slice(&(FILE_HEADER_SIZE as u64).to_le_bytes());
+        out[0x28..0x30].copy_from_slice(&(data_start as u64).to_le_bytes());
+        out[0x30] = 1;
+        out[0x31] = (BND4_IDS | BND4_NAMES | BND4_COMPRESSION).reverse_bits();
+
+        let mut name_offset = names_start;
+        let mut data_offset = data_start;
+        for ((id, _, data), name) in files.iter().zip(&names) {
+            out.extend_from_slice(&[0x40, 0, 0, 0]);
+            out.extend_from_slice(&(-1i32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+            out.extend_from_slice(&id.to_le_bytes());
+            out.extend_from_slice(&(name_offset as u32).to_le_bytes());
+            name_offset += name.len();
+            data_offset += data.len();
+        }
+
+        out.extend(names.concat());
+        for (_, _, data) in files {
+            out.extend_from_slice(data);
+        }
+
+        out
+    }
+
+    pub(crate) fn dcx(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"DCX\0");
+        for v in [0x11000u32, 0x18, 0x24, 0x44, 0x4c] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        out.extend_from_slice(b"DCS\0");
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        out.extend_from_slice(b"DCP\0DFLT");
+        for v in [0x20u32, 0x0900_0000, 0, 0, 0, 0x0001_0100] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        out.extend_from_slice(b"DCA\0");
+        out.extend_from_slice(&8u32.to_be_bytes());
+        out.extend(compressed);
+        out
+    }
+
+    pub(crate) fn encrypt(data: &[u8]) -> Vec<u8> {
+        let iv = [0x5au8; AES_BLOCK_SIZE];
+        let mut buf = data.to_vec();
+        buf.resize(data.len().next_multiple_of(AES_BLOCK_SIZE), 0);
+
+        cbc::Encryptor::<aes::Aes256>::new_from_slices(&REGULATION_KEY, &iv)
+            .unwrap()
+            .encrypt_padded_mut::<NoPadding>(&mut buf, data.len().next_multiple_of(AES_BLOCK_SIZE))
+            .unwrap();
+
+        [&iv[..], &buf].concat()
+    }
+
+    /// A `regulation.bin` holding the given files.
+    pub(crate) fn regulation(files: &[(i32, &str, &[u8])]) -> Vec<u8> {
+        encrypt(&dcx(&bnd4(files)))
+    }
+
+    #[test]
+    fn test_read_regulation() {
+        let files = read_regulation(&regulation(&[
+            (0, r"N:\GR\data\Param\param\GameParam\EquipParamWeapon.param", b"weapons"),
+            (1, r"N:\GR\data\Param\param\GameParam\SpEffectParam.param", b"speffects"),
+        ]))
+        .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].stem(), "EquipParamWeapon");
+        assert_eq!(files[1].id, 1);
+        assert_eq!(files[1].data, b"speffects");
+        assert!(files.iter().all(BinderFile::is_param));
+    }
+
+    /// The first 0x50 bytes of a DCX_DFLT and of a DCX_ZSTD file, laid out
+    /// like the game's files: DCX, DCS, DCP and DCA headers followed by the
+    /// magic of the compressed stream. Only the sizes in DCS are made up.
+    const DFLT_HEADER: [u8; 0x50] = [
+        0x44, 0x43, 0x58, 0x00, 0x00, 0x01, 0x10, 0x00, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00,
+        0x24, 0x00, 0x00, 0x00, 0x44, 0x00, 0x00, 0x00, 0x4c, 0x44, 0x43, 0x53, 0x00, 0x00, 0x1d,
+        0xc6, 0x10, 0x00, 0x0a, 0x2f, 0x5e, 0x44, 0x43, 0x50, 0x00, 0x44, 0x46, 0x4c, 0x54, 0x00,
+        0x00, 0x00, 0x20, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x44, 0x43, 0x41, 0x00, 0x00, 0x00, 0x00,
+        0x08, 0x78, 0xda, 0xec, 0xbd,
+    ];
+    const ZSTD_HEADER: [u8; 0x50] = [
+        0x44, 0x43, 0x58, 0x00, 0x00, 0x01, 0x10, 0x00, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00,
+        0x24, 0x00, 0x00, 0x00, 0x44, 0x00, 0x00, 0x00, 0x4c, 0x44, 0x43, 0x53, 0x00, 0x00, 0x1f,
+        0x0a, 0x90, 0x00, 0x08, 0x41, 0x37, 0x44, 0x43, 0x50, 0x00, 0x5a, 0x53, 0x54, 0x44, 0x00,
+        0x00, 0x00, 0x20, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x44, 0x43, 0x41, 0x00, 0x00, 0x00, 0x00,
+        0x08, 0x28, 0xb5, 0x2f, 0xfd,
+    ];
+
+    #[test]
+    fn test_dcx_headers() {
+        // The header is read, then the data found to be missing.
+        assert_eq!(decompress_dcx(&DFLT_HEADER).unwrap_err(), "Truncated DCX data (0xa2f5e bytes)");
+        assert_eq!(
+            decompress_dcx(&ZSTD_HEADER).unwrap_err(),
+            "Unsupported DCX compression DCX_ZSTD, only DCX_DFLT can be read"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(decrypt(&[0; 15]).is_err());
+        assert!(decompress_dcx(b"not a dcx file").is_err());
+        assert!(read_bnd4(b"BND4").is_err());
+
+        let mut krak = dcx(&bnd4(&[]));
+        krak[0x28..0x2c].copy_from_slice(b"KRAK");
+        assert_eq!(
+            decompress_dcx(&krak).unwrap_err(),
+            "Unsupported DCX compression DCX_KRAK, only DCX_DFLT can be read"
+        );
+
+        let mut unknown = dcx(&bnd4(&[]));
+        unknown[0x28..0x2c].copy_from_slice(b"\0\0\0\0");
+        assert!(decompress_dcx(&unknown).unwrap_err().starts_with("Unknown DCX compression"));
+
+        let mut not_zlib = dcx(&bnd4(&[]));
+        not_zlib[0x4c] = 0;
+        assert_eq!(
+            decompress_dcx(&not_zlib).unwrap_err(),
+            "DCX_DFLT data at 0x4c isn't a zlib stream"
+        );
+
+        // Wrong key or IV: the plaintext isn't a DCX file.
+        let mut bytes = regulation(&[]);
+        bytes[0] ^= 1;
+        assert!(read_regulation(&bytes).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Multiplayer session tracking.
+//!
+//! The lobby state lives in `CSSessionManager` and follows the game's own
+//! session lifecycle: creating a session (placing a sign, using a finger),
+//! hosting, joining someone else's world, and leaving. [`SessionGuard`] turns
+//! the stream of sampled states into engage/release events, so that practice
+//! features can be turned off for as long as other players are involved.
+
+use std::fmt::Display;
+
+/// Number of consecutive offline samples needed before the guard releases.
+/// Smooths over the state briefly dropping to zero on session transitions.
+pub const RELEASE_AFTER_SAMPLES: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// No session, and none is being created or joined.
+    Offline,
+    /// Waiting to host or join a session, or leaving one.
+    Searching,
+    /// Hosting a session, e.g. after using a Tarnished's Furled Finger.
+    HostOfFingers,
+    /// Joined someone else's world.
+    Invader,
+}
+
+impl SessionState {
+    /// Interprets the lobby state stored in `CSSessionManager`. Unknown
+    /// values are treated as an active session.
+    pub fn from_lobby_state(lobby_state: u32) -> Self {
+        match lobby_state {
+            // None, FailedToCreateSession, FailedToJoinSession
+            0 | 2 | 5 => SessionState::Offline,
+            // TryToCreateSession, TryToJoinSession, OnLeaveSession,
+            // FailedToLeaveSession
+            1 | 4 | 7 | 8 => SessionState::Searching,
+            3 => SessionState::HostOfFingers,
+            6 => SessionState::Invader,
+            _ => SessionState::Searching,
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        *self != SessionState::Offline
+    }
+}
+
+impl Display for SessionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionState::Offline => write!(f, "offline"),
+            SessionState::Searching => write!(f, "searching"),
+            SessionState::HostOfFingers => write!(f, "host of fingers"),
+            SessionState::Invader => write!(f, "invader"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardEvent {
+    Engaged(SessionState),
+    Released,
+}
+
+/// Engages as soon as a session becomes active, and releases only after the
+/// game has been offline for a number of consecutive samples.
+#[derive(Debug)]
+pub struct SessionGuard {
+    state: SessionState,
+    engaged: bool,
+    offline_samples: u32,
+    release_after: u32,
+}
+
+impl SessionGuard {
+    pub fn new(release_after: u32) -> Self {
+        SessionGuard {
+            state: SessionState::Offline,
+            engaged: false,
+            offline_samples: 0,
+            release_after,
+        }
+    }
+
+    /// Feeds a new sample to the guard. Returns an event if the guard engaged
+    /// or released as a consequence.
+    pub fn update(&mut self, state: SessionState) -> Option<GuardEvent> {
+        self.state = state;
+
+        if state.is_online() {
+            self.offline_samples = 0;
+            if !self.engaged {
+                self.engaged = true;
+                return Some(GuardEvent::Engaged(state));
+            }
+            return None;
+        }
+
+        if self.engaged {
+            self.offline_samples += 1;
+            if self.offline_samples >= self.release_after {
+                self.engaged = false;
+                self.offline_samples = 0;
+                return Some(GuardEvent::Released);
+            }
+        }
+
+        None
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// The most recently sampled state.
+    pub fn state(&self) -> SessionState {
+        self.state
+    }
+}
+
+impl Default for SessionGuard {
+    fn default() -> Self {
+        SessionGuard::new(RELEASE_AFTER_SAMPLES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(guard: &mut SessionGuard, lobby_states: &[u32]) -> Vec<(usize, GuardEvent)> {
+        lobby_states
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &s)| guard.update(SessionState::from_lobby_state(s)).map(|e| (i, e)))
+            .collect()
+    }
+
+    #[test]
+    fn test_invasion() {
+        let mut guard = SessionGuard::new(3);
+        // Offline, use a finger, join, leave, back to offline.
+        let events = replay(&mut guard, &[0, 0, 4, 4, 6, 6, 6, 7, 0, 0, 0, 0]);
+
+        assert_eq!(events, vec![
+            (2, GuardEvent::Engaged(SessionState::Searching)),
+            (10, GuardEvent::Released),
+        ]);
+        assert!(!guard.is_engaged());
+    }
+
+    #[test]
+    fn test_short_offline_gap_keeps_guard_engaged() {
+        let mut guard = SessionGuard::new(3);
+        let events = replay(&mut guard, &[3, 3, 0, 0, 3, 0, 0]);
+
+        assert_eq!(events, vec![(0, GuardEvent::Engaged(SessionState::HostOfFingers))]);
+        assert!(guard.is_engaged());
+        assert_eq!(guard.state(), SessionState::Offline);
+    }
+
+    #[test]
+    fn test_unknown_lobby_state_engages() {
+        assert_eq!(SessionState::from_lobby_state(0xff), SessionState::Searching);
+        assert_eq!(SessionState::from_lobby_state(5), SessionState::Offline);
+
+        let mut guard = SessionGuard::default();
+        assert_eq!(replay(&mut guard, &[0, 42]), vec![(
+            1,
+            GuardEvent::Engaged(SessionState::Searching)
+        )]);
+    }
+}
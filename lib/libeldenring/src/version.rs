@@ -1,24 +1,65 @@
+use std::collections::BTreeMap;
+#[cfg(windows)]
 use std::ffi::OsStr;
+#[cfg(windows)]
 use std::os::windows::ffi::OsStrExt;
+#[cfg(windows)]
+use std::path::PathBuf;
+#[cfg(windows)]
 use std::ptr::null_mut;
 use std::sync::OnceLock;
 
+#[cfg(windows)]
 use log::*;
+#[cfg(windows)]
 use widestring::U16CString;
+#[cfg(windows)]
 use windows::core::{w, PCWSTR};
+#[cfg(windows)]
 use windows::Win32::Foundation::{HWND, MAX_PATH};
+#[cfg(windows)]
 use windows::Win32::Storage::FileSystem::{
     GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
 };
+#[cfg(windows)]
 use windows::Win32::System::LibraryLoader::{GetModuleFileNameW, GetModuleHandleW};
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_ICONERROR, MB_OK};
 
+use crate::address_pack::AddressPack;
+use crate::aob::{self, ScanResult};
+use crate::offsets::Offsets;
+use crate::prelude::base_addresses::BaseAddresses;
 pub use crate::prelude::base_addresses::Version;
+#[cfg(windows)]
+use crate::{address_pack, memory, pe};
+
+/// The most recent version in the compiled-in tables. Its version-specific
+/// offsets are used when running with unverified addresses.
+pub const LATEST_VERSION: Version = Version::V2_06_0;
+
+/// Where the base addresses in use come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSource {
+    /// The compiled-in table for a known version.
+    BuiltIn,
+    /// An entry in the address pack.
+    Pack,
+    /// A scan of the game module. The addresses are unverified.
+    Scan,
+}
 
 static VERSION: OnceLock<Version> = OnceLock::new();
+static EXE_VERSION: OnceLock<(u32, u32, u32)> = OnceLock::new();
+static ADDRESS_PACK: OnceLock<AddressPack> = OnceLock::new();
+static ADDRESSES: OnceLock<(AddressSource, BTreeMap<&'static str, usize>)> = OnceLock::new();
+static OFFSETS: OnceLock<Offsets> = OnceLock::new();
+#[cfg(windows)]
+static DEFERRED_EXE_PATH: OnceLock<PathBuf> = OnceLock::new();
 
 /// Ensures that the VERSION static gets filled, or returns an error.
 /// The caller MUST exit cleanly in case of an error.
+#[cfg(windows)]
 pub fn check_version() -> Result<Version, (u32, u32, u32)> {
     if let Some(version) = VERSION.get().copied() {
         return Ok(version);
@@ -58,22 +99,184 @@ pub fn check_version() -> Result<Version, (u32, u32, u32)> {
     let patch = (version_info.dwFileVersionLS >> 16) & 0xffff;
 
     info!("Version {} {} {}", major, minor, patch);
+    match pe::module_version(memory::default_source().as_ref()) {
+        Ok(pe_version) if pe_version == (major, minor, patch) => {},
+        Ok((pe_major, pe_minor, pe_patch)) => {
+            warn!("Version resource in memory says {pe_major}.{pe_minor}.{pe_patch}")
+        },
+        Err(e) => warn!("Couldn't read the version resource in memory: {e}"),
+    }
+    EXE_VERSION.set((major, minor, patch)).ok();
+
+    // Hashing the executable takes too long to do while holding the loader
+    // lock, so [`check_address_pack_by_hash`] does it later.
+    if address_pack_needs_hash((major, minor, patch)) {
+        info!("Looking up the address pack once the executable is hashed");
+        DEFERRED_EXE_PATH.set(PathBuf::from(file_path.to_os_string())).ok();
+        let version = Version::try_from((major, minor, patch)).unwrap_or(LATEST_VERSION);
+        while VERSION.set(version).is_err() {}
+        return Ok(version);
+    }
+
+    if let Some(version) = apply_address_pack((major, minor, patch), || None) {
+        while VERSION.set(version).is_err() {}
+        return Ok(version);
+    }
+
     match Version::try_from((major, minor, patch)) {
         Ok(version) => {
             while VERSION.set(version).is_err() {}
             Ok(version)
         },
         Err(()) => {
+            scan_unknown_version((major, minor, patch))?;
+            while VERSION.set(LATEST_VERSION).is_err() {}
+            Ok(LATEST_VERSION)
+        },
+    }
+}
+
+/// Finishes the lookup that [`check_version`] deferred when the address pack
+/// has entries keyed by hash for the running version: hashes the executable
+/// and applies the matching entry, falling back to a scan for unknown
+/// versions. Must be called outside of `DllMain`, before the base addresses
+/// are used. The caller MUST not use the game's memory in case of an error.
+#[cfg(windows)]
+pub fn check_address_pack_by_hash() -> Result<(), (u32, u32, u32)> {
+    let (Some(exe_path), Some(exe_version)) = (DEFERRED_EXE_PATH.get(), exe_version()) else {
+        return Ok(());
+    };
+
+    if apply_address_pack(exe_version, || address_pack::sha256_file(exe_path).ok()).is_some()
+        || Version::try_from(exe_version).is_ok()
+    {
+        return Ok(());
+    }
+
+    scan_unknown_version(exe_version)
+}
+
+/// Whether the address pack has an entry keyed by hash that must be checked
+/// before any other entry for `exe_version`.
+#[cfg(windows)]
+fn address_pack_needs_hash(exe_version: (u32, u32, u32)) -> bool {
+    let mut needs_hash = false;
+    if let Some(pack) = ADDRESS_PACK.get() {
+        pack.find(exe_version, || {
+            needs_hash = true;
+            None
+        });
+    }
+    needs_hash
+}
+
+/// Uses the addresses from the address pack, if it has an entry for the
+/// running executable. Returns the version to use for version-specific
+/// offsets.
+#[cfg(windows)]
+fn apply_address_pack(
+    exe_version: (u32, u32, u32),
+    exe_sha256: impl FnOnce() -> Option<String>,
+) -> Option<Version> {
+    let pack = ADDRESS_PACK.get()?;
+    match address_pack::resolve(pack, exe_version, exe_sha256)? {
+        Ok(resolved) => {
+            info!("Using base addresses from the address pack");
+            ADDRESSES.set((AddressSource::Pack, resolved.addresses)).ok()?;
+            OFFSETS.set(resolved.offsets).ok();
+            Some(Version::try_from(exe_version).unwrap_or(LATEST_VERSION))
+        },
+        Err(e) => {
+            warn!("Ignoring address pack: {e}");
+            None
+        },
+    }
+}
+
+/// Looks up the base addresses by scanning the game module. Only succeeds if
+/// every pattern was found, otherwise tells the user the version isn't
+/// supported.
+#[cfg(windows)]
+fn scan_unknown_version((major, minor, patch): (u32, u32, u32)) -> Result<(), (u32, u32, u32)> {
+    let scan = || {
+        let scan = aob::scan_module(memory::default_source().as_ref())?;
+        if !scan.missing.is_empty() {
+            return Err(format!("Not found: {}", scan.missing.join(", ")));
+        }
+        set_scanned_addresses(scan).map_err(|_| "Addresses were already set".to_string())
+    };
+
+    match scan() {
+        Ok(()) => {
+            warn!("Unknown version {major}.{minor}.{patch}, using unverified addresses");
+            Ok(())
+        },
+        Err(e) => {
+            error!("Couldn't scan for addresses: {e}");
             error_messagebox((major, minor, patch));
             Err((major, minor, patch))
         },
     }
 }
 
+/// Sets the VERSION static explicitly, for when it can't be read from the
+/// running executable, e.g. when attaching to the game from another process.
+/// Returns an error if the version was already set.
+pub fn set_version(version: Version) -> Result<(), Version> {
+    VERSION.set(version)
+}
+
 pub fn get_version() -> Version {
-    VERSION.get().copied().unwrap_or(Version::V2_06_0)
+    VERSION.get().copied().unwrap_or(LATEST_VERSION)
+}
+
+/// The version of the game executable, as read by [`check_version`]. May not
+/// be a known [`Version`].
+pub fn exe_version() -> Option<(u32, u32, u32)> {
+    EXE_VERSION.get().copied()
+}
+
+/// Sets the address pack to look up the running executable in. Must be
+/// called before [`check_version`]. Returns an error if it was already set.
+pub fn set_address_pack(pack: AddressPack) -> Result<(), AddressPack> {
+    ADDRESS_PACK.set(pack)
+}
+
+/// Uses the addresses from a scan instead of the compiled-in tables.
+/// Returns an error if addresses were already set.
+pub fn set_scanned_addresses(scan: ScanResult) -> Result<(), ScanResult> {
+    ADDRESSES.set((AddressSource::Scan, scan.found.clone())).map_err(|_| scan)
+}
+
+pub fn address_source() -> AddressSource {
+    ADDRESSES.get().map(|(source, _)| *source).unwrap_or(AddressSource::BuiltIn)
+}
+
+/// Whether the base addresses come from a scan rather than from a known
+/// version or the address pack.
+pub fn is_unverified() -> bool {
+    address_source() == AddressSource::Scan
+}
+
+/// Base addresses for the running game, relative to the module base. Prefers
+/// the address pack or a scan over the compiled-in tables.
+pub fn base_addresses() -> BaseAddresses {
+    match ADDRESSES.get() {
+        Some((_, addresses)) => aob::base_addresses_from_map(addresses),
+        None => BaseAddresses::from(get_version()),
+    }
+}
+
+/// Version-specific offsets for the running game, including overrides from
+/// the address pack.
+pub fn offsets() -> Offsets {
+    OFFSETS.get().copied().unwrap_or_else(|| {
+        Offsets::resolve(get_version().into(), &BTreeMap::new())
+            .expect("Every known version has offsets")
+    })
 }
 
+#[cfg(windows)]
 fn error_messagebox((major, minor, patch): (u32, u32, u32)) {
     let caption = OsStr::new("Elden Ring Practice Tool - Unsupported version")
         .encode_wide()
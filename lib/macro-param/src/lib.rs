@@ -141,6 +141,8 @@ pub fn macro_param(t: TokenStream) -> TokenStream {
         }
 
         impl ParamStruct for #name {
+            const NAME: &'static str = stringify!(#name);
+
             #visit
         }
 
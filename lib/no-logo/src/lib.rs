@@ -72,11 +72,11 @@ fn dinput8_path() -> U16CString {
 
 unsafe fn apply_patch() {
     let module_base = GetModuleHandleW(PCWSTR(null_mut())).unwrap();
-    let Ok(version) = version::check_version() else {
+    if version::check_version().is_err() {
         return;
-    };
+    }
 
-    let offset = base_addresses::BaseAddresses::from(version).func_remove_intro_screens;
+    let offset = version::base_addresses().func_remove_intro_screens;
 
     let ptr = (module_base.0 as usize + offset) as *mut [u8; 2];
     let mut old = PAGE_PROTECTION_FLAGS(0);
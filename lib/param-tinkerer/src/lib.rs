@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsString;
 use std::fmt::Write;
 use std::os::windows::prelude::OsStringExt;
@@ -8,6 +9,10 @@ use hudhook::hooks::dx12::ImguiDx12Hooks;
 use hudhook::tracing::{error, info};
 use hudhook::ImguiRenderLoop;
 use imgui::*;
+use libeldenring::param_diff::{self, FieldChange};
+use libeldenring::param_io;
+use libeldenring::param_meta::{EnumOption, FieldMeta, PARAM_META};
+use libeldenring::param_search::{self, SearchResult};
 use libeldenring::prelude::*;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::prelude::*;
@@ -43,13 +48,99 @@ pub fn get_dll_path() -> Option<PathBuf> {
     Some(OsString::from_wide(&sz_filename[..len]).into())
 }
 
+/// Directory next to the DLL that params are exported to and imported from.
+fn export_dir() -> Result<PathBuf, String> {
+    let mut path = get_dll_path().ok_or("Could not find the DLL path")?;
+    path.pop();
+    path.push("params");
+    std::fs::create_dir_all(&path)
+        .map_err(|e| format!("Could not create {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+fn export_json(params: &Params) -> Result<String, String> {
+    let path = export_dir()?.join("params.json");
+    std::fs::write(&path, param_io::to_json(&param_io::export_all(params)))
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(format!("Exported all params to {}", path.display()))
+}
+
+fn import_json(params: &Params) -> Result<String, String> {
+    let path = export_dir()?.join("params.json");
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let rows = param_io::import(params, &param_io::from_json(&json)?).map_err(|e| e.join("\n"))?;
+    Ok(format!("Imported {rows} rows from {}", path.display()))
+}
+
+fn export_csv(params: &Params, param: &str) -> Result<String, String> {
+    let path = export_dir()?.join(format!("{param}.csv"));
+    std::fs::write(&path, param_io::param_to_csv(params, param)?)
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(format!("Exported {param} to {}", path.display()))
+}
+
+fn import_csv(params: &Params, param: &str) -> Result<String, String> {
+    let path = export_dir()?.join(format!("{param}.csv"));
+    let csv = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let dump = [(param.to_string(), param_io::param_from_csv(&csv)?)].into_iter().collect();
+    let rows = param_io::import(params, &dump).map_err(|e| e.join("\n"))?;
+    Ok(format!("Imported {rows} rows from {}", path.display()))
+}
+
+/// The rows listed by "Modified only". Comparing every param against its
+/// snapshot is too slow to do every frame, so this is only recomputed after
+/// an edit, an import or a revert, or when refreshed.
+struct ModifiedRows {
+    stale: bool,
+    /// Edited row ids, by param.
+    rows: BTreeMap<String, BTreeSet<u64>>,
+}
+
+impl ModifiedRows {
+    fn update(&mut self, params: &Params) {
+        if !self.stale {
+            return;
+        }
+
+        self.rows.clear();
+        for change in param_diff::all_changes(params) {
+            self.rows.entry(change.param).or_default().insert(change.id);
+        }
+        self.stale = false;
+    }
+
+    fn is_param_modified(&self, param: &str) -> bool {
+        self.rows.contains_key(param)
+    }
+
+    fn is_row_modified(&self, param: &str, id: u64) -> bool {
+        self.rows.get(param).is_some_and(|ids| ids.contains(&id))
+    }
+}
+
 struct ParamTinkerer {
     shown: bool,
     selected_param: usize,
     selected_param_id: usize,
+    /// Only list the params and rows that were edited.
+    modified_only: bool,
+    modified: ModifiedRows,
+    /// Outcome of the last export, import or revert.
+    status: Option<Result<String, String>>,
+    search_field: String,
+    search_value: String,
+    /// Results of the last search, or why it couldn't run.
+    search_results: Result<Vec<SearchResult>, String>,
+    /// Scroll the param lists to the selected row, after jumping to it.
+    scroll_to_selection: bool,
     pointers: Pointers,
 }
 
+/// Searches stop after this many results.
+const SEARCH_LIMIT: usize = 1000;
+
 impl ParamTinkerer {
     fn new() -> Self {
         println!("Initializing");
@@ -109,6 +200,13 @@ impl ParamTinkerer {
             shown: true,
             selected_param: 0,
             selected_param_id: 0,
+            modified_only: false,
+            modified: ModifiedRows { stale: true, rows: BTreeMap::new() },
+            status: None,
+            search_field: String::new(),
+            search_value: String::new(),
+            search_results: Ok(Vec::new()),
+            scroll_to_selection: false,
             pointers: Pointers::new(),
         }
     }
@@ -144,20 +242,99 @@ impl ImguiRenderLoop for ParamTinkerer {
 
                 style_tokens.into_iter().rev().for_each(|t| t.pop());
             });
+
+        ui.window("##search_window")
+            .position([540., 16.], Condition::Always)
+            .bg_alpha(0.8)
+            .flags({
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SCROLLBAR
+                    | WindowFlags::ALWAYS_AUTO_RESIZE
+            })
+            .build(|| self.render_search(ui));
     }
 }
 
 impl ParamTinkerer {
+    /// Searches every param for a field name and/or value. Clicking a result
+    /// selects its row in the param lists.
+    pub fn render_search(&mut self, ui: &imgui::Ui) {
+        let _width = ui.push_item_width(200.);
+        ui.input_text("Field", &mut self.search_field).hint("e.g. sp_effect").build();
+        ui.input_text("Value", &mut self.search_value).hint("e.g. 12345").build();
+
+        if ui.button("Search") {
+            self.search_results = param_search::Query::new(&self.search_field, &self.search_value)
+                .map(|query| param_search::search(&PARAMS.read(), &query, SEARCH_LIMIT));
+        }
+
+        let results = match &self.search_results {
+            Ok(results) => results,
+            Err(e) => {
+                ui.text_colored([1., 0.3, 0.3, 1.], e);
+                return;
+            },
+        };
+
+        ui.same_line();
+        if results.len() == SEARCH_LIMIT {
+            ui.text(format!("First {SEARCH_LIMIT} results"));
+        } else {
+            ui.text(format!("{} results", results.len()));
+        }
+
+        let mut selected = None;
+        ListBox::new("##search_results").size([500., 520.]).build(ui, || {
+            for (idx, result) in results.iter().enumerate() {
+                let label = format!(
+                    "{} {} {} {} = {}##{idx}",
+                    result.param,
+                    result.id,
+                    result.name.as_deref().unwrap_or("-"),
+                    result.field,
+                    result.value
+                );
+                if ui.selectable(label) {
+                    selected = Some(result);
+                }
+            }
+        });
+
+        if let Some(result) = selected {
+            if let Some(param_idx) = PARAMS.read().keys().position(|k| *k == result.param) {
+                self.selected_param = param_idx;
+                self.selected_param_id = result.index;
+                // The row may not be edited, and would be hidden.
+                self.modified_only = false;
+                self.scroll_to_selection = true;
+            }
+        }
+    }
+
     pub fn render_params(&mut self, ui: &imgui::Ui) {
         let params = PARAMS.write();
 
         ui.child_window("##param_child_wnd")
             .flags(WindowFlags::NO_SCROLLBAR)
-            .size([500., 450.])
+            .size([500., 560.])
             .build(|| {
                 const COLUMN1: f32 = 240.;
                 const COLUMN2: f32 = 240.;
                 const COLUMN3: f32 = 500.;
+                const MODIFIED_COLOR: [f32; 4] = [1., 0.8, 0.2, 1.];
+
+                if ui.checkbox("Modified only", &mut self.modified_only) {
+                    self.modified.stale = true;
+                }
+                if self.modified_only {
+                    ui.same_line();
+                    if ui.button("Refresh") {
+                        self.modified.stale = true;
+                    }
+                    self.modified.update(&params);
+                }
 
                 ui.columns(2, "##param_columns", false);
                 ui.set_column_offset(0, 0.);
@@ -169,10 +346,16 @@ impl ParamTinkerer {
                     let _ = ui.push_item_width(-1.);
                     ListBox::new("##param_names").size([COLUMN1, 220.]).build(ui, || {
                         for (idx, k) in params.keys().enumerate() {
+                            if self.modified_only && !self.modified.is_param_modified(k) {
+                                continue;
+                            }
                             if ui.selectable_config(k).selected(idx == self.selected_param).build()
                             {
                                 self.selected_param = idx;
                             }
+                            if self.scroll_to_selection && idx == self.selected_param {
+                                ui.set_scroll_here_y();
+                            }
                         }
                     });
 
@@ -187,9 +370,17 @@ impl ParamTinkerer {
                     ui.set_current_column_width(COLUMN2 + 10.);
 
                     let mut buf = String::new();
+                    let mut selected_id = None;
                     let _ = ui.push_item_width(-1.);
                     ListBox::new("##param_ids").size([COLUMN2, 220.]).build(ui, || {
                         for (idx, id) in param_entries.enumerate() {
+                            if idx == self.selected_param_id {
+                                selected_id = Some(id);
+                            }
+                            if self.modified_only && !self.modified.is_row_modified(param_name, id)
+                            {
+                                continue;
+                            }
                             let param_repr = PARAM_NAMES
                                 .get(param_name)
                                 .and_then(|param_id_names| param_id_names.get(&(id as usize)))
@@ -206,58 +397,222 @@ impl ParamTinkerer {
                                 info!("Selected {idx}: {id}");
                                 self.selected_param_id = idx;
                             }
+                            if self.scroll_to_selection && idx == self.selected_param_id {
+                                ui.set_scroll_here_y();
+                            }
                         }
                     });
+                    self.scroll_to_selection = false;
 
-                    (param_name, self.selected_param_id)
+                    (param_name, self.selected_param_id, selected_id)
                 });
 
-                if let Some((param_name, param_idx)) = param_item {
-                    struct ImguiParamVisitor<'a>(&'a imgui::Ui);
+                ui.columns(1, "##param_io_columns", false);
+                if ui.button("Export JSON") {
+                    self.status = Some(export_json(&params));
+                }
+                ui.same_line();
+                if ui.button("Import JSON") {
+                    self.status = Some(import_json(&params));
+                    self.modified.stale = true;
+                }
+                if let Some((param_name, _, selected_id)) = param_item {
+                    ui.same_line();
+                    if ui.button("Export CSV") {
+                        self.status = Some(export_csv(&params, param_name));
+                    }
+                    ui.same_line();
+                    if ui.button("Import CSV") {
+                        self.status = Some(import_csv(&params, param_name));
+                        self.modified.stale = true;
+                    }
+
+                    if let Some(id) = selected_id {
+                        if ui.button("Revert row") {
+                            self.status = Some(
+                                param_diff::revert_row(&params, param_name, id)
+                                    .map(|n| format!("Reverted {n} fields of {param_name} {id}")),
+                            );
+                            self.modified.stale = true;
+                        }
+                        ui.same_line();
+                    }
+                    if ui.button("Revert param") {
+                        self.status = Some(
+                            param_diff::revert_param(&params, param_name)
+                                .map(|n| format!("Reverted {n} rows of {param_name}")),
+                        );
+                        self.modified.stale = true;
+                    }
+                    ui.same_line();
+                }
+                if ui.button("Revert all") {
+                    self.status =
+                        Some(param_diff::revert_all(&params).map(|n| format!("Reverted {n} rows")));
+                    self.modified.stale = true;
+                }
+                match &self.status {
+                    Some(Ok(status)) => ui.text_wrapped(status),
+                    Some(Err(e)) => ui.text_colored([1., 0.3, 0.3, 1.], e),
+                    None => {},
+                }
+
+                if let Some((param_name, param_idx, selected_id)) = param_item {
+                    struct ImguiParamVisitor<'a> {
+                        ui: &'a imgui::Ui,
+                        param: &'a str,
+                        changes: &'a [FieldChange],
+                        /// Whether any field was changed.
+                        edited: bool,
+                    }
+
+                    impl<'a> ImguiParamVisitor<'a> {
+                        fn highlight(&self, name: &str) -> Option<ColorStackToken<'a>> {
+                            self.changes
+                                .iter()
+                                .any(|change| change.field == name)
+                                .then(|| self.ui.push_style_color(StyleColor::Text, MODIFIED_COLOR))
+                        }
+
+                        fn meta(&self, name: &str) -> Option<&'static FieldMeta> {
+                            PARAM_META.field(self.param, name)
+                        }
+
+                        /// Shows the display name if there is one, keeping the
+                        /// field name as the widget ID.
+                        fn label(name: &str, meta: Option<&FieldMeta>) -> String {
+                            match meta.and_then(|meta| meta.display_name.as_ref()) {
+                                Some(display_name) => format!("{display_name}##{name}"),
+                                None => name.to_string(),
+                            }
+                        }
+
+                        fn tooltip(&self, name: &str, meta: Option<&FieldMeta>) {
+                            if !self.ui.is_item_hovered() {
+                                return;
+                            }
+                            self.ui.tooltip(|| {
+                                self.ui.text(name);
+                                if let Some(description) =
+                                    meta.and_then(|meta| meta.description.as_ref())
+                                {
+                                    let _wrap = self.ui.push_text_wrap_pos_with_pos(300.);
+                                    self.ui.text(description);
+                                }
+                                match meta.map(|meta| (meta.min, meta.max)) {
+                                    Some((Some(min), Some(max))) => {
+                                        self.ui.text(format!("Range: {min} to {max}"))
+                                    },
+                                    Some((Some(min), None)) => self.ui.text(format!("Min: {min}")),
+                                    Some((None, Some(max))) => self.ui.text(format!("Max: {max}")),
+                                    _ => {},
+                                }
+                            });
+                        }
+
+                        fn enum_combo(
+                            &self,
+                            label: &str,
+                            options: &[EnumOption],
+                            v: &mut i64,
+                        ) -> bool {
+                            let preview = options
+                                .iter()
+                                .find(|option| option.value == *v)
+                                .map_or_else(|| v.to_string(), |option| option.name.clone());
+
+                            let mut changed = false;
+                            if let Some(_combo) = self.ui.begin_combo(label, preview) {
+                                for option in options {
+                                    if self
+                                        .ui
+                                        .selectable_config(format!(
+                                            "{} ({})",
+                                            option.name, option.value
+                                        ))
+                                        .selected(option.value == *v)
+                                        .build()
+                                    {
+                                        *v = option.value;
+                                        changed = true;
+                                    }
+                                }
+                            }
+                            changed
+                        }
+
+                        /// Enums are edited with a combo, other integers are
+                        /// clamped to their range.
+                        /// Values that don't fit the field are discarded.
+                        fn visit_int<T: Copy + Into<i64> + TryFrom<i64>>(
+                            &mut self,
+                            name: &str,
+                            v: &mut T,
+                        ) {
+                            let _color = self.highlight(name);
+                            let meta = self.meta(name);
+                            let label = Self::label(name, meta);
+
+                            let mut i: i64 = (*v).into();
+                            let changed = match meta.and_then(|meta| PARAM_META.enum_options(meta))
+                            {
+                                Some(options) => self.enum_combo(&label, options, &mut i),
+                                None => self.ui.input_scalar(&label, &mut i).step(1).build(),
+                            };
+                            self.tooltip(name, meta);
+
+                            if changed {
+                                let i = meta.map_or(i, |meta| meta.clamp(i as f64) as i64);
+                                if let Ok(i) = T::try_from(i) {
+                                    *v = i;
+                                    self.edited = true;
+                                }
+                            }
+                        }
+                    }
 
                     impl ParamVisitor for ImguiParamVisitor<'_> {
                         fn visit_u8(&mut self, name: &str, v: &mut u8) {
-                            let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_u16(&mut self, name: &str, v: &mut u16) {
-                            let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_u32(&mut self, name: &str, v: &mut u32) {
-                            let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_i8(&mut self, name: &str, v: &mut i8) {
-                            let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_i16(&mut self, name: &str, v: &mut i16) {
-                            let mut i = *v as i32;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_i32(&mut self, name: &str, v: &mut i32) {
-                            let mut i = *v;
-                            self.0.input_int(name, &mut i).build();
-                            *v = i as _;
+                            self.visit_int(name, v);
                         }
 
                         fn visit_f32(&mut self, name: &str, v: &mut f32) {
-                            self.0.input_float(name, v).build();
+                            let _color = self.highlight(name);
+                            let meta = self.meta(name);
+                            if self.ui.input_float(Self::label(name, meta), v).build() {
+                                *v = meta.map_or(*v, |meta| meta.clamp(*v as f64) as f32);
+                                self.edited = true;
+                            }
+                            self.tooltip(name, meta);
                         }
 
                         fn visit_bool(&mut self, name: &str, v: &mut bool) {
-                            self.0.checkbox(name, v);
+                            let _color = self.highlight(name);
+                            let meta = self.meta(name);
+                            if self.ui.checkbox(Self::label(name, meta), v) {
+                                self.edited = true;
+                            }
+                            self.tooltip(name, meta);
                         }
                     }
 
@@ -265,7 +620,19 @@ impl ParamTinkerer {
 
                     ListBox::new("##param_detail").size([COLUMN3, 220.]).build(ui, || {
                         let _tok = ui.push_item_width(120.);
-                        params.visit_param_item(param_name, param_idx, &mut ImguiParamVisitor(ui));
+                        let changes = selected_id
+                            .and_then(|id| param_diff::row_changes(&params, param_name, id).ok())
+                            .unwrap_or_default();
+                        let mut visitor = ImguiParamVisitor {
+                            ui,
+                            param: param_name,
+                            changes: &changes,
+                            edited: false,
+                        };
+                        params.visit_param_item_mut(param_name, param_idx, &mut visitor);
+                        if visitor.edited {
+                            self.modified.stale = true;
+                        }
                     });
                 };
             });
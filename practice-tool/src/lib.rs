@@ -30,8 +30,9 @@ use hudhook::hooks::dx12::ImguiDx12Hooks;
 use hudhook::mh::{MH_ApplyQueued, MH_Initialize, MhHook, MH_STATUS};
 use hudhook::tracing::error;
 use hudhook::{eject, Hudhook};
-use libeldenring::codegen::base_addresses::BaseAddresses;
-use libeldenring::version;
+use libeldenring::address_pack::{self, ADDRESS_PACK_FILE_NAME};
+use libeldenring::journal::JOURNAL;
+use libeldenring::{memory, version};
 use once_cell::sync::Lazy;
 use practice_tool::PracticeTool;
 use windows::core::{s, w, GUID, HRESULT, PCWSTR};
@@ -117,23 +118,38 @@ unsafe extern "stdcall" fn xinput_get_state_impl(
     dw_user_index: u32,
     xinput_state: *mut XINPUT_STATE,
 ) -> u32 {
-    let r = (XINPUTGETSTATE)(dw_user_index, xinput_state);
+    let mut r = (XINPUTGETSTATE)(dw_user_index, xinput_state);
+
+    if dw_user_index == 0 && r == 0 {
+        widgets::input_display::record_gamepad(&(*xinput_state).Gamepad);
+    }
 
     if practice_tool::BLOCK_XINPUT.load(Ordering::SeqCst) {
         *xinput_state = Default::default();
     }
 
+    // Playback works without a controller connected.
+    if dw_user_index == 0 && widgets::input_recording::apply_playback(&mut *xinput_state) {
+        r = 0;
+    }
+
     r
 }
 
+/// Records the bytes at `ptr` in the journal before they get patched.
+fn record_patch(ptr: *const c_void, len: usize, label: &str) {
+    JOURNAL.lock().record(&memory::default_source(), ptr as usize, len, Some(label));
+}
+
 unsafe fn apply_no_logo() {
     let module_base = GetModuleHandleW(None).unwrap();
-    let offset = BaseAddresses::from(version::get_version()).func_remove_intro_screens;
+    let offset = version::base_addresses().func_remove_intro_screens;
 
     let ptr = (module_base.0 as usize + offset) as *mut [u8; 2];
     let mut old = PAGE_PROTECTION_FLAGS(0);
     if *ptr == [0x74, 0x53] && VirtualProtect(ptr as _, 2, PAGE_EXECUTE_READWRITE, &mut old).is_ok()
     {
+        record_patch(ptr as _, 2, "No logo");
         (*ptr) = [0x90, 0x90];
         VirtualProtect(ptr as _, 2, old, &mut old).ok();
     }
@@ -142,14 +158,15 @@ unsafe fn apply_no_logo() {
 unsafe fn apply_event_patch() {
     let module_base = GetModuleHandleW(None).unwrap();
 
-    let offset_1 = BaseAddresses::from(version::get_version()).event_patch1;
-    let offset_2 = BaseAddresses::from(version::get_version()).event_patch2;
+    let offset_1 = version::base_addresses().event_patch1;
+    let offset_2 = version::base_addresses().event_patch2;
 
     let ptr_1 = (module_base.0 as usize + offset_1) as *mut [u8; 2];
     let mut old_1 = PAGE_PROTECTION_FLAGS(0);
     if *ptr_1 == [0x32, 0xC0]
         && VirtualProtect(ptr_1 as _, 2, PAGE_EXECUTE_READWRITE, &mut old_1).is_ok()
     {
+        record_patch(ptr_1 as _, 2, "Event patch 1");
         (*ptr_1) = [0xB0, 0x01];
         VirtualProtect(ptr_1 as _, 2, old_1, &mut old_1).ok();
     }
@@ -159,6 +176,7 @@ unsafe fn apply_event_patch() {
     if *ptr_2 == [0x32, 0xC0]
         && VirtualProtect(ptr_2 as _, 2, PAGE_EXECUTE_READWRITE, &mut old_2).is_ok()
     {
+        record_patch(ptr_2 as _, 2, "Event patch 2");
         (*ptr_2) = [0xB0, 0x01];
         VirtualProtect(ptr_2 as _, 2, old_2, &mut old_2).ok();
     }
@@ -166,16 +184,37 @@ unsafe fn apply_event_patch() {
 
 unsafe fn apply_font_patch() {
     let module_base = GetModuleHandleW(None).unwrap();
-    let offset = BaseAddresses::from(version::get_version()).font_patch;
+    let offset = version::base_addresses().font_patch;
 
     let ptr = (module_base.0 as usize + offset) as *mut u8;
     let mut old = PAGE_PROTECTION_FLAGS(0);
     if *ptr == 0x48 && VirtualProtect(ptr as _, 1, PAGE_EXECUTE_READWRITE, &mut old).is_ok() {
+        record_patch(ptr as _, 1, "Font patch");
         (*ptr) = 0xC3;
         VirtualProtect(ptr as _, 1, old, &mut old).ok();
     }
 }
 
+/// Loads the address pack next to the DLL, if there is one, so that base
+/// addresses can be fixed without a new release.
+fn load_address_pack() {
+    let Some(path) = util::get_dll_path().map(|path| path.with_file_name(ADDRESS_PACK_FILE_NAME))
+    else {
+        return;
+    };
+
+    if !path.exists() {
+        return;
+    }
+
+    match address_pack::load(&path) {
+        Ok(pack) => {
+            version::set_address_pack(pack).ok();
+        },
+        Err(e) => error!("{e}"),
+    }
+}
+
 fn start_practice_tool(hmodule: HINSTANCE) {
     let practice_tool = PracticeTool::new();
 
@@ -231,6 +270,8 @@ fn await_rshift() -> bool {
 #[allow(clippy::missing_safety_doc)]
 pub unsafe extern "stdcall" fn DllMain(hmodule: HINSTANCE, reason: u32, _: *mut c_void) -> bool {
     if reason == DLL_PROCESS_ATTACH {
+        load_address_pack();
+
         if version::check_version().is_err() {
             return false;
         }
@@ -240,6 +281,10 @@ pub unsafe extern "stdcall" fn DllMain(hmodule: HINSTANCE, reason: u32, _: *mut
 
         let cloned_hmodule = hmodule.clone();
         thread::spawn(move || {
+            if version::check_address_pack_by_hash().is_err() {
+                return;
+            }
+
             apply_no_logo();
 
             if util::get_dll_path()
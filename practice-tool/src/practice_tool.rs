@@ -10,7 +10,7 @@ use hudhook::{ImguiRenderLoop, RenderContext};
 use imgui::*;
 use imgui::StyleColor;
 use libeldenring::prelude::*;
-use libeldenring::version;
+use libeldenring::{freezer, version};
 use pkg_version::*;
 use practice_tool_core::crossbeam_channel::{self, Receiver, Sender};
 use practice_tool_core::widgets::radial_menu::radial_menu;
@@ -22,10 +22,14 @@ use whoami;
 use windows::Win32::UI::Input::XboxController::{XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B, XINPUT_STATE};
 
 use crate::settings::config::Config;
+use crate::settings::custom_pointer::CustomPointers;
 use crate::settings::indicator::IndicatorType;
+use crate::settings::param_patch;
 use crate::settings::radial_menu::RadialMenu;
 use crate::settings::Settings;
 use crate::update::Update;
+use crate::widgets::frame_advance;
+use crate::widgets::input_display::InputDisplay;
 use crate::{util, XINPUTGETSTATE};
 // The ui textures seem to bug out with greater opacity
 const MAX_OPACITY: f32 = 1.-0.001962;
@@ -36,6 +40,14 @@ const PATCH: usize = pkg_version_patch!();
 
 pub(crate) static BLOCK_XINPUT: AtomicBool = AtomicBool::new(false);
 
+/// Set while a multiplayer session is active. Widgets that write to game
+/// memory, like flags, speed, position or runes, refuse to while this is set.
+pub(crate) static SESSION_LOCKED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn session_locked() -> bool {
+    SESSION_LOCKED.load(Ordering::SeqCst)
+}
+
 struct FontIDs {
     small: FontId,
     normal: FontId,
@@ -54,6 +66,7 @@ enum UiState {
 pub(crate) struct PracticeTool {
     settings: Settings,
     pointers: Pointers,
+    custom_pointers: CustomPointers,
     version_label: String,
     widgets: Vec<Box<dyn Widget>>,
     radial_menu: Vec<RadialMenu>,
@@ -64,7 +77,10 @@ pub(crate) struct PracticeTool {
     ui_state: UiState,
     fonts: Option<FontIDs>,
     config_err: Option<String>,
+    /// Config errors found once the params are loaded.
+    config_err_rx: Receiver<String>,
     update_available: Update,
+    session_guard: SessionGuard,
 
     position_bufs: [String; 4],
     position_prev: [f32; 3],
@@ -75,6 +91,7 @@ pub(crate) struct PracticeTool {
 
     framecount: u32,
     framecount_buf: String,
+    input_display: InputDisplay,
 
     cur_anim_buf: String,
 
@@ -87,6 +104,16 @@ pub(crate) struct PracticeTool {
 
 pub static RUNTIME_CONFIG_FILENAME: OnceLock<Value> = OnceLock::new();
 
+/// The message shown in the tool window for a configuration error.
+fn config_error_message(e: &str) -> String {
+    let runtime_config_filename = RUNTIME_CONFIG_FILENAME
+        .get()
+        .and_then(|config| config.get("config_file_name"))
+        .and_then(Value::as_str)
+        .unwrap_or("er_invasion_tool.toml");
+    format!("Configuration error, please review your {runtime_config_filename} file.\n\n{e}")
+}
+
 impl PracticeTool {
     pub(crate) fn new() -> Self {
         hudhook::alloc_console().ok();
@@ -99,10 +126,6 @@ impl PracticeTool {
         RUNTIME_CONFIG_FILENAME
             .set(invasion_tool_config.clone())
             .expect("Failed to set runtime config filename");
-        let runtime_config_filename = invasion_tool_config
-            .get("config_file_name")
-            .and_then(Value::as_str)
-            .unwrap_or("er_invasion_tool.toml");
 
         fn load_config() -> Result<Config, String> {
             let invasion_tool_config =
@@ -139,10 +162,7 @@ impl PracticeTool {
                 Config::default(),
                 Some({
                     error!("{}", e);
-                    format!(
-                        "Configuration error, please review your {runtime_config_filename} \
-                         file.\n\n{e}"
-                    )
+                    config_error_message(&e)
                 }),
             ),
         };
@@ -196,6 +216,7 @@ impl PracticeTool {
         let (log_tx, log_rx) = crossbeam_channel::unbounded();
 
         let pointers = Pointers::new();
+        freezer::start();
         let poll_interval = Duration::from_millis(100);
         loop {
             if let Some(menu_timer) = pointers.menu_timer.read() {
@@ -205,6 +226,9 @@ impl PracticeTool {
             }
             thread::sleep(poll_interval);
         }
+        let param_patches = config.param_patches.clone();
+        let patch_log_tx = log_tx.clone();
+        let (config_err_tx, config_err_rx) = crossbeam_channel::unbounded();
         wait_for_option_in_thread(
             || unsafe {
                 let mut params = PARAMS.write();
@@ -213,14 +237,26 @@ impl PracticeTool {
                 }
                 params.get_equip_param_goods()
             },
-            |mut epg| {
-                // TODO: Figure out if there are other things that invalidate a
-                // Seamless session
-                // if let Some(spectral_steed_whistle) =
-                // epg.find(|i| i.id == 130).and_then(|p| p.param)
-                // {
-                // spectral_steed_whistle.icon_id = 12;
-                // };
+            move |_| {
+                if param_patches.is_empty() {
+                    return;
+                }
+
+                // Patches write to the params like any other edit.
+                if session_locked() {
+                    let message = "Param patches will be applied after the multiplayer session";
+                    patch_log_tx.send(message.to_string()).ok();
+                    while session_locked() {
+                        thread::sleep(poll_interval);
+                    }
+                }
+
+                let (applied, errors) = param_patch::apply(&param_patches, &PARAMS.write());
+                patch_log_tx.send(format!("Applied {applied} param patches")).ok();
+                for e in errors {
+                    error!("{}", e);
+                    config_err_tx.send(e).ok();
+                }
             },
         );
 
@@ -228,18 +264,29 @@ impl PracticeTool {
             if config.settings.disable_update_prompt { Update::UpToDate } else { Update::check() };
 
         let version_label = {
-            let (maj, min, patch) = version::get_version().into();
-            format!("Game Ver {}.{:02}.{}", maj, min, patch)
+            let (maj, min, patch) =
+                version::exe_version().unwrap_or_else(|| version::get_version().into());
+            match version::address_source() {
+                AddressSource::BuiltIn => format!("Game Ver {}.{:02}.{}", maj, min, patch),
+                AddressSource::Pack => {
+                    format!("Game Ver {}.{:02}.{} (address pack)", maj, min, patch)
+                },
+                AddressSource::Scan => {
+                    format!("Game Ver {}.{:02}.{} (unverified addresses)", maj, min, patch)
+                },
+            }
         };
 
         let settings = config.settings.clone();
         let radial_menu = config.radial_menu.clone();
-        let widgets = config.make_commands(&pointers);
+        let custom_pointers = config.custom_pointers(&pointers);
+        let widgets = config.make_commands(&pointers, &custom_pointers);
         println!("Practice Tool Initialized");
 
         PracticeTool {
             settings,
             pointers,
+            custom_pointers,
             version_label,
             widgets,
             log: Vec::new(),
@@ -248,6 +295,7 @@ impl PracticeTool {
             fonts: None,
             ui_state: UiState::Closed,
             config_err,
+            config_err_rx,
             position_prev: Default::default(),
             position_bufs: Default::default(),
             position_change_buf: Default::default(),
@@ -255,8 +303,10 @@ impl PracticeTool {
             fps_buf: Default::default(),
             framecount: 0,
             framecount_buf: Default::default(),
+            input_display: InputDisplay::new(),
             cur_anim_buf: Default::default(),
             update_available,
+            session_guard: SessionGuard::default(),
             radial_menu,
             gamepad_state: Default::default(),
             gamepad_stick: Default::default(),
@@ -375,7 +425,7 @@ impl PracticeTool {
                         ui.separator();
 
                         for indicator in &mut self.settings.indicators {
-                            let label = match indicator.indicator {
+                            let label = match &indicator.indicator {
                                 IndicatorType::GameVersion => "Game Version",
                                 IndicatorType::Position => "Player Position",
                                 IndicatorType::PositionChange => "Player Velocity",
@@ -383,7 +433,13 @@ impl PracticeTool {
                                 IndicatorType::Igt => "IGT Timer",
                                 IndicatorType::Fps => "FPS",
                                 IndicatorType::FrameCount => "Frame Counter",
+                                IndicatorType::Input => "Input Display",
                                 IndicatorType::ImguiDebug => "ImGui Debug Info",
+                                IndicatorType::Custom(name) => self
+                                    .custom_pointers
+                                    .get(name)
+                                    .map(|value| value.label.as_str())
+                                    .unwrap_or(name),
                             };
 
                             let mut state = indicator.default;
@@ -407,6 +463,8 @@ impl PracticeTool {
 
                                 if ui.button("Reset") {
                                     self.framecount = 0;
+                                    // The history is timestamped with the frame count.
+                                    self.input_display.clear();
                                 }
                             }
                         }
@@ -525,7 +583,7 @@ impl PracticeTool {
                         continue;
                     }
 
-                    match indicator.indicator {
+                    match &indicator.indicator {
                         IndicatorType::GameVersion => {
                             ui.text(&self.version_label);
                         },
@@ -628,11 +686,24 @@ impl PracticeTool {
                         IndicatorType::FrameCount => {
                             self.framecount_buf.clear();
                             write!(self.framecount_buf, "Frame count {0}", self.framecount,).ok();
+                            if frame_advance::game_frozen() {
+                                self.framecount_buf.push_str(" (paused)");
+                            }
                             ui.text(&self.framecount_buf);
                         },
+                        IndicatorType::Input => {
+                            self.input_display.render(ui);
+                        },
                         IndicatorType::ImguiDebug => {
                             imgui_debug(ui);
                         },
+                        IndicatorType::Custom(name) => {
+                            if let Some(value) = self.custom_pointers.get(name) {
+                                if let Some(v) = value.read_string() {
+                                    ui.text(format!("{} {v}", value.label));
+                                }
+                            }
+                        },
                     }
                 }
 
@@ -715,6 +786,97 @@ impl PracticeTool {
         }
     }
 
+    /// Samples the session state and, when a multiplayer session starts,
+    /// turns off every practice flag, action freeze, animation speed and
+    /// frozen value, and locks them until it ends.
+    fn update_session_guard(&mut self) {
+        let Some(state) = self.pointers.session_state() else {
+            return;
+        };
+
+        match self.session_guard.update(state) {
+            Some(GuardEvent::Engaged(state)) => {
+                SESSION_LOCKED.store(true, Ordering::SeqCst);
+
+                let mut flags: Vec<(&str, &Bitflag<u8>, bool)> = self.pointers.practice_flags();
+                flags.extend(
+                    self.custom_pointers
+                        .iter()
+                        .filter_map(|(name, value)| Some((name.as_str(), value.flag()?, false))),
+                );
+
+                let mut disabled = flags
+                    .into_iter()
+                    .filter(|(_, flag, vanilla)| flag.get().is_some_and(|value| value != *vanilla))
+                    .map(|(name, flag, vanilla)| {
+                        flag.set(vanilla);
+                        name
+                    })
+                    .collect::<Vec<_>>();
+
+                let action_force = &self.pointers.func_dbg_action_force;
+                let (state_off, state_on) = self.pointers.func_dbg_action_force_state_values;
+                if action_force.read() == Some(state_on) {
+                    action_force.write(state_off);
+                    disabled.push("action_freeze");
+                }
+
+                let speeds =
+                    [&self.pointers.animation_speed, &self.pointers.torrent_animation_speed];
+                if speeds.iter().any(|ptr| ptr.read().is_some_and(|speed| speed != 1.0)) {
+                    for ptr in speeds {
+                        ptr.write(1.0);
+                    }
+                    disabled.push("cycle_speed");
+                }
+
+                let unfrozen = FREEZER.clear();
+
+                self.log_tx.send(format!("Multiplayer session ({state}): flags locked")).ok();
+                if !disabled.is_empty() {
+                    self.log_tx.send(format!("Disabled {}", disabled.join(", "))).ok();
+                }
+                if !unfrozen.is_empty() {
+                    self.log_tx.send(format!("Unfroze {}", unfrozen.join(", "))).ok();
+                }
+            },
+            Some(GuardEvent::Released) => {
+                SESSION_LOCKED.store(false, Ordering::SeqCst);
+                self.log_tx.send("Session ended: flags unlocked".to_string()).ok();
+            },
+            None => {},
+        }
+    }
+
+    fn render_session_banner(&mut self, ui: &imgui::Ui) {
+        if !self.session_guard.is_engaged() {
+            return;
+        }
+
+        let [dw, _] = ui.io().display_size;
+
+        ui.window("##session_banner")
+            .position_pivot([0.5, 0.])
+            .position([dw * 0.5, 10.], Condition::Always)
+            .bg_alpha(0.8)
+            .flags({
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SCROLLBAR
+                    | WindowFlags::ALWAYS_AUTO_RESIZE
+                    | WindowFlags::NO_INPUTS
+            })
+            .build(|| {
+                let style = ui.push_style_color(StyleColor::Text, [1., 0.35, 0.25, 1.]);
+                ui.text(format!(
+                    "Multiplayer session active ({}): practice flags are locked",
+                    self.session_guard.state()
+                ));
+                style.end();
+            });
+    }
+
     fn render_logs(&mut self, ui: &imgui::Ui) {
         let io = ui.io();
 
@@ -801,7 +963,19 @@ impl ImguiRenderLoop for PracticeTool {
         let display = self.settings.display.is_pressed(ui);
         let hide = self.settings.hide.map(|k| k.is_pressed(ui)).unwrap_or(false);
 
-        self.framecount += 1;
+        // Frames the game spent paused don't count.
+        if !frame_advance::game_frozen() {
+            self.framecount += 1;
+        }
+
+        // While the input indicator is on, its history is recorded every frame,
+        // whether the menu is open, closed or hidden.
+        let input_shown = self.settings.indicators.iter().any(|indicator| {
+            indicator.default && matches!(indicator.indicator, IndicatorType::Input)
+        });
+        if input_shown {
+            self.input_display.record(ui, self.framecount as u64);
+        }
 
         if !ui.io().want_capture_keyboard && (display || hide) {
             self.ui_state = match (&self.ui_state, hide) {
@@ -818,6 +992,8 @@ impl ImguiRenderLoop for PracticeTool {
             }
         }
 
+        self.update_session_guard();
+
         self.render_radial(ui);
 
         match &self.ui_state {
@@ -837,10 +1013,18 @@ impl ImguiRenderLoop for PracticeTool {
             w.log(self.log_tx.clone());
         }
 
+        for e in self.config_err_rx.try_iter() {
+            match &mut self.config_err {
+                Some(config_err) => write!(config_err, "\n{e}").unwrap(),
+                None => self.config_err = Some(config_error_message(&e)),
+            }
+        }
+
         let now = Instant::now();
         self.log.extend(self.log_rx.try_iter().inspect(|log| info!("{}", log)).map(|l| (now, l)));
         self.log.retain(|(tm, _)| tm.elapsed() < std::time::Duration::from_secs(5));
 
+        self.render_session_banner(ui);
         self.render_logs(ui);
         drop(font_token);
         text_color.pop();
@@ -7,23 +7,32 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::Widget;
 use serde::Deserialize;
 
+use super::custom_pointer::CustomPointers;
 use super::flag_spec::FlagSpec;
 use super::multi_flag_spec::MultiFlagSpec;
 use super::Settings;
 use crate::widgets::action_freeze::action_freeze;
+use crate::widgets::attack_rating::AttackRating;
 use crate::widgets::character_stats::character_stats_edit;
 use crate::widgets::cycle_color::cycle_color;
 use crate::widgets::cycle_speed::cycle_speed;
 use crate::widgets::deathcam::deathcam;
 use crate::widgets::flag::flag_widget;
+use crate::widgets::frame_advance::FrameAdvance;
+use crate::widgets::frame_data::FrameData;
+use crate::widgets::freeze::{freeze, FreezeValue};
 use crate::widgets::group::group;
+use crate::widgets::input_recording::InputRecording;
 use crate::widgets::item_spawn::ItemSpawner;
 use crate::widgets::label::label_widget;
+use crate::widgets::legality::Legality;
+use crate::widgets::matchmaking::Matchmaking;
 use crate::widgets::multiflag::multi_flag;
 use crate::widgets::none::NoneWidget;
 use crate::widgets::nudge_pos::nudge_position;
 use crate::widgets::position::save_position;
 use crate::widgets::quitout::quitout;
+use crate::widgets::restore_vanilla::restore_vanilla;
 use crate::widgets::runes::runes;
 use crate::widgets::savefile_manager::savefile_manager;
 use crate::widgets::target::Target;
@@ -43,18 +52,20 @@ pub enum CfgCommand {
         hotkey_load: PlaceholderOption<Key>,
         feature: Feature,
     },
-    Flag {
-        flag: FlagSpec,
-        hotkey: Option<Key>,
-        feature: Feature,
-    },
     MultiFlag {
         flag: MultiFlagSpec,
         hotkey: Option<Key>,
         feature: Feature,
     },
     SpecialFlag {
-        flag: String,
+        flag: SpecialFlagSpec,
+        hotkey: Option<Key>,
+        feature: Feature,
+    },
+    // Any other flag name is taken as a custom pointer, so this goes after the
+    // variants with a `flag` field, or it would shadow `MultiFlag` and `SpecialFlag`.
+    Flag {
+        flag: FlagSpec,
         hotkey: Option<Key>,
         feature: Feature,
     },
@@ -113,6 +124,44 @@ pub enum CfgCommand {
         _warp: bool,
         feature: Feature,
     },
+    AttackRating {
+        #[serde(rename = "attack_rating")]
+        _attack_rating: bool,
+        feature: Feature,
+    },
+    Matchmaking {
+        #[serde(rename = "matchmaking")]
+        _matchmaking: bool,
+        feature: Feature,
+    },
+    Legality {
+        #[serde(rename = "legality")]
+        _legality: bool,
+        feature: Feature,
+    },
+    FrameAdvance {
+        #[serde(rename = "frame_advance")]
+        hotkey_pause: PlaceholderOption<Key>,
+        step: Option<Key>,
+        step_many: Option<Key>,
+        #[serde(default = "default_step_frames")]
+        frames: u32,
+        feature: Feature,
+    },
+    FrameData {
+        #[serde(rename = "frame_data")]
+        _frame_data: bool,
+        feature: Feature,
+    },
+    InputRecording {
+        #[serde(rename = "input_recording")]
+        name: String,
+        record: Option<Key>,
+        play: Option<Key>,
+        #[serde(default)]
+        looping: bool,
+        feature: Feature,
+    },
     Group {
         #[serde(rename = "group")]
         label: String,
@@ -123,17 +172,100 @@ pub enum CfgCommand {
         #[serde(rename = "quitout")]
         hotkey: PlaceholderOption<Key>,
     },
+    RestoreVanilla {
+        #[serde(rename = "restore_vanilla")]
+        hotkey: PlaceholderOption<Key>,
+    },
+    Freeze {
+        freeze: FreezeSpec,
+        value: Option<f32>,
+        hotkey: Option<Key>,
+        feature: Feature,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecialFlagSpec {
+    Deathcam,
+    ActionFreeze,
+}
+
+/// Values that can be locked with the freezer. HP, FP and stamina are locked
+/// at their maximum, everything else at the value it had when frozen, unless
+/// a `value` is given.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeSpec {
+    Hp,
+    Fp,
+    Stamina,
+    Runes,
+    AnimationSpeed,
 }
 
 impl CfgCommand {
-    pub fn into_widget(self, settings: &Settings, chains: &Pointers) -> Option<Box<dyn Widget>> {
+    /// Collects the custom pointers used as flags by this command and the
+    /// commands it contains, with their config path.
+    pub fn custom_flags<'a>(&'a self, path: &str, out: &mut Vec<(String, &'a str)>) {
+        match self {
+            CfgCommand::Flag { flag, .. } => {
+                if let Some(name) = flag.custom_name() {
+                    out.push((format!("{path}.flag"), name));
+                }
+            },
+            CfgCommand::MultiFlagUser { flags, .. } => {
+                for (i, flag) in flags.iter().enumerate() {
+                    if let Some(name) = flag.custom_name() {
+                        out.push((format!("{path}.flags[{i}]"), name));
+                    }
+                }
+            },
+            CfgCommand::Group { commands, .. } => {
+                for (i, command) in commands.iter().enumerate() {
+                    command.custom_flags(&format!("{path}.commands[{i}]"), out);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Collects the visible `frame_data` and `target` commands, which the
+    /// config check pairs up, by name with their config path.
+    pub fn visible_widgets(&self, path: &str, out: &mut Vec<(String, &'static str)>) {
+        match self {
+            CfgCommand::FrameData { feature, .. } if feature.visible => {
+                out.push((path.to_string(), "frame_data"));
+            },
+            CfgCommand::Target { feature, .. } if feature.visible => {
+                out.push((path.to_string(), "target"));
+            },
+            CfgCommand::Group { commands, feature, .. } if feature.visible => {
+                for (i, command) in commands.iter().enumerate() {
+                    command.visible_widgets(&format!("{path}.commands[{i}]"), out);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    pub fn into_widget(
+        self,
+        settings: &Settings,
+        chains: &Pointers,
+        custom: &CustomPointers,
+    ) -> Option<Box<dyn Widget>> {
         let mut exit = false;
         let widget = match self {
             CfgCommand::Flag { flag, hotkey, feature } => {
                 if !feature.visible {
                     return Some(Box::new(NoneWidget {}));
                 }
-                flag_widget(&flag.label, (flag.getter)(chains).clone(), hotkey)
+                let Some((label, flag)) = flag.resolve(chains, custom) else {
+                    error!("Invalid flag {:?}", flag);
+                    return None;
+                };
+                flag_widget(&label, flag, hotkey)
             },
             CfgCommand::MultiFlag { flag, hotkey, feature } => {
                 if !feature.visible {
@@ -151,13 +283,15 @@ impl CfgCommand {
                 }
                 multi_flag(
                     label.as_str(),
-                    flags.iter().map(|flag| (flag.getter)(chains).clone()).collect(),
+                    flags
+                        .iter()
+                        .filter_map(|flag| flag.resolve(chains, custom))
+                        .map(|(_, flag)| flag)
+                        .collect(),
                     hotkey,
                 )
             },
-            CfgCommand::SpecialFlag { flag: special_flag, hotkey, feature }
-                if special_flag == "deathcam" =>
-            {
+            CfgCommand::SpecialFlag { flag: SpecialFlagSpec::Deathcam, hotkey, feature } => {
                 if !feature.visible {
                     return Some(Box::new(NoneWidget {}));
                 }
@@ -168,9 +302,7 @@ impl CfgCommand {
                     hotkey,
                 )
             },
-            CfgCommand::SpecialFlag { flag: special_flag, hotkey, feature }
-                if special_flag == "action_freeze" =>
-            {
+            CfgCommand::SpecialFlag { flag: SpecialFlagSpec::ActionFreeze, hotkey, feature } => {
                 if !feature.visible {
                     return Some(Box::new(NoneWidget {}));
                 }
@@ -180,10 +312,6 @@ impl CfgCommand {
                     hotkey,
                 )
             },
-            CfgCommand::SpecialFlag { flag: special_flag, hotkey: _, feature: _ } => {
-                error!("Invalid flag {}", special_flag);
-                return None;
-            },
             CfgCommand::Label { label, feature: _ } => label_widget(label.as_str()),
             CfgCommand::SavefileManager { hotkey_load, feature } => {
                 if !feature.visible {
@@ -274,6 +402,68 @@ impl CfgCommand {
                     settings.display,
                 ))
             },
+            CfgCommand::AttackRating { feature, .. } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(AttackRating::new(
+                    chains.character_stats.clone(),
+                    chains.chr_asm.clone(),
+                    settings.display,
+                ))
+            },
+            CfgCommand::Matchmaking { feature, .. } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(Matchmaking::new(
+                    chains.character_stats.clone(),
+                    chains.matchmaking_weapon_level.clone(),
+                    settings.display,
+                ))
+            },
+            CfgCommand::Legality { feature, .. } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(Legality::new(
+                    chains.character_stats.clone(),
+                    chains.character_blessings.clone(),
+                    chains.matchmaking_weapon_level.clone(),
+                    chains.chr_asm.clone(),
+                    chains.inventory.clone(),
+                    settings.display,
+                ))
+            },
+            CfgCommand::FrameAdvance { hotkey_pause, step, step_many, frames, feature } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(FrameAdvance::new(
+                    chains.game_speed.clone(),
+                    frames,
+                    hotkey_pause.into_option(),
+                    step,
+                    step_many,
+                ))
+            },
+            CfgCommand::FrameData { feature, .. } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(FrameData::new(
+                    chains.cur_anim.clone(),
+                    chains.cur_anim_time.clone(),
+                    chains.cur_anim_length.clone(),
+                    settings.display,
+                ))
+            },
+            CfgCommand::InputRecording { name, record, play, looping, feature } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}))
+                }
+                Box::new(InputRecording::new(name, looping, record, play))
+            },
             CfgCommand::Target { hotkey, feature } => {
                 if !feature.visible {
                     return Some(Box::new(NoneWidget {}))
@@ -285,13 +475,73 @@ impl CfgCommand {
                 ))
             },
             CfgCommand::Quitout { hotkey } => quitout(chains.quitout.clone(), hotkey.into_option()),
+            CfgCommand::RestoreVanilla { hotkey } => restore_vanilla(hotkey.into_option()),
+            CfgCommand::Freeze { freeze: spec, value, hotkey, feature } => {
+                if !feature.visible {
+                    return Some(Box::new(NoneWidget {}));
+                }
+
+                let label = match spec {
+                    FreezeSpec::Hp => "Lock HP",
+                    FreezeSpec::Fp => "Lock FP",
+                    FreezeSpec::Stamina => "Lock stamina",
+                    FreezeSpec::Runes => "Lock runes",
+                    FreezeSpec::AnimationSpeed => "Lock animation speed",
+                };
+                let label = match value {
+                    Some(value) => format!("{label} ({value})"),
+                    None => label.to_string(),
+                };
+
+                match spec {
+                    FreezeSpec::Hp => freeze(
+                        &label,
+                        chains.hp.clone(),
+                        value.map_or(FreezeValue::Source(chains.max_hp.clone()), |v| {
+                            FreezeValue::Fixed(v as i32)
+                        }),
+                        hotkey,
+                    ),
+                    FreezeSpec::Fp => freeze(
+                        &label,
+                        chains.fp.clone(),
+                        value.map_or(FreezeValue::Source(chains.max_fp.clone()), |v| {
+                            FreezeValue::Fixed(v as i32)
+                        }),
+                        hotkey,
+                    ),
+                    FreezeSpec::Stamina => freeze(
+                        &label,
+                        chains.stamina.clone(),
+                        value.map_or(FreezeValue::Source(chains.max_stamina.clone()), |v| {
+                            FreezeValue::Fixed(v as i32)
+                        }),
+                        hotkey,
+                    ),
+                    FreezeSpec::Runes => freeze(
+                        &label,
+                        chains.runes.clone(),
+                        value.map_or(FreezeValue::Current, |v| FreezeValue::Fixed(v as u32)),
+                        hotkey,
+                    ),
+                    FreezeSpec::AnimationSpeed => freeze(
+                        &label,
+                        chains.animation_speed.clone(),
+                        value.map_or(FreezeValue::Current, FreezeValue::Fixed),
+                        hotkey,
+                    ),
+                }
+            },
             CfgCommand::Group { label, commands , feature} => {
                 if !feature.visible {
                     return Some(Box::new(NoneWidget {}))
                 }
                 group(
                     label.as_str(),
-                    commands.into_iter().filter_map(|c| c.into_widget(settings, chains)).collect(),
+                    commands
+                        .into_iter()
+                        .filter_map(|c| c.into_widget(settings, chains, custom))
+                        .collect(),
                     settings.display,
                 )
             },
@@ -317,3 +567,7 @@ impl<T> PlaceholderOption<T> {
         }
     }
 }
+
+fn default_step_frames() -> u32 {
+    10
+}
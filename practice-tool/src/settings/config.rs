@@ -1,10 +1,16 @@
-use super::cfg_command::CfgCommand;
-use super::radial_menu::RadialMenu;
-use super::Settings;
+use std::collections::BTreeMap;
+
 use libeldenring::prelude::*;
 use practice_tool_core::widgets::Widget;
 use serde::Deserialize;
 
+use super::cfg_command::CfgCommand;
+use super::custom_pointer::{CustomPointer, CustomPointers};
+use super::indicator::IndicatorType;
+use super::param_patch::ParamPatch;
+use super::radial_menu::RadialMenu;
+use super::Settings;
+
 #[cfg_attr(test, derive(Debug))]
 #[derive(Deserialize)]
 pub(crate) struct Config {
@@ -12,22 +18,108 @@ pub(crate) struct Config {
     #[serde(rename = "radial-menu")]
     pub(crate) radial_menu: Vec<RadialMenu>,
     commands: Vec<CfgCommand>,
+    #[serde(default)]
+    pointers: BTreeMap<String, CustomPointer>,
+    #[serde(default)]
+    pub(crate) param_patches: Vec<ParamPatch>,
 }
 
 impl Config {
     pub(crate) fn parse(cfg: &str) -> Result<Self, String> {
         let de = &mut toml::de::Deserializer::new(cfg);
-        serde_path_to_error::deserialize(de)
-            .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))
+        let config: Config = serde_path_to_error::deserialize(de)
+            .map_err(|e| format!("TOML config error at {}: {}", e.path(), e.inner()))?;
+        config.check_custom_pointers()?;
+        config.check_param_patches()?;
+        config.check_frame_data()?;
+        Ok(config)
+    }
+
+    /// Checks that every custom pointer used by a command or indicator is
+    /// declared, and that those used as flags have a bitmask.
+    fn check_custom_pointers(&self) -> Result<(), String> {
+        let mut flags = Vec::new();
+        for (i, command) in self.commands.iter().enumerate() {
+            command.custom_flags(&format!("commands[{i}]"), &mut flags);
+        }
+
+        for (path, name) in flags {
+            match self.pointers.get(name) {
+                None => {
+                    return Err(format!(
+                        "TOML config error at {path}: \"{name}\" is not a valid flag specifier"
+                    ))
+                },
+                Some(pointer) if !pointer.is_flag() => {
+                    return Err(format!(
+                        "TOML config error at {path}: pointer \"{name}\" has no bitmask"
+                    ))
+                },
+                Some(_) => {},
+            }
+        }
+
+        for (i, indicator) in self.settings.indicators.iter().enumerate() {
+            if let IndicatorType::Custom(name) = &indicator.indicator {
+                if !self.pointers.contains_key(name) {
+                    return Err(format!(
+                        "TOML config error at settings.indicators[{i}]: Unrecognized indicator: \
+                         {name}"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_param_patches(&self) -> Result<(), String> {
+        for (i, patch) in self.param_patches.iter().enumerate() {
+            patch
+                .check_param()
+                .map_err(|e| format!("TOML config error at param_patches[{i}].param: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a visible `frame_data` command comes with a visible
+    /// `target` command, whose hook is the only way to find the locked-on
+    /// target it records.
+    fn check_frame_data(&self) -> Result<(), String> {
+        let mut widgets = Vec::new();
+        for (i, command) in self.commands.iter().enumerate() {
+            command.visible_widgets(&format!("commands[{i}]"), &mut widgets);
+        }
+
+        if widgets.iter().any(|(_, name)| *name == "target") {
+            return Ok(());
+        }
+        match widgets.iter().find(|(_, name)| *name == "frame_data") {
+            Some((path, _)) => Err(format!(
+                "TOML config error at {path}: frame_data needs a visible target command to record \
+                 the locked-on target"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves the custom pointers against the running game.
+    pub(crate) fn custom_pointers(&self, chains: &Pointers) -> CustomPointers {
+        self.pointers
+            .iter()
+            .map(|(name, pointer)| (name.clone(), pointer.resolve(name, chains)))
+            .collect()
     }
 
     pub(crate) fn make_commands(
         self,
         chains: &Pointers,
+        custom: &CustomPointers,
     ) -> Vec<Box<dyn Widget>> {
         self.commands
             .into_iter()
-            .filter_map(|c| c.into_widget(&self.settings, chains))
+            .filter_map(|c| c.into_widget(&self.settings, chains, custom))
             .collect()
     }
 }
@@ -38,6 +130,8 @@ impl Default for Config {
             settings: Settings::default(),
             radial_menu: Vec::new(),
             commands: Vec::new(),
+            pointers: BTreeMap::new(),
+            param_patches: Vec::new(),
         }
     }
 }
@@ -55,6 +149,152 @@ mod tests {
         println!("{:?}", Config::parse(include_str!("../../../er_invasion_tool.toml")));
     }
 
+    #[test]
+    fn test_parse_custom_pointers() {
+        let config = Config::parse(
+            r#"commands = [
+                  { flag = "no_dead", feature = { visible = true, default = false } },
+                  { flag = "my_flag", feature = { visible = true, default = false } },
+                  { group = "Custom", commands = [
+                    { flags = ["my_flag", "one_shot"], label = "Both", feature = { visible = true, default = false } },
+                  ], feature = { visible = true, default = false } },
+                ]
+                radial-menu = []
+
+                [pointers.my_flag]
+                base = "chr_dbg_flags"
+                offsets = [0x1]
+                type = "u8"
+                bitmask = 0x1
+
+                [pointers.my_value]
+                base = "world_chr_man"
+                offsets = [0x1e508, 0x6c0]
+                type = "f32"
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                indicators = [{ indicator = "my_value", default = true, visible = true }]
+                "#,
+        );
+        assert!(config.is_ok(), "{config:?}");
+
+        let err = Config::parse(
+            r#"commands = [
+                  { group = "Custom", commands = [
+                    { flags = ["one_shot", "my_value"], label = "Both", feature = { visible = true, default = false } },
+                  ], feature = { visible = true, default = false } },
+                ]
+                radial-menu = []
+
+                [pointers.my_value]
+                base = "world_chr_man"
+                type = "f32"
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("commands[0].commands[0].flags[1]"), "{err}");
+
+        let err = Config::parse(
+            r#"commands = []
+                radial-menu = []
+
+                [pointers.my_value]
+                base = "NotABaseAddress"
+                type = "f32"
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("pointers.my_value.base"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_param_patches() {
+        let config = Config::parse(
+            r#"commands = []
+                radial-menu = []
+
+                [[param_patches]]
+                param = "EquipParamGoods"
+                row = 130
+                field = "icon_id"
+                value = 12
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        );
+        assert_eq!(config.map(|c| c.param_patches.len()), Ok(1));
+
+        let err = Config::parse(
+            r#"commands = []
+                radial-menu = []
+
+                [[param_patches]]
+                param = "EquipParamGoods"
+                row = 130
+                field = "icon_id"
+                value = 12
+
+                [[param_patches]]
+                param = "EquipParamGood"
+                row = 130
+                field = "icon_id"
+                value = 12
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("param_patches[1].param"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_frame_data() {
+        let config = Config::parse(
+            r#"commands = [
+                  { frame_data = true, feature = { visible = true, default = false } },
+                  { group = "Target", commands = [
+                    { target = "ctrl+n", feature = { visible = true, default = false } },
+                  ], feature = { visible = true, default = false } },
+                ]
+                radial-menu = []
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        );
+        assert!(config.is_ok(), "{config:?}");
+
+        let err = Config::parse(
+            r#"commands = [
+                  { frame_data = true, feature = { visible = true, default = false } },
+                  { target = "ctrl+n", feature = { visible = false, default = false } },
+                ]
+                radial-menu = []
+
+                [settings]
+                log_level = "DEBUG"
+                display = "f2"
+                "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("commands[0]"), "{err}");
+    }
+
     #[test]
     fn test_parse_errors() {
         println!(
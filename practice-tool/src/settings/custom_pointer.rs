@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use libeldenring::aob::{base_address, BASE_ADDRESS_FIELDS};
+use libeldenring::prelude::*;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Custom pointers, resolved against the running game and keyed by the name
+/// they were declared with.
+pub(crate) type CustomPointers = BTreeMap<String, CustomValue>;
+
+/// A pointer declared in the `[pointers]` section of the config. It is
+/// evaluated like `pointer_chain!(base, offsets...)`.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(try_from = "CustomPointerConfig")]
+pub(crate) struct CustomPointer {
+    label: Option<String>,
+    base: BaseAddressName,
+    offsets: Vec<usize>,
+    kind: CustomKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CustomKind {
+    Flag(u8),
+    U8,
+    U32,
+    F32,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ValueType {
+    U8,
+    U32,
+    F32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BaseAddressName(&'static str);
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomPointerConfig {
+    label: Option<String>,
+    base: BaseAddressName,
+    #[serde(default)]
+    offsets: Vec<usize>,
+    #[serde(rename = "type")]
+    value_type: ValueType,
+    bitmask: Option<u8>,
+}
+
+impl TryFrom<String> for BaseAddressName {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        BASE_ADDRESS_FIELDS
+            .iter()
+            .find(|field| **field == value)
+            .map(|field| BaseAddressName(field))
+            .ok_or_else(|| format!("\"{value}\" is not a valid base address"))
+    }
+}
+
+impl<'de> Deserialize<'de> for BaseAddressName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BaseAddressName::try_from(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+impl TryFrom<CustomPointerConfig> for CustomPointer {
+    type Error = String;
+
+    fn try_from(config: CustomPointerConfig) -> Result<Self, Self::Error> {
+        let kind = match (config.value_type, config.bitmask) {
+            (ValueType::U8, Some(0)) => return Err("bitmask can't be 0".to_string()),
+            (ValueType::U8, Some(mask)) => CustomKind::Flag(mask),
+            (ValueType::U8, None) => CustomKind::U8,
+            (ValueType::U32, None) => CustomKind::U32,
+            (ValueType::F32, None) => CustomKind::F32,
+            (_, Some(_)) => return Err("bitmask is only supported for type \"u8\"".to_string()),
+        };
+
+        Ok(CustomPointer { label: config.label, base: config.base, offsets: config.offsets, kind })
+    }
+}
+
+impl CustomPointer {
+    /// Whether the pointer can be used as a flag, i.e. it has a bitmask.
+    pub(crate) fn is_flag(&self) -> bool {
+        matches!(self.kind, CustomKind::Flag(_))
+    }
+
+    pub(crate) fn resolve(&self, name: &str, chains: &Pointers) -> CustomValue {
+        let base = base_address(&chains.base_addresses, self.base.0)
+            .expect("Base address names are validated on parse");
+        let chain = std::iter::once(base).chain(self.offsets.iter().copied()).collect::<Vec<_>>();
+
+        let value = match self.kind {
+            CustomKind::Flag(mask) => {
+                CustomPointerValue::Flag(Bitflag::new(PointerChain::new(&chain), mask))
+            },
+            CustomKind::U8 => CustomPointerValue::U8(PointerChain::new(&chain)),
+            CustomKind::U32 => CustomPointerValue::U32(PointerChain::new(&chain)),
+            CustomKind::F32 => CustomPointerValue::F32(PointerChain::new(&chain)),
+        };
+
+        CustomValue { label: self.label.clone().unwrap_or_else(|| name.to_string()), value }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CustomValue {
+    pub(crate) label: String,
+    value: CustomPointerValue,
+}
+
+#[derive(Debug, Clone)]
+enum CustomPointerValue {
+    Flag(Bitflag<u8>),
+    U8(PointerChain<u8>),
+    U32(PointerChain<u32>),
+    F32(PointerChain<f32>),
+}
+
+impl CustomValue {
+    pub(crate) fn flag(&self) -> Option<&Bitflag<u8>> {
+        match &self.value {
+            CustomPointerValue::Flag(flag) => Some(flag),
+            _ => None,
+        }
+    }
+
+    /// Reads the current value, formatted for display in an indicator.
+    pub(crate) fn read_string(&self) -> Option<String> {
+        match &self.value {
+            CustomPointerValue::Flag(flag) => {
+                flag.get().map(|v| if v { "on" } else { "off" }.into())
+            },
+            CustomPointerValue::U8(chain) => chain.read().map(|v| v.to_string()),
+            CustomPointerValue::U32(chain) => chain.read().map(|v| v.to_string()),
+            CustomPointerValue::F32(chain) => chain.read().map(|v| format!("{v:.3}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::CustomPointer;
+
+    fn parse(toml: &str) -> Result<BTreeMap<String, CustomPointer>, String> {
+        let de = &mut toml::de::Deserializer::new(toml);
+        serde_path_to_error::deserialize(de).map_err(|e| format!("{}: {}", e.path(), e.inner()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let pointers = parse(
+            r#"
+            [no_dead_alt]
+            label = "No death (alt)"
+            base = "chr_dbg_flags"
+            offsets = [0x1]
+            type = "u8"
+            bitmask = 0x1
+
+            [anim_speed]
+            base = "world_chr_man"
+            offsets = [0x1e508, 0x190, 0x28, 0x17c8]
+            type = "f32"
+            "#,
+        )
+        .unwrap();
+
+        assert!(pointers["no_dead_alt"].is_flag());
+        assert!(!pointers["anim_speed"].is_flag());
+        assert_eq!(pointers["anim_speed"].offsets.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse(
+            r#"
+            [bad]
+            base = "WorldChrMan"
+            type = "u8"
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("bad.base: "), "{err}");
+
+        assert!(parse("[bad]\nbase = \"world_chr_man\"\ntype = \"f32\"\nbitmask = 1").is_err());
+        assert!(parse("[bad]\nbase = \"world_chr_man\"\ntype = \"i64\"").is_err());
+    }
+}
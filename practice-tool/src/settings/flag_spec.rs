@@ -1,22 +1,51 @@
 use libeldenring::prelude::*;
 use serde::Deserialize;
 
+use super::custom_pointer::CustomPointers;
+
+/// A built-in flag, or the name of a custom pointer declared in the config.
 #[derive(Deserialize, Clone)]
 #[serde(try_from = "String")]
-pub struct FlagSpec {
-    pub label: String,
-    pub getter: fn(&Pointers) -> &Bitflag<u8>,
+pub enum FlagSpec {
+    Builtin { label: String, getter: fn(&Pointers) -> &Bitflag<u8> },
+    Custom(String),
 }
 
 impl std::fmt::Debug for FlagSpec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "FlagSpec {{ label: {:?} }}", self.label)
+        match self {
+            FlagSpec::Builtin { label, .. } => write!(f, "FlagSpec {{ label: {label:?} }}"),
+            FlagSpec::Custom(name) => write!(f, "FlagSpec {{ custom: {name:?} }}"),
+        }
     }
 }
 
 impl FlagSpec {
     fn new(label: &str, getter: fn(&Pointers) -> &Bitflag<u8>) -> FlagSpec {
-        FlagSpec { label: label.to_string(), getter }
+        FlagSpec::Builtin { label: label.to_string(), getter }
+    }
+
+    pub fn custom_name(&self) -> Option<&str> {
+        match self {
+            FlagSpec::Builtin { .. } => None,
+            FlagSpec::Custom(name) => Some(name),
+        }
+    }
+
+    /// The flag's label and pointer. Returns `None` for custom pointers that
+    /// aren't declared or have no bitmask.
+    pub fn resolve(
+        &self,
+        chains: &Pointers,
+        custom: &CustomPointers,
+    ) -> Option<(String, Bitflag<u8>)> {
+        match self {
+            FlagSpec::Builtin { label, getter } => Some((label.clone(), getter(chains).clone())),
+            FlagSpec::Custom(name) => {
+                let value = custom.get(name)?;
+                Some((value.label.clone(), value.flag()?.clone()))
+            },
+        }
     }
 }
 
@@ -28,7 +57,7 @@ impl TryFrom<String> for FlagSpec {
           ($x:expr, [ $( ($flag_name:ident, $flag_label:expr), )* ]) => {
               match $x {
                   $(stringify!($flag_name) => Ok(FlagSpec::new($flag_label, |c| &c.$flag_name)),)*
-                  e => Err(format!("\"{}\" is not a valid flag specifier", e)),
+                  e => Ok(FlagSpec::Custom(e.to_string())),
               }
           }
       }
@@ -18,6 +18,9 @@ pub(crate) enum IndicatorType {
     Fps,
     FrameCount,
     Animation,
+    Input,
+    /// A custom pointer declared in the config.
+    Custom(String),
 }
 
 impl Indicator {
@@ -30,6 +33,7 @@ impl Indicator {
             Indicator { indicator: IndicatorType::Animation, default: false, visible: false },
             Indicator { indicator: IndicatorType::Fps, default: false, visible: false },
             Indicator { indicator: IndicatorType::FrameCount, default: false, visible: true },
+            Indicator { indicator: IndicatorType::Input, default: true, visible: true },
             Indicator { indicator: IndicatorType::ImguiDebug, default: false, visible: false },
         ]
     }
@@ -82,12 +86,21 @@ impl TryFrom<IndicatorConfig> for Indicator {
                 default: indicator.default,
                 visible: indicator.visible,
             }),
+            "input" => Ok(Indicator {
+                indicator: IndicatorType::Input,
+                default: indicator.default,
+                visible: indicator.visible,
+            }),
             "imgui_debug" => Ok(Indicator {
                 indicator: IndicatorType::ImguiDebug,
                 default: indicator.default,
                 visible: indicator.visible,
             }),
-            value => Err(format!("Unrecognized indicator: {value}")),
+            value => Ok(Indicator {
+                indicator: IndicatorType::Custom(value.to_string()),
+                default: indicator.default,
+                visible: indicator.visible,
+            }),
         }
     }
 }
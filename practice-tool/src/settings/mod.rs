@@ -1,9 +1,11 @@
 mod cfg_command;
+pub mod custom_pointer;
 mod flag_spec;
 pub mod indicator;
 mod level_filter_serde;
 mod multi_flag_spec;
 pub mod config;
+pub mod param_patch;
 pub mod radial_menu;
 use cfg_command::feature::Feature;
 use practice_tool_core::controller::ControllerCombination;
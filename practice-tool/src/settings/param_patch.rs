@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use libeldenring::param_io::{self, ParamsDump, RowDump, Value};
+use libeldenring::prelude::*;
+use serde::Deserialize;
+
+/// A param field set to a fixed value once the params are loaded, declared
+/// in the `[[param_patches]]` section of the config.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ParamPatch {
+    param: String,
+    row: u64,
+    field: String,
+    value: Value,
+}
+
+impl ParamPatch {
+    /// Params can be checked before the game loads them, rows and fields
+    /// only once they are loaded.
+    pub(crate) fn check_param(&self) -> Result<(), String> {
+        if PARAM_VTABLE.contains_key(&self.param) {
+            Ok(())
+        } else {
+            Err(format!("\"{}\" is not a valid param", self.param))
+        }
+    }
+}
+
+/// The values set by `patches`. Later patches override earlier ones.
+fn to_dump<'a>(patches: impl IntoIterator<Item = &'a ParamPatch>) -> ParamsDump {
+    let mut dump = ParamsDump::new();
+    for patch in patches {
+        dump.entry(patch.param.clone())
+            .or_default()
+            .entry(patch.row)
+            .or_insert_with(|| RowDump { name: None, fields: BTreeMap::new() })
+            .fields
+            .insert(patch.field.clone(), patch.value);
+    }
+    dump
+}
+
+/// Applies every patch that matches a row and field of the loaded params,
+/// with a single import. Returns the number of patches applied, and a config
+/// error for each of the others.
+pub(crate) fn apply(patches: &[ParamPatch], params: &Params) -> (usize, Vec<String>) {
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, patch) in patches.iter().enumerate() {
+        let patch_errors = param_io::check(params, &to_dump([patch]));
+        if patch_errors.is_empty() {
+            valid.push(patch);
+        } else {
+            errors.extend(
+                patch_errors
+                    .into_iter()
+                    .map(|e| format!("TOML config error at param_patches[{i}]: {e}")),
+            );
+        }
+    }
+
+    if valid.is_empty() {
+        return (0, errors);
+    }
+
+    match param_io::import(params, &to_dump(valid.iter().copied())) {
+        Ok(_) => (valid.len(), errors),
+        Err(e) => {
+            errors.extend(e.into_iter().map(|e| format!("Couldn't apply param patches: {e}")));
+            (0, errors)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_dump, ParamPatch};
+
+    #[derive(serde::Deserialize)]
+    struct Patches {
+        param_patches: Vec<ParamPatch>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let patches: Patches = toml::from_str(
+            r#"
+            [[param_patches]]
+            param = "EquipParamWeapon"
+            row = 1000000
+            field = "sa_durability"
+            value = 120
+
+            [[param_patches]]
+            param = "SpEffect"
+            row = 100
+            field = "effect_endurance"
+            value = 2.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(patches.param_patches.len(), 2);
+        assert!(patches.param_patches.iter().all(|p| p.check_param().is_ok()));
+        assert_eq!(to_dump(&patches.param_patches)["SpEffect"][&100].fields.len(), 1);
+
+        let patches: Patches = toml::from_str(
+            "[[param_patches]]\nparam = \"Nope\"\nrow = 1\nfield = \"x\"\nvalue = true",
+        )
+        .unwrap();
+        assert!(patches.param_patches[0].check_param().is_err());
+        assert!(toml::from_str::<Patches>("[[param_patches]]\nparam = \"Nope\"").is_err());
+    }
+}
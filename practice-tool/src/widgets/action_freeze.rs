@@ -3,6 +3,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::flag::{Flag, FlagWidget};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 struct ActionFreeze {
     ptr: PointerChain<u8>,
     state: bool,
@@ -28,6 +30,9 @@ impl Flag for ActionFreeze {
     }
 
     fn set(&mut self, value: bool) {
+        if session_locked() {
+            return;
+        }
         self.state = value;
         self.ptr.write(if self.state { self.state_on } else { self.state_off });
     }
@@ -0,0 +1,217 @@
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, WindowFlags};
+use libeldenring::attack_rating::{
+    self, Attribute, Attributes, DamageType, StatusEffect, Weapon, AFFINITIES,
+};
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+const POPUP_TAG: &str = "##attack_rating";
+
+#[derive(Debug)]
+pub(crate) struct AttackRating {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+    stats: PointerChain<CharacterStats>,
+    chr_asm: PointerChain<ChrAsm>,
+    what_if: Weapon,
+    what_if_attributes: Attributes,
+}
+
+impl AttackRating {
+    pub(crate) fn new(
+        stats: PointerChain<CharacterStats>,
+        chr_asm: PointerChain<ChrAsm>,
+        hotkey_close: Key,
+    ) -> Self {
+        AttackRating {
+            label: "Attack rating".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            stats,
+            chr_asm,
+            what_if: Weapon { id: 1000000, ..Default::default() },
+            what_if_attributes: Attributes([10; 5]),
+        }
+    }
+
+    fn render_equipped(&mut self, ui: &imgui::Ui, params: &Params) {
+        let (Some(stats), Some(chr_asm)) = (self.stats.read(), self.chr_asm.read()) else {
+            ui.text("Not loaded");
+            return;
+        };
+        let attributes = Attributes::from(&stats);
+        let mut equipped = None;
+
+        for (left, slot, hand) in
+            [(false, chr_asm.right_weapon_slot, "Right"), (true, chr_asm.left_weapon_slot, "Left")]
+        {
+            let Some(mut weapon) =
+                chr_asm.weapon_id(left, slot as usize).and_then(Weapon::from_equip_id)
+            else {
+                continue;
+            };
+            weapon.two_handed = chr_asm.two_handed(left);
+            equipped = equipped.or(Some(weapon));
+            render_rating(
+                ui,
+                &format!("{hand} hand: {}", describe(&weapon)),
+                attack_rating::calculate(params, &weapon, &attributes),
+            );
+        }
+
+        if ui.button("Copy to what if##attack_rating-copy") {
+            if let Some(weapon) = equipped {
+                self.what_if = weapon;
+            }
+            self.what_if_attributes = attributes;
+        }
+    }
+
+    fn render_what_if(&mut self, ui: &imgui::Ui, params: &Params) {
+        let _tok = ui.push_item_width(150. * scaling_factor(ui));
+
+        let mut id = self.what_if.id as i32;
+        if ui.input_int("Weapon ID##attack_rating-id", &mut id).step(10000).build() {
+            self.what_if.id = id.max(0) as u32;
+        }
+
+        let affinity = AFFINITIES.get(self.what_if.affinity as usize).copied().unwrap_or("?");
+        if let Some(_combo) = ui.begin_combo("Affinity##attack_rating-affinity", affinity) {
+            for (i, name) in AFFINITIES.iter().enumerate() {
+                if ui.selectable_config(name).selected(i as u32 == self.what_if.affinity).build() {
+                    self.what_if.affinity = i as u32;
+                }
+            }
+        }
+
+        let mut level = self.what_if.level as i32;
+        if ui.input_int("Level##attack_rating-level", &mut level).build() {
+            self.what_if.level = level.clamp(0, 25) as u32;
+        }
+
+        for attribute in Attribute::ALL {
+            let value = &mut self.what_if_attributes.0[attribute as usize];
+            if ui.input_int(format!("{}##attack_rating-attr", attribute.name()), value).build() {
+                *value = (*value).clamp(1, 99);
+            }
+        }
+
+        ui.checkbox("Two-handed##attack_rating-2h", &mut self.what_if.two_handed);
+
+        render_rating(
+            ui,
+            &describe(&self.what_if),
+            attack_rating::calculate(params, &self.what_if, &self.what_if_attributes),
+        );
+    }
+}
+
+fn describe(weapon: &Weapon) -> String {
+    let affinity = AFFINITIES.get(weapon.affinity as usize).copied().unwrap_or("?");
+    let hands = if weapon.two_handed { ", two-handed" } else { "" };
+    format!("{} {affinity} +{}{hands}", weapon.id, weapon.level)
+}
+
+fn render_rating(ui: &imgui::Ui, label: &str, rating: Result<attack_rating::AttackRating, String>) {
+    ui.text(label);
+    let rating = match rating {
+        Ok(rating) => rating,
+        Err(e) => {
+            ui.text_colored([1., 0.5, 0.5, 1.], e);
+            return;
+        },
+    };
+
+    ui.text(format!("  AR {}", rating.total() as i32));
+    for t in DamageType::ALL {
+        let (base, scaling) = (rating.base[t as usize], rating.scaling[t as usize]);
+        if base != 0. {
+            ui.text(format!("    {:10} {:4} {:+5}", t.name(), base as i32, scaling as i32));
+        }
+    }
+
+    let grades = Attribute::ALL
+        .iter()
+        .map(|&a| format!("{} {}", a.name(), rating.grades[a as usize].unwrap_or('-')))
+        .collect::<Vec<_>>();
+    ui.text(format!("  Scaling: {}", grades.join("  ")));
+
+    let unmet = Attribute::ALL
+        .iter()
+        .filter(|&&a| !rating.requirements_met[a as usize])
+        .map(|a| a.name())
+        .collect::<Vec<_>>();
+    if !unmet.is_empty() {
+        ui.text_colored(
+            [1., 0.5, 0.5, 1.],
+            format!("  Requirements not met: {}", unmet.join(", ")),
+        );
+    }
+
+    for s in StatusEffect::ALL {
+        let buildup = rating.status[s as usize];
+        if buildup != 0. {
+            ui.text(format!("    {:10} {:4}", s.name(), buildup as i32));
+        }
+    }
+}
+
+impl Widget for AttackRating {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let (x, y) = unsafe {
+            let mut wnd_pos = ImVec2::default();
+            igGetWindowPos(&mut wnd_pos);
+            (igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y)
+        };
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        unsafe {
+            igSetNextWindowPos(
+                ImVec2::new(x + 200. * scale, y),
+                Condition::Always as i8 as _,
+                ImVec2::new(0., 0.),
+            )
+        };
+        if let Some(_token) = ui
+            .modal_popup_config(POPUP_TAG)
+            .flags(
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::ALWAYS_AUTO_RESIZE,
+            )
+            .begin_popup()
+        {
+            // The params are loaded in the background; don't wait for them.
+            match PARAMS.try_read() {
+                Some(params) => {
+                    ui.text("Equipped");
+                    ui.separator();
+                    self.render_equipped(ui, &params);
+                    ui.separator();
+                    ui.text("What if");
+                    ui.separator();
+                    self.render_what_if(ui, &params);
+                },
+                None => ui.text("Params not loaded"),
+            }
+
+            ui.separator();
+            if ui.button_with_size(&self.label_close, [400., button_height])
+                || (self.hotkey_close.is_pressed(ui) && !ui.is_any_item_active())
+            {
+                ui.close_current_popup();
+            }
+        }
+    }
+}
@@ -3,6 +3,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::stats_editor::{Datum, Stats, StatsEditor};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 #[derive(Debug)]
 struct CharacterStatsEdit {
     stats_ptr: PointerChain<CharacterStats>,
@@ -62,6 +64,9 @@ impl Stats for CharacterStatsEdit {
     }
 
     fn write(&mut self) {
+        if session_locked() {
+            return;
+        }
         if let Some(stats) = self.stats.clone() {
             self.stats_ptr.write(stats);
         }
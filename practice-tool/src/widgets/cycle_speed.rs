@@ -6,6 +6,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 #[derive(Debug)]
 struct CycleSpeed {
     ptr: [PointerChain<f32>; 2],
@@ -37,6 +39,9 @@ impl ReadWrite for CycleSpeed {
     }
 
     fn write(&mut self) {
+        if session_locked() {
+            return;
+        }
         let next = *self
             .current
             .and_then(|current| self.values.iter().find(|&&x| x > current))
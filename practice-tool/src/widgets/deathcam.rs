@@ -3,6 +3,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::flag::{Flag, FlagWidget};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 #[derive(Debug)]
 pub(crate) struct Deathcam {
     flag: Bitflag<u8>,
@@ -22,6 +24,9 @@ impl Deathcam {
 
 impl Flag for Deathcam {
     fn set(&mut self, value: bool) {
+        if session_locked() {
+            return;
+        }
         if let Some(state) = self.flag.get() {
             self.seven.write(if state { 7 } else { 0 });
             self.flag.set(value);
@@ -3,10 +3,15 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::flag::{Flag, FlagWidget};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 struct Bitflag(BitflagInner<u8>);
 
 impl Flag for Bitflag {
     fn set(&mut self, value: bool) {
+        if session_locked() {
+            return;
+        }
         self.0.set(value);
     }
 
@@ -0,0 +1,167 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use libeldenring::frame_advance::FrameAdvance as Stepper;
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+use crate::practice_tool::session_locked;
+
+/// Set while the game is paused and not stepping, so the frame counter
+/// only counts frames the game actually ran.
+static GAME_FROZEN: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn game_frozen() -> bool {
+    GAME_FROZEN.load(Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub(crate) struct FrameAdvance {
+    game_speed: PointerChain<f32>,
+    stepper: Stepper,
+    frames: u32,
+    hotkey_pause: Option<Key>,
+    hotkey_step: Option<Key>,
+    hotkey_step_many: Option<Key>,
+    label_pause: String,
+    label_resume: String,
+    label_step: String,
+    label_step_many: String,
+    last_frame: i32,
+    logs: Vec<String>,
+}
+
+impl FrameAdvance {
+    pub(crate) fn new(
+        game_speed: PointerChain<f32>,
+        frames: u32,
+        hotkey_pause: Option<Key>,
+        hotkey_step: Option<Key>,
+        hotkey_step_many: Option<Key>,
+    ) -> Self {
+        let with_key = |label: &str, key: Option<Key>| match key {
+            Some(key) => format!("{label} ({key})"),
+            None => label.to_string(),
+        };
+
+        FrameAdvance {
+            game_speed,
+            stepper: Stepper::default(),
+            frames,
+            label_pause: with_key("Pause", hotkey_pause),
+            label_resume: with_key("Resume", hotkey_pause),
+            label_step: with_key("Step", hotkey_step),
+            label_step_many: with_key(&format!("Step {frames}"), hotkey_step_many),
+            hotkey_pause,
+            hotkey_step,
+            hotkey_step_many,
+            last_frame: -1,
+            logs: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, speed: Option<f32>) {
+        if let Some(speed) = speed {
+            self.game_speed.write(speed);
+        }
+        GAME_FROZEN.store(self.stepper.is_frozen(), Ordering::Relaxed);
+    }
+
+    fn toggle(&mut self) {
+        if !self.stepper.is_paused() && session_locked() {
+            self.logs.push("Can't pause during a multiplayer session".to_string());
+            return;
+        }
+
+        let Some(current) = self.game_speed.read() else {
+            return;
+        };
+        let speed = self.stepper.toggle(current);
+        self.apply(speed);
+    }
+
+    fn step(&mut self, frames: u32) {
+        let speed = self.stepper.step(frames);
+        self.apply(speed);
+    }
+
+    /// Counts the frame and resumes the game if a multiplayer session
+    /// started, at most once per frame whether the menu is open or not.
+    fn tick(&mut self, ui: &imgui::Ui) {
+        let frame = ui.frame_count();
+        if frame == self.last_frame {
+            return;
+        }
+        self.last_frame = frame;
+
+        if self.stepper.is_paused() && session_locked() {
+            let speed = self.stepper.resume();
+            self.apply(speed);
+            self.logs.push("Multiplayer session: game resumed".to_string());
+            return;
+        }
+
+        let speed = self.stepper.tick();
+        self.apply(speed);
+    }
+}
+
+impl Widget for FrameAdvance {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+        let half_width = (button_width - ui.clone_style().item_spacing[0]) / 2.;
+
+        let label = if self.stepper.is_paused() { &self.label_resume } else { &self.label_pause };
+        if ui.button_with_size(label, [button_width, button_height]) {
+            self.toggle();
+        }
+
+        let _token = ui.begin_disabled(!self.stepper.is_paused());
+        if ui.button_with_size(&self.label_step, [half_width, button_height]) {
+            self.step(1);
+        }
+        ui.same_line();
+        if ui.button_with_size(&self.label_step_many, [half_width, button_height]) {
+            self.step(self.frames);
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        match self.stepper.steps_left() {
+            _ if !self.stepper.is_paused() => {},
+            0 => ui.text("Paused"),
+            left => ui.text(format!("Stepping ({left} frames left)")),
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey_pause.is_some_and(|k| k.is_pressed(ui)) {
+            self.toggle();
+        }
+        if self.hotkey_step.is_some_and(|k| k.is_pressed(ui)) {
+            self.step(1);
+        }
+        if self.hotkey_step_many.is_some_and(|k| k.is_pressed(ui)) {
+            self.step(self.frames);
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
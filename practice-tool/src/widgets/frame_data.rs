@@ -0,0 +1,292 @@
+use std::sync::atomic::Ordering;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, WindowFlags};
+use libeldenring::frame_data::{AnimationSample, Session, Track};
+use libeldenring::memedit::PointerChain;
+use libeldenring::pointer_chain;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+use super::frame_advance::game_frozen;
+use super::target::LOCKED_TARGET;
+
+const POPUP_TAG: &str = "##frame-data";
+
+/// Seconds of the timeline shown at once.
+const TIMELINE_SECONDS: f64 = 5.;
+/// Records listed per track below the timeline.
+const TABLE_ROWS: usize = 8;
+
+#[derive(Debug)]
+pub(crate) struct FrameData {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+    cur_anim: PointerChain<u32>,
+    cur_anim_time: PointerChain<f32>,
+    cur_anim_length: PointerChain<f32>,
+    session: Session,
+    recording: bool,
+    /// Seconds recorded, not counting frames spent paused.
+    elapsed: f64,
+    last_sample: Instant,
+    last_frame: i32,
+    logs: Vec<String>,
+}
+
+impl FrameData {
+    pub(crate) fn new(
+        cur_anim: PointerChain<u32>,
+        cur_anim_time: PointerChain<f32>,
+        cur_anim_length: PointerChain<f32>,
+        hotkey_close: Key,
+    ) -> Self {
+        FrameData {
+            label: "Frame data".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            cur_anim,
+            cur_anim_time,
+            cur_anim_length,
+            session: Session::new(&["Player", "Target"]),
+            recording: false,
+            elapsed: 0.,
+            last_sample: Instant::now(),
+            last_frame: -1,
+            logs: Vec::new(),
+        }
+    }
+
+    fn player(&self) -> Option<AnimationSample> {
+        Some(AnimationSample {
+            animation: self.cur_anim.read()?,
+            time: self.cur_anim_time.read()?,
+            length: self.cur_anim_length.read()?,
+        })
+    }
+
+    /// The locked-on target, as published by the target widget. Its hook is
+    /// only in place while it's enabled, which is why the config check
+    /// requires a visible `target` command along with `frame_data`.
+    fn target(&self) -> Option<AnimationSample> {
+        let addr = LOCKED_TARGET.load(Ordering::Relaxed) as usize;
+        if addr == 0 {
+            return None;
+        }
+
+        Some(AnimationSample {
+            animation: pointer_chain!(addr + 0x190, 0x18, 0x20).read()?,
+            time: pointer_chain!(addr + 0x190, 0x18, 0x24).read()?,
+            length: pointer_chain!(addr + 0x190, 0x18, 0x2C).read()?,
+        })
+    }
+
+    /// Samples both tracks, at most once per frame whether the menu is open
+    /// or not.
+    fn sample(&mut self, ui: &imgui::Ui) {
+        let frame = ui.frame_count();
+        if !self.recording || frame == self.last_frame {
+            return;
+        }
+        self.last_frame = frame;
+
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_sample).as_secs_f64();
+        self.last_sample = now;
+        // Frames where the game is paused aren't part of any animation.
+        if game_frozen() {
+            return;
+        }
+
+        self.elapsed += delta;
+        let samples = [self.player(), self.target()];
+        self.session.sample(self.elapsed, &samples);
+    }
+
+    fn clear(&mut self) {
+        self.session.clear();
+        self.elapsed = 0.;
+        self.last_sample = Instant::now();
+    }
+
+    fn toggle_recording(&mut self) {
+        self.recording = !self.recording;
+        if self.recording {
+            self.clear();
+            self.logs.push("Frame data recording started".to_string());
+        } else {
+            self.logs
+                .push(format!("Frame data recording stopped after {} frames", self.session.frames));
+        }
+    }
+
+    fn save(&mut self) {
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let result = crate::util::get_dll_path()
+            .ok_or_else(|| "Could not find the DLL path".to_string())
+            .and_then(|path| {
+                let path = path.with_file_name(format!("frame_data_{timestamp}.json"));
+                std::fs::write(&path, self.session.to_json())
+                    .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+                Ok(format!("Saved frame data to {}", path.display()))
+            });
+        self.logs.push(result.unwrap_or_else(|e| e));
+    }
+
+    fn render_timeline(&self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let width = 600. * scale;
+        let row_height = 20. * scale;
+        let label_width = 60. * scale;
+
+        let end = self.session.duration.max(TIMELINE_SECONDS);
+        let begin = end - TIMELINE_SECONDS;
+        let to_x = |time: f64| ((time - begin) / TIMELINE_SECONDS) as f32 * (width - label_width);
+
+        let [x, y] = ui.cursor_screen_pos();
+        let draw_list = ui.get_window_draw_list();
+
+        for (row, track) in self.session.tracks.iter().enumerate() {
+            let top = y + row as f32 * row_height;
+            let bottom = top + row_height - 2.;
+            draw_list.add_text([x, top], [1., 1., 1., 1.], &track.name);
+
+            let visible = track
+                .all_records()
+                .filter(|r| r.start_time + r.duration >= begin && r.start_time <= end);
+            for record in visible {
+                let x0 = x + label_width + to_x(record.start_time).max(0.);
+                let x1 = x + label_width + to_x(record.start_time + record.duration);
+                let x1 = x1.max(x0 + 1.);
+
+                draw_list
+                    .add_rect([x0, top], [x1, bottom], animation_color(record.animation))
+                    .filled(true)
+                    .build();
+                if x1 - x0 > 50. * scale {
+                    draw_list.add_text(
+                        [x0 + 2., top + 2.],
+                        [0., 0., 0., 1.],
+                        record.animation.to_string(),
+                    );
+                }
+                if ui.is_mouse_hovering_rect([x0, top], [x1, bottom]) {
+                    ui.tooltip_text(format!(
+                        "{}\nStart frame {}\n{} frames, {:.3}s of {:.3}s",
+                        record.animation,
+                        record.start_frame,
+                        record.frames,
+                        record.duration,
+                        record.length
+                    ));
+                }
+            }
+        }
+
+        ui.dummy([width, row_height * self.session.tracks.len() as f32]);
+    }
+
+    fn render_table(ui: &imgui::Ui, track: &Track) {
+        ui.text(&track.name);
+        for record in track.all_records().rev().take(TABLE_ROWS) {
+            ui.text(format!(
+                "  {:>10}  frame {:>6}  {:>4} frames  {:.3}s / {:.3}s",
+                record.animation, record.start_frame, record.frames, record.duration, record.length
+            ));
+        }
+    }
+}
+
+/// A stable color per animation ID, so repeated animations stand out.
+fn animation_color(animation: u32) -> [f32; 4] {
+    let hash = animation.wrapping_mul(2654435761);
+    let channel = |shift: u32| 0.4 + ((hash >> shift) & 0xff) as f32 / 255. * 0.6;
+    [channel(0), channel(8), channel(16), 1.]
+}
+
+impl Widget for FrameData {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.sample(ui);
+
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let (x, y) = unsafe {
+            let mut wnd_pos = ImVec2::default();
+            igGetWindowPos(&mut wnd_pos);
+            (igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y)
+        };
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        unsafe {
+            igSetNextWindowPos(
+                ImVec2::new(x + 200. * scale, y),
+                Condition::Always as i8 as _,
+                ImVec2::new(0., 0.),
+            )
+        };
+        if let Some(_token) = ui
+            .modal_popup_config(POPUP_TAG)
+            .flags(
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::ALWAYS_AUTO_RESIZE,
+            )
+            .begin_popup()
+        {
+            let label = if self.recording { "Stop recording" } else { "Start recording" };
+            if ui.button_with_size(label, [195., button_height]) {
+                self.toggle_recording();
+            }
+            ui.same_line();
+            if ui.button_with_size("Clear", [95., button_height]) {
+                self.clear();
+            }
+            ui.same_line();
+            if ui.button_with_size("Save", [95., button_height]) {
+                self.save();
+            }
+
+            ui.text(format!("{} frames, {:.2}s", self.session.frames, self.session.duration));
+            if LOCKED_TARGET.load(Ordering::Relaxed) == 0 {
+                ui.text("Enable the target entity info and lock on to record the target");
+            }
+            ui.separator();
+            self.render_timeline(ui);
+            ui.separator();
+            for track in &self.session.tracks {
+                Self::render_table(ui, track);
+            }
+
+            ui.separator();
+            if ui.button_with_size(&self.label_close, [400., button_height])
+                || (self.hotkey_close.is_pressed(ui) && !ui.is_any_item_active())
+            {
+                ui.close_current_popup();
+            }
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.sample(ui);
+
+        if self.recording {
+            ui.text(format!("Recording frame data ({} frames)", self.session.frames));
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
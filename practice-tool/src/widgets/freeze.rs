@@ -0,0 +1,58 @@
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::flag::{Flag, FlagWidget};
+use practice_tool_core::widgets::Widget;
+
+use crate::practice_tool::session_locked;
+
+/// What a frozen value is locked to.
+pub(crate) enum FreezeValue<T> {
+    /// The value at the time it was frozen.
+    Current,
+    Fixed(T),
+    /// Whatever another location holds, e.g. max HP.
+    Source(PointerChain<T>),
+}
+
+struct Freeze<T> {
+    label: String,
+    chain: PointerChain<T>,
+    value: FreezeValue<T>,
+    id: Option<FreezeId>,
+}
+
+impl<T: Copy + Send + Sync + 'static> Flag for Freeze<T> {
+    fn set(&mut self, value: bool) {
+        if let Some(id) = self.id.take() {
+            FREEZER.unfreeze(id);
+        }
+
+        if !value || session_locked() {
+            return;
+        }
+
+        self.id = match &self.value {
+            FreezeValue::Current => {
+                self.chain.read().map(|v| FREEZER.freeze(&self.label, self.chain.clone(), v))
+            },
+            FreezeValue::Fixed(v) => Some(FREEZER.freeze(&self.label, self.chain.clone(), *v)),
+            FreezeValue::Source(source) => {
+                Some(FREEZER.freeze_to(&self.label, self.chain.clone(), source.clone()))
+            },
+        };
+    }
+
+    fn get(&self) -> Option<bool> {
+        Some(self.id.is_some_and(|id| FREEZER.is_frozen(id)))
+    }
+}
+
+pub(crate) fn freeze<T: Copy + Send + Sync + 'static>(
+    label: &str,
+    chain: PointerChain<T>,
+    value: FreezeValue<T>,
+    key: Option<Key>,
+) -> Box<dyn Widget> {
+    let freeze = Freeze { label: label.to_string(), chain, value, id: None };
+    Box::new(FlagWidget::new(label, freeze, key))
+}
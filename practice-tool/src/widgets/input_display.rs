@@ -0,0 +1,137 @@
+use imgui::{Key, ProgressBar};
+use libeldenring::input_history::{Gamepad, InputHistory, BUTTONS};
+use parking_lot::Mutex;
+use practice_tool_core::widgets::scaling_factor;
+use windows::Win32::UI::Input::XboxController::XINPUT_GAMEPAD;
+
+/// Entries kept in the history.
+const HISTORY_CAPACITY: usize = 64;
+/// Entries shown, newest first.
+const HISTORY_SHOWN: usize = 16;
+
+const MOUSE_BUTTONS: [&str; 5] = ["LMB", "RMB", "MMB", "Mouse4", "Mouse5"];
+
+const COLOR_HELD: [f32; 4] = [0.4, 1., 0.4, 1.];
+const COLOR_RELEASED: [f32; 4] = [0.5, 0.5, 0.5, 1.];
+
+/// The last state of the first gamepad the game polled, before any blocking
+/// by the radial menu.
+static GAMEPAD: Mutex<Gamepad> = Mutex::new(Gamepad {
+    buttons: 0,
+    left_trigger: 0,
+    right_trigger: 0,
+    left_stick: [0, 0],
+    right_stick: [0, 0],
+});
+
+/// Called from the XInput hook.
+pub(crate) fn record_gamepad(gamepad: &XINPUT_GAMEPAD) {
+    *GAMEPAD.lock() = Gamepad {
+        buttons: gamepad.wButtons.0,
+        left_trigger: gamepad.bLeftTrigger,
+        right_trigger: gamepad.bRightTrigger,
+        left_stick: [gamepad.sThumbLX, gamepad.sThumbLY],
+        right_stick: [gamepad.sThumbRX, gamepad.sThumbRY],
+    };
+}
+
+pub(crate) fn current_gamepad() -> Gamepad {
+    *GAMEPAD.lock()
+}
+
+#[derive(Debug)]
+pub(crate) struct InputDisplay {
+    history: InputHistory,
+}
+
+impl InputDisplay {
+    pub(crate) fn new() -> Self {
+        InputDisplay { history: InputHistory::new(HISTORY_CAPACITY) }
+    }
+
+    /// Records the current inputs under `frame`. Called once per frame
+    /// whether the indicator is drawn or not, so that the history has no
+    /// gaps while the menu is open.
+    pub(crate) fn record(&mut self, ui: &imgui::Ui, frame: u64) {
+        let mut inputs = current_gamepad().held();
+        inputs.extend(keyboard_and_mouse(ui));
+        self.history.sample(frame, inputs);
+    }
+
+    /// Draws the current gamepad state along with the history.
+    pub(crate) fn render(&self, ui: &imgui::Ui) {
+        render_gamepad(ui, &current_gamepad());
+
+        for entry in self.history.entries().rev().take(HISTORY_SHOWN) {
+            let inputs = if entry.inputs.is_empty() {
+                "Neutral".to_string()
+            } else {
+                entry.inputs.join(" + ")
+            };
+            ui.text(format!("{:>7} {:>4}f  {inputs}", entry.frame, entry.frames));
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// Keys and mouse buttons held, as far as imgui sees them.
+fn keyboard_and_mouse(ui: &imgui::Ui) -> Vec<String> {
+    let keys = Key::VARIANTS
+        .iter()
+        .filter(|key| ui.is_key_down(**key))
+        .map(|key| format!("{key:?}"))
+        // Gamepad buttons come from XInput, and modifiers are already listed as
+        // their left or right keys.
+        .filter(|name| {
+            !["Gamepad", "Mod", "ReservedFor"].iter().any(|prefix| name.starts_with(prefix))
+        });
+
+    let mouse = ui
+        .io()
+        .mouse_down
+        .iter()
+        .zip(MOUSE_BUTTONS)
+        .filter(|(down, _)| **down)
+        .map(|(_, name)| name.to_string());
+
+    keys.chain(mouse).collect()
+}
+
+fn render_gamepad(ui: &imgui::Ui, gamepad: &Gamepad) {
+    for (i, (button, name)) in BUTTONS.iter().enumerate() {
+        if i > 0 {
+            ui.same_line();
+        }
+        let color = if gamepad.is_pressed(*button) { COLOR_HELD } else { COLOR_RELEASED };
+        ui.text_colored(color, name);
+    }
+
+    let scale = scaling_factor(ui);
+    let pbar_size = [80. * scale, 4. * scale];
+    for (name, value) in [("LT", gamepad.left_trigger), ("RT", gamepad.right_trigger)] {
+        ui.text(name);
+        ui.same_line();
+        ProgressBar::new(value as f32 / 255.).size(pbar_size).overlay_text("").build(ui);
+        ui.same_line();
+    }
+    ui.new_line();
+
+    // Both sticks, side by side.
+    let radius = 20. * scale;
+    let [x, y] = ui.cursor_screen_pos();
+    let draw_list = ui.get_window_draw_list();
+    for (i, [sx, sy]) in [gamepad.left_stick, gamepad.right_stick].into_iter().enumerate() {
+        let center = [x + radius + i as f32 * (radius * 2. + 10. * scale), y + radius];
+        let offset = [sx as f32 / 32768. * radius, -(sy as f32) / 32768. * radius];
+
+        draw_list.add_circle(center, radius, COLOR_RELEASED).build();
+        draw_list
+            .add_circle([center[0] + offset[0], center[1] + offset[1]], 3. * scale, COLOR_HELD)
+            .filled(true)
+            .build();
+    }
+    ui.dummy([radius * 4. + 10. * scale, radius * 2.]);
+}
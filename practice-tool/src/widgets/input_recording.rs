@@ -0,0 +1,251 @@
+use libeldenring::input_history::Gamepad;
+use libeldenring::input_recording::{Playback, Recording};
+use parking_lot::Mutex;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+use windows::Win32::UI::Input::XboxController::{
+    XINPUT_GAMEPAD, XINPUT_GAMEPAD_BUTTON_FLAGS, XINPUT_STATE,
+};
+
+use super::frame_advance::game_frozen;
+use super::input_display::current_gamepad;
+use crate::practice_tool::session_locked;
+
+/// The state being played back, and a packet number that changes with it,
+/// as the game ignores polls whose packet number didn't change.
+static PLAYBACK: Mutex<Option<(u32, Gamepad)>> = Mutex::new(None);
+
+/// Called from the XInput hook. Replaces the state with the one being
+/// played back, if any, and returns whether it did.
+pub(crate) fn apply_playback(state: &mut XINPUT_STATE) -> bool {
+    let Some((packet, gamepad)) = *PLAYBACK.lock() else {
+        return false;
+    };
+
+    *state = XINPUT_STATE {
+        dwPacketNumber: packet,
+        Gamepad: XINPUT_GAMEPAD {
+            wButtons: XINPUT_GAMEPAD_BUTTON_FLAGS(gamepad.buttons),
+            bLeftTrigger: gamepad.left_trigger,
+            bRightTrigger: gamepad.right_trigger,
+            sThumbLX: gamepad.left_stick[0],
+            sThumbLY: gamepad.left_stick[1],
+            sThumbRX: gamepad.right_stick[0],
+            sThumbRY: gamepad.right_stick[1],
+        },
+    };
+    true
+}
+
+#[derive(Debug)]
+pub(crate) struct InputRecording {
+    name: String,
+    looping: bool,
+    hotkey_record: Option<Key>,
+    hotkey_play: Option<Key>,
+    label_record: String,
+    label_play: String,
+    recording: Option<Recording>,
+    playback: Option<Playback>,
+    packet: u32,
+    last_frame: i32,
+    logs: Vec<String>,
+}
+
+impl InputRecording {
+    pub(crate) fn new(
+        name: String,
+        looping: bool,
+        hotkey_record: Option<Key>,
+        hotkey_play: Option<Key>,
+    ) -> Self {
+        let with_key = |label: String, key: Option<Key>| match key {
+            Some(key) => format!("{label} ({key})"),
+            None => label,
+        };
+
+        InputRecording {
+            label_record: with_key(format!("Record {name}"), hotkey_record),
+            label_play: with_key(format!("Play {name}"), hotkey_play),
+            name,
+            looping,
+            hotkey_record,
+            hotkey_play,
+            recording: None,
+            playback: None,
+            packet: 0,
+            last_frame: -1,
+            logs: Vec::new(),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}.inputs.json", self.name)
+    }
+
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(recording) => self.save(recording),
+            None => {
+                self.stop_playback();
+                self.recording = Some(Recording::default());
+                self.logs.push(format!("Recording inputs for {}", self.name));
+            },
+        }
+    }
+
+    fn save(&mut self, recording: Recording) {
+        let frames = recording.frames();
+        let result = crate::util::get_dll_path()
+            .ok_or_else(|| "Could not find the DLL path".to_string())
+            .and_then(|path| {
+                let path = path.with_file_name(self.file_name());
+                std::fs::write(&path, recording.to_json())
+                    .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+                Ok(format!("Saved {frames} frames of inputs to {}", path.display()))
+            });
+        self.logs.push(result.unwrap_or_else(|e| e));
+    }
+
+    fn load(&self) -> Result<Recording, String> {
+        let path = crate::util::get_dll_path()
+            .ok_or_else(|| "Could not find the DLL path".to_string())?
+            .with_file_name(self.file_name());
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        Recording::from_json(&json)
+    }
+
+    fn toggle_playback(&mut self) {
+        if self.playback.is_some() {
+            self.stop_playback();
+            self.logs.push(format!("Stopped playing {}", self.name));
+            return;
+        }
+
+        if session_locked() {
+            self.logs.push("Can't play inputs back during a multiplayer session".to_string());
+            return;
+        }
+        if self.recording.is_some() {
+            return;
+        }
+
+        match self.load() {
+            Ok(recording) => {
+                self.logs.push(format!("Playing {} ({} frames)", self.name, recording.frames()));
+                self.playback = Some(Playback::new(recording, self.looping));
+            },
+            Err(e) => self.logs.push(e),
+        }
+    }
+
+    fn stop_playback(&mut self) {
+        if self.playback.take().is_some() {
+            *PLAYBACK.lock() = None;
+        }
+    }
+
+    /// Records or plays back one frame, at most once per frame whether the
+    /// menu is open or not. Frames where the game is paused are skipped, so
+    /// frame stepping works through a recording.
+    fn tick(&mut self, ui: &imgui::Ui) {
+        let frame = ui.frame_count();
+        if frame == self.last_frame {
+            return;
+        }
+        self.last_frame = frame;
+
+        if self.playback.is_some() && session_locked() {
+            self.stop_playback();
+            self.logs.push(format!("Multiplayer session: stopped playing {}", self.name));
+        }
+
+        if game_frozen() {
+            return;
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.push(current_gamepad());
+        }
+
+        if let Some(playback) = &mut self.playback {
+            match playback.next_frame() {
+                Some(gamepad) => {
+                    self.packet = self.packet.wrapping_add(1);
+                    *PLAYBACK.lock() = Some((self.packet, gamepad));
+                },
+                None => {
+                    self.stop_playback();
+                    self.logs.push(format!("Finished playing {}", self.name));
+                },
+            }
+        }
+    }
+
+    fn status(&self) -> Option<String> {
+        if let Some(recording) = &self.recording {
+            Some(format!("Recording {}: {} frames", self.name, recording.frames()))
+        } else {
+            self.playback.as_ref().map(|playback| {
+                format!("Playing {}: {}/{}", self.name, playback.frame(), playback.frames())
+            })
+        }
+    }
+}
+
+impl Widget for InputRecording {
+    fn render(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+        let half_width = (button_width - ui.clone_style().item_spacing[0]) / 2.;
+
+        let label =
+            if self.recording.is_some() { "Stop recording" } else { self.label_record.as_str() };
+        if ui.button_with_size(label, [half_width, button_height]) {
+            self.toggle_recording();
+        }
+        ui.same_line();
+        let label = if self.playback.is_some() { "Stop playing" } else { self.label_play.as_str() };
+        if ui.button_with_size(label, [half_width, button_height]) {
+            self.toggle_playback();
+        }
+
+        if let Some(status) = self.status() {
+            ui.text(status);
+        }
+    }
+
+    fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        if let Some(status) = self.status() {
+            ui.text(status);
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        self.tick(ui);
+
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey_record.is_some_and(|k| k.is_pressed(ui)) {
+            self.toggle_recording();
+        }
+        if self.hotkey_play.is_some_and(|k| k.is_pressed(ui)) {
+            self.toggle_playback();
+        }
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
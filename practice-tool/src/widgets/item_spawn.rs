@@ -15,6 +15,7 @@ use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_
 use serde::Deserialize;
 
 use super::string_match;
+use crate::practice_tool::session_locked;
 
 static AFFINITIES: [(u32, &str); 13] = [
     (0, "No affinity"),
@@ -209,6 +210,10 @@ impl ItemSpawner<'_> {
     }
 
     fn spawn(&mut self) {
+        if session_locked() {
+            self.write_log("Not spawning items during a multiplayer session".into());
+            return;
+        }
         if self.sentinel.get().is_none() {
             self.write_log("Not spawning item when not in game".into());
             return;
@@ -0,0 +1,134 @@
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, WindowFlags};
+use libeldenring::legality::{self, Character};
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+const POPUP_TAG: &str = "##legality";
+
+#[derive(Debug)]
+pub(crate) struct Legality {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+    stats: PointerChain<CharacterStats>,
+    blessings: Option<PointerChain<CharacterBlessings>>,
+    weapon_level: PointerChain<u8>,
+    chr_asm: PointerChain<ChrAsm>,
+    inventory: PointerChain<InventoryItems>,
+    findings: Option<Vec<String>>,
+}
+
+impl Legality {
+    pub(crate) fn new(
+        stats: PointerChain<CharacterStats>,
+        blessings: Option<PointerChain<CharacterBlessings>>,
+        weapon_level: PointerChain<u8>,
+        chr_asm: PointerChain<ChrAsm>,
+        inventory: PointerChain<InventoryItems>,
+        hotkey_close: Key,
+    ) -> Self {
+        Legality {
+            label: "Legality check".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            stats,
+            blessings,
+            weapon_level,
+            chr_asm,
+            inventory,
+            findings: None,
+        }
+    }
+
+    /// Runs every check, or returns `None` if the character isn't loaded.
+    fn check(&self) -> Option<Vec<String>> {
+        let character = Character {
+            stats: self.stats.read()?,
+            blessings: self.blessings.as_ref().and_then(|b| b.read()),
+            weapon_level: self.weapon_level.read().map(u32::from),
+        };
+        let mut findings = legality::check(&character);
+
+        let Some(params) = PARAMS.try_read() else {
+            findings.push("Equipment not checked: params not loaded".to_string());
+            return Some(findings);
+        };
+        let Some(chr_asm) = self.chr_asm.read() else {
+            findings.push("Equipment not checked: couldn't read the equipped items".to_string());
+            return Some(findings);
+        };
+        let inventory = self.inventory.read().and_then(|i| i.read_items(self.inventory.source()));
+        if inventory.is_none() {
+            findings.push("Inventory not checked: couldn't read the inventory".to_string());
+        }
+        findings.extend(legality::check_equipment(
+            &params,
+            &chr_asm.equip_param_ids,
+            inventory.as_deref().unwrap_or_default(),
+            character.weapon_level,
+        ));
+
+        Some(findings)
+    }
+}
+
+impl Widget for Legality {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let (x, y) = unsafe {
+            let mut wnd_pos = ImVec2::default();
+            igGetWindowPos(&mut wnd_pos);
+            (igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y)
+        };
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            self.findings = self.check();
+            ui.open_popup(POPUP_TAG);
+        }
+
+        unsafe {
+            igSetNextWindowPos(
+                ImVec2::new(x + 200. * scale, y),
+                Condition::Always as i8 as _,
+                ImVec2::new(0., 0.),
+            )
+        };
+        if let Some(_token) = ui
+            .modal_popup_config(POPUP_TAG)
+            .flags(
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::ALWAYS_AUTO_RESIZE,
+            )
+            .begin_popup()
+        {
+            match &self.findings {
+                None => ui.text("Not loaded"),
+                Some(findings) if findings.is_empty() => {
+                    ui.text_colored([0.5, 1., 0.5, 1.], "No problems found")
+                },
+                Some(findings) => {
+                    for finding in findings {
+                        ui.text_colored([1., 0.5, 0.5, 1.], finding);
+                    }
+                },
+            }
+
+            ui.separator();
+            if ui.button_with_size("Check again", [400., button_height]) {
+                self.findings = self.check();
+            }
+            if ui.button_with_size(&self.label_close, [400., button_height])
+                || (self.hotkey_close.is_pressed(ui) && !ui.is_any_item_active())
+            {
+                ui.close_current_popup();
+            }
+        }
+    }
+}
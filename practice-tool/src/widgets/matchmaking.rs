@@ -0,0 +1,130 @@
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, WindowFlags};
+use libeldenring::matchmaking::{Brackets, MAX_LEVEL};
+use libeldenring::prelude::*;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+const POPUP_TAG: &str = "##matchmaking";
+
+#[derive(Debug)]
+pub(crate) struct Matchmaking {
+    label: String,
+    label_close: String,
+    hotkey_close: Key,
+    stats: PointerChain<CharacterStats>,
+    weapon_level: PointerChain<u8>,
+    what_if_level: i32,
+    what_if_weapon_level: i32,
+}
+
+impl Matchmaking {
+    pub(crate) fn new(
+        stats: PointerChain<CharacterStats>,
+        weapon_level: PointerChain<u8>,
+        hotkey_close: Key,
+    ) -> Self {
+        Matchmaking {
+            label: "Matchmaking".to_string(),
+            label_close: format!("Close ({hotkey_close})"),
+            hotkey_close,
+            stats,
+            weapon_level,
+            what_if_level: 125,
+            what_if_weapon_level: 25,
+        }
+    }
+
+    fn render_current(&mut self, ui: &imgui::Ui) {
+        let (Some(stats), Some(weapon_level)) = (self.stats.read(), self.weapon_level.read())
+        else {
+            ui.text("Not loaded");
+            return;
+        };
+
+        let brackets = Brackets::new(stats.level, weapon_level as u32);
+        render_brackets(ui, "Current", &brackets);
+        render_brackets(ui, "Next level", &brackets.level_up(1));
+        if let Some(upgrade) = brackets.upgrade() {
+            render_brackets(ui, "Next upgrade", &upgrade);
+        }
+        if let Some(upgrade) = brackets.upgrade_somber().filter(|b| Some(*b) != brackets.upgrade())
+        {
+            render_brackets(ui, "Next somber upgrade", &upgrade);
+        }
+
+        if ui.button("Copy to what if##matchmaking-copy") {
+            self.what_if_level = brackets.level;
+            self.what_if_weapon_level = brackets.weapon_level as i32;
+        }
+    }
+
+    fn render_what_if(&mut self, ui: &imgui::Ui) {
+        let _tok = ui.push_item_width(150. * scaling_factor(ui));
+
+        if ui.input_int("Level##matchmaking-level", &mut self.what_if_level).build() {
+            self.what_if_level = self.what_if_level.clamp(1, MAX_LEVEL);
+        }
+        if ui.input_int("Weapon level##matchmaking-weapon", &mut self.what_if_weapon_level).build()
+        {
+            self.what_if_weapon_level = self.what_if_weapon_level.clamp(0, 25);
+        }
+
+        let brackets = Brackets::new(self.what_if_level, self.what_if_weapon_level as u32);
+        render_brackets(ui, "What if", &brackets);
+    }
+}
+
+fn render_brackets(ui: &imgui::Ui, label: &str, brackets: &Brackets) {
+    let (lo, hi) = brackets.rune_levels;
+    let (wlo, whi) = brackets.weapon_levels;
+    ui.text(format!("{label}: level {}, weapon +{}", brackets.level, brackets.weapon_level));
+    ui.text(format!("  Rune levels {lo}-{hi}, weapons +{wlo}-{whi}"));
+}
+
+impl Widget for Matchmaking {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        let (x, y) = unsafe {
+            let mut wnd_pos = ImVec2::default();
+            igGetWindowPos(&mut wnd_pos);
+            (igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y)
+        };
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            ui.open_popup(POPUP_TAG);
+        }
+
+        unsafe {
+            igSetNextWindowPos(
+                ImVec2::new(x + 200. * scale, y),
+                Condition::Always as i8 as _,
+                ImVec2::new(0., 0.),
+            )
+        };
+        if let Some(_token) = ui
+            .modal_popup_config(POPUP_TAG)
+            .flags(
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::ALWAYS_AUTO_RESIZE,
+            )
+            .begin_popup()
+        {
+            self.render_current(ui);
+            ui.separator();
+            self.render_what_if(ui);
+
+            ui.separator();
+            if ui.button_with_size(&self.label_close, [400., button_height])
+                || (self.hotkey_close.is_pressed(ui) && !ui.is_any_item_active())
+            {
+                ui.close_current_popup();
+            }
+        }
+    }
+}
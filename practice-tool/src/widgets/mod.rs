@@ -1,17 +1,26 @@
 pub(crate) mod action_freeze;
+pub(crate) mod attack_rating;
 pub(crate) mod character_stats;
 pub(crate) mod cycle_color;
 pub(crate) mod cycle_speed;
 pub(crate) mod deathcam;
 pub(crate) mod flag;
+pub(crate) mod frame_advance;
+pub(crate) mod frame_data;
+pub(crate) mod freeze;
 pub(crate) mod group;
+pub(crate) mod input_display;
+pub(crate) mod input_recording;
 pub(crate) mod item_spawn;
 pub(crate) mod label;
+pub(crate) mod legality;
+pub(crate) mod matchmaking;
 pub(crate) mod multiflag;
 pub(crate) mod none;
 pub(crate) mod nudge_pos;
 pub(crate) mod position;
 pub(crate) mod quitout;
+pub(crate) mod restore_vanilla;
 pub(crate) mod runes;
 pub(crate) mod savefile_manager;
 pub(crate) mod target;
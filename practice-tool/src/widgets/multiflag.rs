@@ -3,6 +3,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::flag::{Flag, FlagWidget};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 #[derive(Debug)]
 struct MultiFlag {
     bitflags: Vec<Bitflag<u8>>,
@@ -16,6 +18,9 @@ impl MultiFlag {
 
 impl Flag for MultiFlag {
     fn set(&mut self, value: bool) {
+        if session_locked() {
+            return;
+        }
         for flag in &self.bitflags {
             flag.set(value);
         }
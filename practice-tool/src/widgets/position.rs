@@ -6,6 +6,8 @@ use practice_tool_core::widgets::nudge_position::NudgePositionStorage;
 use practice_tool_core::widgets::position::{Position, PositionStorage};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 pub(super) struct SavePosition {
     global_position: ErPosition,
     chunk_position: ErPosition,
@@ -57,6 +59,9 @@ impl PositionStorage for SavePosition {
     }
 
     fn load(&mut self) {
+        if session_locked() {
+            return;
+        }
         if let (Some([gx, gy, gz, _, _]), Some([cx, cy, cz, _, _])) =
             (self.global_position.read(), self.chunk_position.read())
         {
@@ -116,20 +121,26 @@ impl PositionStorage for SavePosition {
 
 impl NudgePositionStorage for SavePosition {
     fn nudge_up(&mut self) {
+        if session_locked() {
+            return;
+        }
         if let Some(y) = self.chunk_position.y.read() {
-            self.chunk_position.y.write(y + self.nudge);
+            self.chunk_position.y.write_untracked(y + self.nudge);
         }
         if let Some(y) = self.torrent_chunk_position.y.read() {
-            self.torrent_chunk_position.y.write(y + self.nudge);
+            self.torrent_chunk_position.y.write_untracked(y + self.nudge);
         }
     }
 
     fn nudge_down(&mut self) {
+        if session_locked() {
+            return;
+        }
         if let Some(y) = self.chunk_position.y.read() {
-            self.chunk_position.y.write(y - self.nudge);
+            self.chunk_position.y.write_untracked(y - self.nudge);
         }
         if let Some(y) = self.torrent_chunk_position.y.read() {
-            self.torrent_chunk_position.y.write(y - self.nudge);
+            self.torrent_chunk_position.y.write_untracked(y - self.nudge);
         }
     }
 }
@@ -147,3 +158,44 @@ pub(crate) fn save_position(
         key_save,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use libeldenring::prelude::{FakeMemory, PointerChain};
+
+    use super::*;
+
+    fn position(mem: &Arc<FakeMemory>, addr: usize, values: [f32; 5]) -> ErPosition {
+        mem.map_value(addr, values);
+        mem.map_value(addr + 0x20, 0u32);
+        let chain = |offset: usize| PointerChain::with_source(mem.clone(), &[addr + offset]);
+        ErPosition {
+            x: chain(0x0),
+            y: chain(0x4),
+            z: chain(0x8),
+            angle1: chain(0xc),
+            angle2: chain(0x10),
+            map_id: Some(chain(0x20).cast()),
+        }
+    }
+
+    #[test]
+    fn test_load_translates_into_chunk_space() {
+        let mem = Arc::new(FakeMemory::new());
+        let global = position(&mem, 0x1000, [100., 200., 300., 0., 0.]);
+        let chunk = position(&mem, 0x2000, [10., 20., 30., 1., 2.]);
+        let torrent = position(&mem, 0x3000, [11., 21., 31., 0., 0.]);
+
+        let mut save_position = SavePosition::new(global.clone(), chunk.clone(), torrent, 0.);
+        save_position.save();
+        assert!(save_position.is_valid());
+
+        global.write([150., 190., 300., 0., 0.]);
+        chunk.write([60., 10., 30., 3., 4.]);
+        save_position.load();
+
+        assert_eq!(chunk.read(), Some([10., 20., 30., 1., 2.]));
+    }
+}
@@ -19,7 +19,7 @@ impl ReadWrite for Quitout {
     }
 
     fn write(&mut self) {
-        self.ptr.write(1);
+        self.ptr.write_untracked(1);
     }
 
     fn label(&self) -> &str {
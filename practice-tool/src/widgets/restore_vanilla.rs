@@ -0,0 +1,144 @@
+use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, igSetNextWindowPos, ImVec2};
+use imgui::{Condition, WindowFlags};
+use libeldenring::prelude::*;
+use practice_tool_core::crossbeam_channel::Sender;
+use practice_tool_core::key::Key;
+use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
+
+const POPUP_TAG: &str = "##restore-vanilla";
+const NOT_JOURNALED: &str = "Player position: not journaled";
+
+#[derive(Debug)]
+pub(crate) struct RestoreVanilla {
+    label: String,
+    label_restore: String,
+    hotkey: Option<Key>,
+    dirty: Vec<String>,
+    logs: Vec<String>,
+}
+
+impl RestoreVanilla {
+    pub(crate) fn new(hotkey: Option<Key>) -> Self {
+        let label_restore = match hotkey {
+            Some(k) => format!("Restore vanilla state ({k})"),
+            None => "Restore vanilla state".to_string(),
+        };
+
+        RestoreVanilla {
+            label: "Modified memory".to_string(),
+            label_restore,
+            hotkey,
+            dirty: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+
+    fn restore(&mut self) {
+        // Frozen values would be written again right after being restored.
+        let unfrozen = FREEZER.clear();
+        if !unfrozen.is_empty() {
+            self.logs.push(format!("Unfroze {}", unfrozen.join(", ")));
+        }
+
+        match JOURNAL.lock().restore_all() {
+            Ok(count) => self.logs.push(format!("Restored {count} memory locations")),
+            Err(addrs) => {
+                let addrs = addrs.iter().map(|a| format!("{a:#x}")).collect::<Vec<_>>();
+                self.logs.push(format!("Couldn't restore {}", addrs.join(", ")));
+            },
+        }
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        self.dirty = JOURNAL.lock().dirty().iter().map(DirtyEntry::describe).collect();
+    }
+}
+
+impl Widget for RestoreVanilla {
+    fn render(&mut self, ui: &imgui::Ui) {
+        let scale = scaling_factor(ui);
+        let button_width = BUTTON_WIDTH * scale;
+        let button_height = BUTTON_HEIGHT;
+
+        if ui.button_with_size(&self.label_restore, [button_width, button_height]) {
+            self.restore();
+        }
+
+        let (x, y) = unsafe {
+            let mut wnd_pos = ImVec2::default();
+            igGetWindowPos(&mut wnd_pos);
+            (igGetCursorPosX() + wnd_pos.x, igGetCursorPosY() + wnd_pos.y)
+        };
+
+        if ui.button_with_size(&self.label, [button_width, button_height]) {
+            self.refresh();
+            ui.open_popup(POPUP_TAG);
+        }
+
+        unsafe {
+            igSetNextWindowPos(
+                ImVec2::new(x + 200. * scale, y),
+                Condition::Always as i8 as _,
+                ImVec2::new(0., 0.),
+            )
+        };
+
+        if let Some(_token) = ui
+            .modal_popup_config(POPUP_TAG)
+            .flags(
+                WindowFlags::NO_TITLE_BAR
+                    | WindowFlags::NO_RESIZE
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_SCROLLBAR,
+            )
+            .begin_popup()
+        {
+            ui.child_window("##restore-vanilla-list").size([400., 200.]).build(|| {
+                if self.dirty.is_empty() {
+                    ui.text("Nothing is modified");
+                }
+                for line in &self.dirty {
+                    ui.text(line);
+                }
+                ui.text_disabled(NOT_JOURNALED);
+            });
+
+            if ui.button_with_size("Refresh", [400., button_height]) {
+                self.refresh();
+            }
+
+            if ui.button_with_size(&self.label_restore, [400., button_height]) {
+                self.restore();
+            }
+
+            if ui.button_with_size("Close", [400., button_height]) {
+                ui.close_current_popup();
+            }
+        }
+    }
+
+    fn interact(&mut self, ui: &imgui::Ui) {
+        if ui.is_any_item_active() {
+            return;
+        }
+
+        if self.hotkey.map(|k| k.is_pressed(ui)).unwrap_or(false) {
+            self.restore();
+        }
+    }
+
+    fn action(&mut self) {
+        self.restore();
+    }
+
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+}
+
+pub(crate) fn restore_vanilla(hotkey: Option<Key>) -> Box<dyn Widget> {
+    Box::new(RestoreVanilla::new(hotkey))
+}
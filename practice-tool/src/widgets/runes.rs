@@ -3,6 +3,8 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::store_value::{ReadWrite, StoreValue};
 use practice_tool_core::widgets::Widget;
 
+use crate::practice_tool::session_locked;
+
 struct Runes {
     ptr: PointerChain<u32>,
     current: u32,
@@ -27,6 +29,9 @@ impl ReadWrite for Runes {
     }
 
     fn write(&mut self) {
+        if session_locked() {
+            return;
+        }
         self.ptr.write(self.current + self.amount);
     }
 
@@ -1,18 +1,30 @@
 use std::f32::consts::PI;
 use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
 use imgui::sys::{igGetCursorPosX, igGetCursorPosY, igGetWindowPos, ImVec2};
 use imgui::{ProgressBar, StyleColor};
+use libeldenring::combat_log::{CombatLog, Hit, Sample, STATUS_NAMES};
 use libeldenring::memedit::PointerChain;
 use libeldenring::pointer_chain;
 use libeldenring::prelude::Position as ErPosition;
+use practice_tool_core::crossbeam_channel::Sender;
 use practice_tool_core::key::Key;
 use practice_tool_core::widgets::Widget;
 use windows::Win32::System::Memory::{
     VirtualAlloc, MEM_COMMIT, MEM_RESERVE, PAGE_EXECUTE_READWRITE,
 };
 
-#[derive(Debug, Default)]
+/// Address of the locked-on entity, or 0. Published for other widgets.
+pub(crate) static LOCKED_TARGET: AtomicU64 = AtomicU64::new(0);
+
+/// Hits kept for the export.
+const COMBAT_LOG_CAPACITY: usize = 1000;
+/// Hits shown under the target info.
+const COMBAT_LOG_SHOWN: usize = 5;
+
+#[derive(Debug, Default, Clone)]
 struct EnemyInfo {
     hp: u32,
     max_hp: u32,
@@ -25,7 +37,7 @@ struct EnemyInfo {
     position: EntityPosition,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[repr(C)]
 struct EnemyResistances {
     poison: u32,
@@ -44,7 +56,7 @@ struct EnemyResistances {
     mad_max: u32,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[repr(C)]
 struct PoiseMeter {
     poise: f32,
@@ -53,7 +65,7 @@ struct PoiseMeter {
     poise_time: f32,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[repr(C)]
 struct EntityPosition {
     angle1: f32,
@@ -86,6 +98,12 @@ pub(crate) struct Target {
     player_position: ErPosition,
 
     distance_text: String,
+
+    combat_log: CombatLog,
+    start: Instant,
+    /// The target as of the last [`Target::sample`], for rendering.
+    info: Option<EnemyInfo>,
+    logs: Vec<String>,
 }
 
 unsafe impl Send for Target {}
@@ -129,6 +147,11 @@ impl Target {
             player_position,
 
             distance_text: String::new(),
+
+            combat_log: CombatLog::new(COMBAT_LOG_CAPACITY),
+            start: Instant::now(),
+            info: None,
+            logs: Vec::new(),
         }
     }
 
@@ -156,6 +179,44 @@ impl Target {
         Some(EnemyInfo { hp, max_hp, mp, max_mp, sp, max_sp, res, poise, position })
     }
 
+    /// Reads the target and records any hit it took since the last frame.
+    /// Runs every frame from [`Widget::interact`], whether or not the
+    /// target info is shown.
+    fn sample(&mut self) {
+        LOCKED_TARGET.store(self.entity_addr, Ordering::Relaxed);
+        self.info = self.get_data();
+        let Some(info) = &self.info else {
+            self.combat_log.lose_target();
+            return;
+        };
+
+        let res = &info.res;
+        self.combat_log.sample(Sample {
+            time: self.start.elapsed().as_secs_f64(),
+            entity: self.entity_addr,
+            hp: info.hp,
+            max_hp: info.max_hp,
+            poise: info.poise.poise,
+            buildup: [res.poison, res.rot, res.bleed, res.blight, res.frost, res.sleep, res.mad],
+        });
+    }
+
+    fn export_combat_log(&mut self, json: bool) {
+        let result = crate::util::get_dll_path()
+            .ok_or_else(|| "Could not find the DLL path".to_string())
+            .and_then(|path| {
+                let (path, contents) = if json {
+                    (path.with_file_name("combat_log.json"), self.combat_log.to_json())
+                } else {
+                    (path.with_file_name("combat_log.csv"), self.combat_log.to_csv()?)
+                };
+                std::fs::write(&path, contents)
+                    .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+                Ok(format!("Exported the combat log to {}", path.display()))
+            });
+        self.logs.push(result.unwrap_or_else(|e| e));
+    }
+
     fn enable(&mut self) {
         // Unwraps are valid because the addresses are static.
 
@@ -190,9 +251,35 @@ impl Target {
     fn disable(&mut self) {
         self.detour_addr.write(self.detour_orig_data);
         self.is_enabled = false;
+        self.info = None;
+        LOCKED_TARGET.store(0, Ordering::Relaxed);
+    }
+
+    /// Catches up with the detour being undone elsewhere, e.g. when the
+    /// vanilla state is restored.
+    fn sync(&mut self) {
+        if self.is_enabled && self.detour_addr.read() == Some(self.detour_orig_data) {
+            self.is_enabled = false;
+            self.entity_addr = 0;
+            self.info = None;
+            LOCKED_TARGET.store(0, Ordering::Relaxed);
+        }
     }
 }
 
+fn describe_hit(hit: &Hit) -> String {
+    let mut text = format!("{:>6} dmg {:>6.1} poise", hit.damage, hit.poise_damage);
+    for (name, &buildup) in STATUS_NAMES.iter().zip(&hit.buildup) {
+        if buildup > 0 {
+            write!(text, " +{buildup} {name}").unwrap();
+        }
+    }
+    if let Some(t) = hit.since_last_hit {
+        write!(text, " ({t:.2}s)").unwrap();
+    }
+    text
+}
+
 #[inline]
 fn u32_to_array(val: u32) -> [u8; 4] {
     let mut buf = [0u8; 4];
@@ -217,6 +304,7 @@ fn u64_to_array(val: u64) -> [u8; 8] {
 
 impl Widget for Target {
     fn render(&mut self, ui: &imgui::Ui) {
+        self.sync();
         let mut state = self.is_enabled;
 
         if ui.checkbox(&self.label, &mut state) {
@@ -226,15 +314,32 @@ impl Widget for Target {
                 self.disable();
             }
         }
+
+        if !self.is_enabled {
+            return;
+        }
+
+        if ui.button("Export combat log (CSV)") {
+            self.export_combat_log(false);
+        }
+        ui.same_line();
+        if ui.button("JSON") {
+            self.export_combat_log(true);
+        }
+        ui.same_line();
+        if ui.button("Clear") {
+            self.combat_log.clear();
+        }
     }
 
     fn render_closed(&mut self, ui: &imgui::Ui) {
+        self.sync();
         if !self.is_enabled {
             return;
         }
 
         let Some(EnemyInfo { hp, max_hp, mp, max_mp, sp, max_sp, res, poise, position }) =
-            self.get_data()
+            self.info.clone()
         else {
             if self.is_enabled {
                 ui.text("No enemy locked on")
@@ -375,9 +480,18 @@ impl Widget for Target {
             draw_list.add_line([x, y], [x + dx, y + dy], [1.0, 1.0, 1.0]).build();
             ui.new_line();
         }
+
+        for hit in self.combat_log.hits().rev().take(COMBAT_LOG_SHOWN) {
+            ui.text(describe_hit(hit));
+        }
     }
 
     fn interact(&mut self, ui: &imgui::Ui) {
+        self.sync();
+        if self.is_enabled {
+            self.sample();
+        }
+
         if ui.is_any_item_active() {
             return;
         }
@@ -387,6 +501,12 @@ impl Widget for Target {
         }
     }
 
+    fn log(&mut self, tx: Sender<String>) {
+        for log in self.logs.drain(..) {
+            tx.send(log).ok();
+        }
+    }
+
     fn action(&mut self) {
         if self.is_enabled {
             self.disable();
@@ -8,6 +8,7 @@ use practice_tool_core::key::Key;
 use practice_tool_core::widgets::{scaling_factor, Widget, BUTTON_HEIGHT, BUTTON_WIDTH};
 
 use super::string_match;
+use crate::practice_tool::session_locked;
 
 type WarpFunc = extern "system" fn(u64, u64, u32);
 
@@ -48,6 +49,9 @@ impl Warp {
     }
 
     fn warp(&mut self) {
+        if session_locked() {
+            return;
+        }
         let warp_fn: WarpFunc = unsafe { mem::transmute(self.warp_ptr) };
         let arg1 = self.arg1.read();
         let arg2 = self.arg2.read();
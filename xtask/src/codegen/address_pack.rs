@@ -0,0 +1,38 @@
+use std::fs;
+
+use address_scan::aob;
+use address_scan::pack::{sha256_hex, AddressPack, AddressPackEntry, ADDRESS_PACK_FILE_NAME};
+use address_scan::pe::PeFile;
+use anyhow::Context;
+
+use super::aob_scans::patches_paths;
+use crate::{project_root, Result};
+
+/// Scans every patch in `ER_PATCHES_PATH` and writes the base addresses to
+/// an address pack, keyed by game version and executable hash.
+pub(crate) fn codegen() -> Result<()> {
+    let mut entries = Vec::new();
+
+    for path in patches_paths() {
+        let bytes = fs::read(&path).with_context(|| format!("Couldn't read {path:?}"))?;
+        let file = PeFile::parse(&bytes).map_err(anyhow::Error::msg)?;
+        let version = file.file_version().map_err(anyhow::Error::msg)?;
+
+        let scan = aob::scan_file(&file);
+        if !scan.missing.is_empty() {
+            eprintln!("{path:?}: skipping, not found: {}", scan.missing.join(", "));
+            continue;
+        }
+
+        entries.push(AddressPackEntry::new(version, Some(sha256_hex(&bytes)), &scan.found));
+    }
+
+    entries.sort_by_key(|entry| entry.version().ok());
+
+    fs::write(
+        project_root().join("lib/data").join(ADDRESS_PACK_FILE_NAME),
+        AddressPack::new(entries).to_json(),
+    )?;
+
+    Ok(())
+}
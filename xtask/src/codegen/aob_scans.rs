@@ -1,10 +1,16 @@
-use std::env;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::{env, fs};
 
-use practice_tool_tasks::codegen::{self, aob_direct, aob_indirect_twice};
+use address_scan::aob::{self, AOBS};
+use address_scan::pe::PeFile;
+use anyhow::Context;
+use heck::ToSnakeCase;
 use textwrap::dedent;
 
-fn patches_paths() -> impl Iterator<Item = PathBuf> {
+use crate::Result;
+
+pub(super) fn patches_paths() -> impl Iterator<Item = PathBuf> {
     let base_path = PathBuf::from(
         env::var("ER_PATCHES_PATH").unwrap_or_else(|_| panic!("{}", dedent(r"
             ER_PATCHES_PATH environment variable undefined.
@@ -31,279 +37,120 @@ fn base_addresses_rs_path() -> PathBuf {
         .join("base_addresses.rs")
 }
 
-pub(crate) fn get_base_addresses() {
-    let aobs = &[
-        aob_indirect_twice(
-            "BulletMan",
-            &["48 8B 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8D 44 24 ?? 48 89 44 24 ?? 48 89 7C 24 ?? \
-               C7 44 24 ?? ?? ?? ?? ?? 48"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "ChrDbgFlags",
-            &["?? 80 3D ?? ?? ?? ?? 00 0F 85 ?? ?? ?? ?? 32 C0 48"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSFD4VirtualMemoryFlag",
-            &["48 8B 3D ?? ?? ?? ?? 48 85 FF 74 ?? 48 8B 49"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSFlipper",
-            &["48 8B 0D ?? ?? ?? ?? 80 BB D7 00 00 00 00 0F 84 CE 00 00 00 48 85 C9 75 2E"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSLuaEventManager",
-            &[
-                "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 ?? 41 BE 01 00 00 00 44 89 74 24",
-                "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 ?? 41 BE 01 00 00 00 44 89 75 83",
-            ],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSMenuMan",
-            &["E8 ?? ?? ?? ?? 4C 8B F8 48 85 C0 0F 84 ?? ?? ?? ?? 48 8B 0D"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSMenuManImp",
-            &["48 8B 0D ?? ?? ?? ?? 48 8B 49 08 E8 ?? ?? ?? ?? 48 8B D0 48 8B CE E8 ?? ?? ?? ??"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSNetMan",
-            &["48 8B 0D ?? ?? ?? ?? 48 85 C9 74 5E 48 8B 89 ?? ?? ?? ?? B2 01"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSRegulationManager",
-            &["48 8B 0D ?? ?? ?? ?? 48 85 C9 74 0B 4C 8B C0 48 8B D7"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "CSSessionManager",
-            &["48 8B 05 ?? ?? ?? ?? 33 F6 89 74 24 20 BF 01 00 00 00"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "DamageCtrl",
-            &["48 8B 05 ?? ?? ?? ?? 49 8B D9 49 8B F8 48 8B F2 48 85 C0 75 2E"],
-            3,
-            7,
-            true,
-        ),
-        // aob_indirect_twice("FieldArea", "48 8B 3D ?? ?? ?? ?? 48 85 FF 0F 84 ?? ?? ?? ?? 45 38
-        // 66 34",3,7),
-        aob_indirect_twice(
-            "FieldArea",
-            &["48 8B 0D ?? ?? ?? ?? 48 ?? ?? ?? 44 0F B6 61 ?? E8 ?? ?? ?? ?? 48 63 87 ?? ?? ?? \
-               ?? 48 ?? ?? ?? 48 85 C0"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "GameDataMan",
-            &["48 8B 05 ?? ?? ?? ?? 48 85 C0 74 05 48 8B 40 58 C3 C3"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "GameMan",
-            &["48 8B 1D ?? ?? ?? ?? 48 8B F8 48 85 DB 74 18 4C 8B 03"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "GlobalPos",
-            &["48 8B 3D ?? ?? ?? ?? 33 DB 49 8B F0 4C 8B F1 48 85 FF"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "GroupMask",
-            &["?? 80 3D ?? ?? ?? ?? 00 0F 10 00 0F 11 45 D0 0F 84 ?? ?? ?? ?? 80 3D"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "HitIns",
-            &["48 8B 05 ?? ?? ?? ?? 48 8D 4C 24 ?? 48 89 4c 24 ?? 0F 10 44 24 70"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "HitInsHitboxOffset",
-            &["0F B6 25 ?? ?? ?? ?? 44 0F B6 3D ?? ?? ?? ?? E8 ?? ?? ?? ?? 0F B6 F8"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "MapItemMan",
-            &["48 8B 0D ?? ?? ?? ?? C7 44 24 50 FF FF FF FF C7 45 A0 FF FF FF FF 48 85 C9 75 2E"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "MenuManIns",
-            &["48 8b 0d ?? ?? ?? ?? 48 8b 53 08 48 8b 92 d8 00 00 00 48 83 c4 20 5b"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "MsgRepository",
-            &["48 8B 3D ?? ?? ?? ?? 44 0F B6 30 48 85 FF 75 26"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "SoloParamRepository",
-            &["48 8B 0D ?? ?? ?? ?? 48 85 C9 0F 84 ?? ?? ?? ?? 45 33 C0 BA 8D 00 00 00 E8"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "WorldChrMan",
-            &[
-                "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 0F 48 39 88 ?? ?? ?? ?? 75 06 89 B1 5C 03 00 00 \
-                 0F 28 05 ?? ?? ?? ?? 4C 8D 45 E7",
-                "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 0F 48 39 88",
-            ],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "WorldChrManDbg",
-            &["48 8B 0D ?? ?? ?? ?? 89 5C 24 20 48 85 C9 74 12 B8 ?? ?? ?? ?? 8B D8"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "WorldChrManImp",
-            &[
-                "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 0F 48 39 88 ?? ?? ?? ?? 75 06 89 B1 5C 03 00 00 \
-                 0F 28 05 ?? ?? ?? ?? 4C 8D 45 E7",
-                "48 8B 35 ?? ?? ?? ?? 48 85 F6 ?? ?? BB 01 00 00 00 89 5C 24 20 48 8B B6",
-            ],
-            3,
-            7,
-            true,
-        ),
-        aob_direct(
-            "FuncItemSpawn",
-            &["48 8B C4 56 57 41 56 48 81 EC ?? ?? ?? ?? 48 C7 44 24 ?? ?? ?? ?? ?? 48 89 58 ?? \
-               48 89 68 ?? 48 8B 05 ?? ?? ?? ?? 48 33 C4 48 89 84 24 ?? ?? ?? ?? 41 0F B6 F9"],
-            true,
-        ),
-        aob_direct(
-            "FuncItemInject",
-            &[
-                "40 55 56 57 41 54 41 55 41 56 41 57 48 8D 6C 24 B0 48 81 EC 50 01 00 00 48 C7 45 \
-                 C0 FE FF FF FF", // 1.02
-                "40 55 56 57 41 54 41 55 41 56 41 57 48 8d ac 24 ?? ?? ?? ?? 48 81 ec ?? ?? ?? ?? \
-                 48 c7 45 ?? ?? ?? ?? ?? 48 89 9c 24 ?? ?? ?? ?? 48 8b 05 ?? ?? ?? ?? 48 33 c4 48 \
-                 89 85 ?? ?? ?? ?? 44 89 4c 24", // 1.03
-                "40 55 56 57 41 54 41 55 41 56 41 57 48 8D AC 24 70 FF FF FF 48 81 EC 90 01 00 00 \
-                 48 C7 45 C8 FE FF FF FF 48 89 9C 24 D8",
-            ],
-            true,
-        ), // 1.04
-        aob_direct(
-            "FuncRemoveIntroScreens",
-            &["74 53 48 8B 05 ?? ?? ?? ?? 48 85 C0 75 2E 48 8D 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 4C \
-               8B C8"],
-            true,
-        ),
-        aob_direct("FuncDbgActionForce", &["48 8B 41 08 0F BE 80 ?? E9 00 00 48 8D 64"], true),
-        aob_direct("LuaWarp", &["C3 ?? ?? ?? ?? ?? ?? 57 48 83 EC ?? 48 8B FA 44"], true),
-        aob_direct("CurrentTarget", &["48 8B 48 08 49 89 8D ?? ?? ?? ?? 49 8B CE E8"], true),
-        aob_indirect_twice(
-            "FuncCheckGraces",
-            &["0F B6 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 44 8B E0"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "BaseFPS",
-            &["48 8B 0D ?? ?? ?? ?? 48 85 C9 75 2E 48 8D 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 4C 8B C8 \
-               4C 8D 05 ?? ?? ?? ?? BA ?? ?? ?? ?? 48 8D 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B 0D \
-               ?? ?? ?? ?? 48 83 C1 20 E8 ?? ?? ?? ??"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "BaseAnim",
-            &["48 89 0D ?? ?? ?? ?? 8D 46 9C 83 F8 21 77 37 83 FE 66 74 26 83 FE 70 74 15 83 FE \
-               7C 0F 85 ?? ?? ?? ?? 48 8D BB ?? ?? ?? ?? E9 ?? ?? ?? ?? 48 8D BB ?? ?? ?? ??"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "DbgEventManOff",
-            &["48 8B 0D ?? ?? ?? ?? 48 85 C9 74 13 83 CF 20 89 7D 87 E8 ?? ?? ?? ?? 84 C0 74 04 \
-               B0 01 EB 02 32 C0 40 F6 C7 20 74 06"],
-            3,
-            7,
-            true,
-        ),
-        aob_indirect_twice(
-            "EventPatch1",
-            &["E8 ?? ?? ?? ?? 84 C0 74 06 E8 ?? ?? ?? ?? 90 48 8B C7"],
-            1,
-            5,
-            true,
-        ),
-        aob_indirect_twice(
-            "EventPatch2",
-            &["E8 ?? ?? ?? ?? 84 C0 74 06 E8 ?? ?? ?? ?? 90 48 8B C7"],
-            10,
-            14,
-            true,
-        ),
-        aob_direct(
-            "FontPatch",
-            &["48 89 5C 24 10 55 56 57 41 54 41 55 41 56 41 57 48 8D 6C 24 ?? 48 81 EC ?? ?? ?? \
-               ?? 0F 29 B4 24 ?? ?? ?? ??"],
-            true,
-        ),
-    ];
+/// Scans every patch in `ER_PATCHES_PATH` with the same code the runtime
+/// uses for unknown versions, and writes the compiled-in tables.
+pub(crate) fn get_base_addresses() -> Result<()> {
+    let mut versions = BTreeMap::new();
+
+    for path in patches_paths() {
+        let bytes = fs::read(&path).with_context(|| format!("Couldn't read {path:?}"))?;
+        let file = PeFile::parse(&bytes).map_err(anyhow::Error::msg)?;
+        let version = file.file_version().map_err(anyhow::Error::msg)?;
+
+        let scan = aob::scan_file(&file);
+        for name in &scan.missing {
+            eprintln!("{path:?}: {name} not found");
+        }
+
+        versions.insert(version, scan.found);
+    }
+
+    fs::write(base_addresses_rs_path(), base_addresses_rs(&versions))?;
+
+    Ok(())
+}
+
+fn base_addresses_rs(versions: &BTreeMap<(u32, u32, u32), BTreeMap<&str, usize>>) -> String {
+    let fields = AOBS.iter().map(|aob| (aob.name, aob.name.to_snake_case())).collect::<Vec<_>>();
+    let names = versions
+        .keys()
+        .map(|&(major, minor, patch)| format!("{major}_{minor:02}_{patch}"))
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut line = |s: &str| {
+        out.push_str(s);
+        out.push('\n');
+    };
+
+    line("// **********************************");
+    line("// *** AUTOGENERATED, DO NOT EDIT ***");
+    line("// **********************************");
+
+    line("#[derive(Debug)]");
+    line("pub struct BaseAddresses {");
+    for (_, field) in &fields {
+        line(&format!("    pub {field}: usize,"));
+    }
+    line("}");
+    line("");
+
+    line("impl BaseAddresses {");
+    line("    pub fn with_module_base_addr(self, base: usize) -> BaseAddresses {");
+    line("        BaseAddresses {");
+    for (_, field) in &fields {
+        line(&format!("            {field}: self.{field} + base,"));
+    }
+    line("        }");
+    line("    }");
+    line("}");
+    line("");
+
+    line("#[derive(Clone, Copy)]");
+    line("pub enum Version {");
+    for name in &names {
+        line(&format!("    V{name},"));
+    }
+    line("}");
+    line("");
+
+    line("impl TryFrom<(u32, u32, u32)> for Version {");
+    line("    type Error = ();");
+    line("");
+    line("    fn try_from(v: (u32, u32, u32)) -> Result<Self, ()> {");
+    line("        match v {");
+    for (&(major, minor, patch), name) in versions.keys().zip(&names) {
+        line(&format!("            ({major}, {minor}, {patch}) => Ok(Version::V{name}),"));
+    }
+    line("            (maj, min, patch) => {");
+    line("                log::error!(\"Unrecognized version {maj}.{min:02}.{patch}\");");
+    line("                Err(())");
+    line("            },");
+    line("        }");
+    line("    }");
+    line("}");
+    line("");
+
+    line("impl From<Version> for (u32, u32, u32) {");
+    line("    fn from(v: Version) -> Self {");
+    line("        match v {");
+    for (&(major, minor, patch), name) in versions.keys().zip(&names) {
+        line(&format!("            Version::V{name} => ({major}, {minor}, {patch}),"));
+    }
+    line("        }");
+    line("    }");
+    line("}");
+    line("");
+
+    line("impl From<Version> for BaseAddresses {");
+    line("    fn from(v: Version) -> Self {");
+    line("        match v {");
+    for name in &names {
+        line(&format!("            Version::V{name} => BASE_ADDRESSES_{name},"));
+    }
+    line("        }");
+    line("    }");
+    line("}");
+
+    for (found, name) in versions.values().zip(&names) {
+        line("");
+        line(&format!("pub const BASE_ADDRESSES_{name}: BaseAddresses = BaseAddresses {{"));
+        for (aob, field) in &fields {
+            let addr = found.get(aob).copied().unwrap_or(0);
+            line(&format!("    {field}: {addr:#x},"));
+        }
+        line("};");
+    }
 
-    codegen::codegen_base_addresses(base_addresses_rs_path(), patches_paths(), aobs)
+    out
 }
@@ -1,11 +1,13 @@
 use anyhow::Result;
 
+mod address_pack;
 mod aob_scans;
 mod item_ids;
 mod params;
 
 pub(crate) fn codegen() -> Result<()> {
-    aob_scans::get_base_addresses();
+    aob_scans::get_base_addresses()?;
+    address_pack::codegen()?;
     params::codegen()?;
     item_ids::codegen()?;
 
@@ -1,6 +1,8 @@
+import json
 import pandas as pd
 import re
 import sys
+import xml.etree.ElementTree as ET
 from glob import glob
 from pathlib import Path
 from itertools import zip_longest
@@ -67,18 +69,80 @@ def build_param_layouts(paramdex_path, xtask_path):
     assert(xml_files.keys() == param_names.keys())
 
     return [
-        ParamLayout(name=param_names[i], layout=xml_files[i])
+        ParamLayout(
+            name=param_names[i],
+            layout=xml_files[i],
+            meta=paramdex_path / 'ER/Meta' / xml_files[i].name,
+        )
         for i in sorted(param_names.keys())
     ]
 
 
+def read_meta(path):
+    # English names, descriptions and enums -- Paramdex's meta files, when present
+    if not path.exists():
+        return {}, {}
+
+    root = ET.parse(path).getroot()
+    fields = dict(
+        (field.tag, field.attrib)
+        for field in root.findall('./Field/*')
+    )
+    enums = dict(
+        (enum.get('Name'), [
+            {'value': int(option.get('Value')), 'name': option.get('Name')}
+            for option in enum.findall('./Option')
+        ])
+        for enum in root.findall('./Enums/Enum')
+    )
+    return fields, enums
+
+
+def build_param_meta(layouts):
+    enums = {}
+    params = {}
+    for l in layouts:
+        enums.update(l.enums)
+        fields = dict(
+            (name, meta)
+            for field in l.fields
+            for name, meta in field.meta()
+            if meta
+        )
+        if fields:
+            params[l.name] = fields
+
+    # Only keep the enums that are used, and the fields whose enum is known
+    used = set(
+        meta['enum']
+        for fields in params.values()
+        for meta in fields.values()
+        if 'enum' in meta
+    )
+    for fields in params.values():
+        for meta in fields.values():
+            if meta.get('enum') not in enums:
+                meta.pop('enum', None)
+
+    # An empty table means the Paramdex checkout is incomplete
+    if not params:
+        raise ValueError('No param metadata found, check the Paramdex checkout')
+
+    return {
+        'enums': dict((k, v) for k, v in sorted(enums.items()) if k in used),
+        'params': params,
+    }
+
+
 class ParamLayout:
-    def __init__(self, name, layout):
+    def __init__(self, name, layout, meta):
         self.name = name
         # self.name = to_camel_case(pd.read_xml(layout)['ParamType'][0])
         self.name_snake_case = to_snake_case(name)
+        field_meta, self.enums = read_meta(meta)
         self.fields = ParamLayout.dedup_fields(ParamLayout.group_bitfields([
-            Field(i) for i in pd.read_xml(layout, xpath='./Fields/*')['Def']
+            Field(i, field_meta)
+            for i in pd.read_xml(layout, xpath='./Fields/*').to_dict('records')
         ]))
 
     def get_struct(self):
@@ -143,6 +207,12 @@ class Bitfield:
             for idx, flag in self.fields
         ) + '\n        ' + field_tpl
 
+    def meta(self):
+        return [
+            (ParamLayout.fix_name(flag.name), flag.field_meta)
+            for _, flag in self.fields
+        ]
+
     def rename(self, idx):
         self.name = self.name + f'_{idx}'
 
@@ -165,7 +235,8 @@ class Field:
         'dummy8': 'u8',
     }
 
-    def __init__(self, definition):
+    def __init__(self, row, field_meta):
+        definition = row['Def']
         if matches := Field.def_array_re.match(definition):
             self.kind = 'array'
             self.name = matches.group(2)
@@ -193,18 +264,49 @@ class Field:
         else:
             raise ValueError(f'Couldn\'t parse: {definition}')
 
+        self.field_meta = Field.read_field_meta(row, field_meta.get(self.name, {}))
+
+    @staticmethod
+    def read_field_meta(row, field_meta):
+        def get(key):
+            value = row.get(key)
+            return None if pd.isna(value) else value
+
+        meta = {
+            'display_name': field_meta.get('AltName') or get('DisplayName'),
+            'description': field_meta.get('Wiki') or get('Description'),
+            'min': get('Minimum'),
+            'max': get('Maximum'),
+            'enum': field_meta.get('Enum') or get('Enum'),
+        }
+        for key in ('min', 'max'):
+            if meta[key] is not None:
+                meta[key] = float(meta[key])
+        return dict((k, v) for k, v in meta.items() if v is not None)
+
     def format(self):
         return FIELD_TEMPLATE.format(
             field_name=ParamLayout.fix_name(to_snake_case(self.name)),
             field_type=self.type
         )
 
+    def meta(self):
+        # Arrays aren't visited
+        if self.kind == 'array':
+            return []
+        return [(ParamLayout.fix_name(to_snake_case(self.name)), self.field_meta)]
+
     def rename(self, idx):
         self.name = self.name + f'_{idx}'
             
 
 if __name__ == '__main__':
     layouts = build_param_layouts(sys.argv[1], sys.argv[2])
+
+    if '--meta' in sys.argv[3:]:
+        print(json.dumps(build_param_meta(layouts), indent=2, ensure_ascii=False))
+        sys.exit(0)
+
     print('// **********************************')
     print('// *** AUTOGENERATED, DO NOT EDIT ***')
     print('// **********************************')
@@ -10,7 +10,8 @@ use crate::{project_root, Result};
 
 pub(crate) fn codegen() -> Result<()> {
     checkout_paramdex()?;
-    run_python_script()?;
+    run_python_script(&[], "lib/libeldenring/src/codegen/param_data.rs")?;
+    run_python_script(&["--meta"], "lib/libeldenring/src/codegen/param_meta.json")?;
     codegen_param_names(
         "target/Paramdex/ER/Names",
         "lib/libeldenring/src/codegen/param_names.json",
@@ -19,7 +20,7 @@ pub(crate) fn codegen() -> Result<()> {
     Ok(())
 }
 
-fn run_python_script() -> Result<()> {
+fn run_python_script(args: &[&str], output: &str) -> Result<()> {
     let python = env::var("PYTHON").unwrap_or_else(|_| "python".to_string());
     let cmd = Command::new(python)
         .current_dir(project_root().join("target"))
@@ -28,6 +29,7 @@ fn run_python_script() -> Result<()> {
             project_root().join("target/Paramdex"),
             project_root().join("xtask"),
         ])
+        .args(args)
         .output()
         .context("python")?;
 
@@ -35,8 +37,7 @@ fn run_python_script() -> Result<()> {
         bail!("python codegen failed");
     }
 
-    File::create(project_root().join("lib/libeldenring/src/codegen/param_data.rs"))?
-        .write_all(&cmd.stdout)?;
+    File::create(project_root().join(output))?.write_all(&cmd.stdout)?;
 
     Ok(())
 }
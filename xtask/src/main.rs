@@ -1,7 +1,10 @@
 use std::ffi::OsStr;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 use std::{env, fs, iter};
 
+use address_scan::pack::ADDRESS_PACK_FILE_NAME;
+use address_scan::pe::PeFile;
 use anyhow::{bail, Context, Result};
 use practice_tool_tasks::{
     cargo_command, project_root, steam_command, target_path, Distribution, FileInstall,
@@ -25,6 +28,7 @@ fn main() -> Result<()> {
     match task.as_deref() {
         Some("dist") => dist()?,
         Some("codegen") => codegen::codegen()?,
+        Some("identify") => identify(env::args().skip(2))?,
         Some("inject") => inject(env::args().skip(1).map(String::from))?,
         Some("run") => run()?,
         Some("install") => install()?,
@@ -43,6 +47,7 @@ Tasks:
 run ............. compile and start the practice tool
 dist ............ build distribution artifacts
 codegen ......... generate Rust code: parameters, base addresses, ...
+identify <exe> .. print the game version of one or more eldenring.exe files
 inject <args> ... standalone dll inject
 install ......... install standalone dll to $ER_PATH
 uninstall ....... uninstall standalone dll from $ER_PATH
@@ -51,6 +56,31 @@ help ............ print this help
     );
 }
 
+fn identify(paths: impl Iterator<Item = String>) -> Result<()> {
+    let paths = paths.collect::<Vec<_>>();
+    if paths.is_empty() {
+        bail!("Usage: cargo xtask identify <path to eldenring.exe>...");
+    }
+
+    for path in paths {
+        let bytes = fs::read(&path).with_context(|| format!("Couldn't read {path}"))?;
+        let file = PeFile::parse(&bytes).map_err(anyhow::Error::msg)?;
+        let (major, minor, patch) = file.file_version().map_err(anyhow::Error::msg)?;
+        let supported =
+            if is_supported((major, minor, patch))? { "supported" } else { "not supported" };
+        println!("{path}: {major}.{minor:02}.{patch} ({supported})");
+    }
+
+    Ok(())
+}
+
+/// Whether the compiled-in tables have the base addresses for `version`.
+fn is_supported((major, minor, patch): (u32, u32, u32)) -> Result<bool> {
+    let base_addresses =
+        fs::read_to_string(project_root().join("lib/libeldenring/src/codegen/base_addresses.rs"))?;
+    Ok(base_addresses.contains(&format!("BASE_ADDRESSES_{major}_{minor:02}_{patch}:")))
+}
+
 fn run() -> Result<()> {
     let runtime_config_filename = RUNTIME_CONFIG_FILENAME.get().expect("Could not read runtime config filename");
     let status = cargo_command("build")
@@ -66,6 +96,9 @@ fn run() -> Result<()> {
         project_root().join(runtime_config_filename),
         target_path("debug").join(runtime_config_filename),
     )?;
+    if let Some(pack) = address_pack() {
+        fs::copy(pack, target_path("debug").join(ADDRESS_PACK_FILE_NAME))?;
+    }
 
     let dll_path = target_path("debug").join("libjdsd_er_practice_tool.dll").canonicalize()?;
 
@@ -76,12 +109,15 @@ fn run() -> Result<()> {
 
 fn dist() -> Result<()> {
     let runtime_config_filename = RUNTIME_CONFIG_FILENAME.get().expect("Could not read runtime config filename");
-    Distribution::new("jdsd_er_practice_tool.zip")
+    let mut dist = Distribution::new("jdsd_er_practice_tool.zip")
         .with_artifact("libjdsd_er_practice_tool.dll", "jdsd_er_practice_tool.dll")
         .with_artifact("jdsd_er_practice_tool.exe", "jdsd_er_practice_tool.exe")
         .with_file("lib/data/RELEASE-README.txt", "README.txt")
-        .with_file(runtime_config_filename, runtime_config_filename)
-        .build(&["--locked", "--release", "--workspace", "--exclude", "xtask"])
+        .with_file(runtime_config_filename, runtime_config_filename);
+    if let Some(pack) = address_pack() {
+        dist = dist.with_file(pack, ADDRESS_PACK_FILE_NAME);
+    }
+    dist.build(&["--locked", "--release", "--workspace", "--exclude", "xtask"])
 }
 
 fn install() -> Result<()> {
@@ -95,24 +131,37 @@ fn install() -> Result<()> {
         bail!("cargo build failed");
     }
 
-    FileInstall::new()
+    let mut install = FileInstall::new()
         .with_file(target_path("release").join("libjdsd_er_practice_tool.dll"), "dinput8.dll")
-        .with_file(project_root().join(runtime_config_filename), runtime_config_filename)
-        .install("ER_PATH")?;
+        .with_file(project_root().join(runtime_config_filename), runtime_config_filename);
+    if let Some(pack) = address_pack() {
+        install = install.with_file(pack, ADDRESS_PACK_FILE_NAME);
+    }
+    install.install("ER_PATH")?;
 
     Ok(())
 }
 
 fn uninstall() -> Result<()> {
     let runtime_config_filename = RUNTIME_CONFIG_FILENAME.get().expect("Could not read runtime config filename");
-    FileInstall::new()
+    let mut install = FileInstall::new()
         .with_file(target_path("release").join("libjdsd_er_practice_tool.dll"), "dinput8.dll")
-        .with_file(project_root().join(runtime_config_filename), runtime_config_filename)
-        .uninstall("ER_PATH")?;
+        .with_file(project_root().join(runtime_config_filename), runtime_config_filename);
+    if let Some(pack) = address_pack() {
+        install = install.with_file(pack, ADDRESS_PACK_FILE_NAME);
+    }
+    install.uninstall("ER_PATH")?;
 
     Ok(())
 }
 
+/// The address pack written by `cargo xtask codegen`, if it was generated.
+/// Until then there is nothing to ship.
+fn address_pack() -> Option<PathBuf> {
+    let path = project_root().join("lib/data").join(ADDRESS_PACK_FILE_NAME);
+    path.exists().then_some(path)
+}
+
 fn inject<S: AsRef<OsStr>>(args: impl Iterator<Item = S>) -> Result<()> {
     cargo_command("build").args(["--release", "--bin", "inject"]).status().context("cargo")?;
 